@@ -0,0 +1,261 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::Path;
+use std::{fs, io};
+
+/// Known answers for the real inputs, keyed by day and part.
+///
+/// Stored as a small subset of TOML, one table per day:
+///
+/// ```toml
+/// [day1]
+/// part1 = "75622"
+/// part2 = "213159"
+/// ```
+///
+/// Example answers can also be given for a particular file, for when a day
+/// is run against an example other than its usual one:
+///
+/// ```toml
+/// [day9."day9_part2.txt"]
+/// part1 = "88"
+/// ```
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct Answers {
+    answers: BTreeMap<(u8, u8), String>,
+    /// Keyed by day, file and part
+    variants: BTreeMap<(u8, String, u8), String>,
+}
+
+impl Answers {
+    /// Read the answers file. A missing file is treated as having no answers.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = match fs::read_to_string(path) {
+            Ok(x) => x,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+        return contents.parse().map_err(|e: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), e),
+            )
+        });
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        return fs::write(path, self.to_string());
+    }
+
+    pub fn get(&self, day: u8, part: u8) -> Option<&str> {
+        return self.answers.get(&(day, part)).map(|x| x.as_str());
+    }
+
+    pub fn insert(&mut self, day: u8, part: u8, answer: &str) {
+        self.answers.insert((day, part), answer.to_owned());
+    }
+
+    /// The answer for one part run against `file` in place of the day's
+    /// usual example
+    pub fn get_variant(&self, day: u8, file: &str, part: u8) -> Option<&str> {
+        let key = (day, file.to_owned(), part);
+        return self.variants.get(&key).map(|x| x.as_str());
+    }
+
+    pub fn insert_variant(&mut self, day: u8, file: &str, part: u8, answer: &str) {
+        self.variants
+            .insert((day, file.to_owned(), part), answer.to_owned());
+    }
+
+    /// Every answer given for a particular file, as day, file, part and
+    /// answer
+    pub fn variants(&self) -> impl Iterator<Item = (u8, &str, u8, &str)> {
+        return self
+            .variants
+            .iter()
+            .map(|((day, file, part), answer)| (*day, file.as_str(), *part, answer.as_str()));
+    }
+
+    /// The expected answer for one part run against the example at `path`
+    /// in `dir`. The day's own answers only count for its usual example, and
+    /// any other file needs answers of its own.
+    pub fn for_example(&self, dir: &Path, day: u8, part: u8, path: &Path) -> Option<&str> {
+        if path == crate::example_path(dir, day, part) {
+            return self.get(day, part);
+        }
+        let file = path.strip_prefix(dir).unwrap_or(path).to_string_lossy();
+        return self.get_variant(day, &file, part);
+    }
+}
+
+fn unquote(s: &str) -> Option<String> {
+    let inner = s.strip_prefix('"')?.strip_suffix('"')?;
+    let mut out = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                'n' => out.push('\n'),
+                'r' => out.push('\r'),
+                't' => out.push('\t'),
+                '"' => out.push('"'),
+                '\\' => out.push('\\'),
+                _ => return None,
+            },
+            '"' => return None,
+            c => out.push(c),
+        }
+    }
+    return Some(out);
+}
+
+fn quote(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t");
+    return format!("\"{}\"", escaped);
+}
+
+impl std::str::FromStr for Answers {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut answers = Self::default();
+        let mut table = None;
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            let error = |msg: &str| format!("line {}: {}: `{}`", i + 1, msg, line);
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix("[day").and_then(|l| l.strip_suffix(']')) {
+                let (day, file) = match header.split_once('.') {
+                    Some((day, file)) => {
+                        let file =
+                            unquote(file.trim()).ok_or_else(|| error("invalid file name"))?;
+                        (day, Some(file))
+                    }
+                    None => (header, None),
+                };
+                let day = day.trim().parse::<u8>().map_err(|_| error("invalid day"))?;
+                table = Some((day, file));
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error("expected `partN = \"answer\"`"))?;
+            let part = key
+                .trim()
+                .strip_prefix("part")
+                .and_then(|p| p.parse::<u8>().ok())
+                .ok_or_else(|| error("expected `part1` or `part2`"))?;
+            let value = unquote(value.trim()).ok_or_else(|| error("invalid string"))?;
+            match table
+                .as_ref()
+                .ok_or_else(|| error("answer given before any `[dayN]`"))?
+            {
+                (day, Some(file)) => answers.insert_variant(*day, file, part, &value),
+                (day, None) => answers.insert(*day, part, &value),
+            }
+        }
+        return Ok(answers);
+    }
+}
+
+impl fmt::Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // each day's own answers come first, then any for particular files
+        let days: BTreeSet<u8> = self
+            .answers
+            .keys()
+            .map(|(day, _part)| *day)
+            .chain(self.variants.keys().map(|(day, _file, _part)| *day))
+            .collect();
+        let mut first = true;
+        let mut table = |f: &mut fmt::Formatter, header: String| {
+            if !first {
+                writeln!(f)?;
+            }
+            first = false;
+            return writeln!(f, "{}", header);
+        };
+        for day in days {
+            let own = self.answers.range((day, 0)..=(day, u8::MAX));
+            let mut own = own.peekable();
+            if own.peek().is_some() {
+                table(f, format!("[day{}]", day))?;
+            }
+            for ((_day, part), answer) in own {
+                writeln!(f, "part{} = {}", part, quote(answer))?;
+            }
+            let mut last_file = None;
+            for (d, file, part, answer) in self.variants() {
+                if d != day {
+                    continue;
+                }
+                if last_file != Some(file) {
+                    table(f, format!("[day{}.{}]", day, quote(file)))?;
+                    last_file = Some(file);
+                }
+                writeln!(f, "part{} = {}", part, quote(answer))?;
+            }
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "# comments are ignored
+[day1]
+part1 = \"24000\"
+part2 = \"45000\"
+
+[day10]
+part2 = \"\\n#.\\n.#\"
+
+[day10.\"big.txt\"]
+part1 = \"1\"
+";
+
+    #[test]
+    fn parses_answers_by_day_and_part() {
+        let answers: Answers = EXAMPLE.parse().unwrap();
+        assert_eq!(answers.get(1, 1), Some("24000"));
+        assert_eq!(answers.get(1, 2), Some("45000"));
+        assert_eq!(answers.get(10, 2), Some("\n#.\n.#"));
+        assert_eq!(answers.get(10, 1), None);
+        assert_eq!(answers.get_variant(10, "big.txt", 1), Some("1"));
+        assert_eq!(answers.get_variant(10, "big.txt", 2), None);
+    }
+
+    #[test]
+    fn written_answers_read_back_the_same() {
+        let mut answers = Answers::default();
+        answers.insert(10, 2, "\n#.\n.#");
+        answers.insert(1, 2, "say \"hi\"\\");
+        answers.insert(1, 1, "24000");
+        answers.insert_variant(10, "big.txt", 1, "1");
+        let written = answers.to_string();
+        assert_eq!(
+            written,
+            "[day1]\npart1 = \"24000\"\npart2 = \"say \\\"hi\\\"\\\\\"\n\n[day10]\npart2 = \"\\n#.\\n.#\"\n\n[day10.\"big.txt\"]\npart1 = \"1\"\n"
+        );
+        assert_eq!(written.parse::<Answers>(), Ok(answers));
+    }
+
+    #[test]
+    fn reports_line_of_bad_entry() {
+        let result = "[day1]\npart1 = 24000\n".parse::<Answers>();
+        assert_eq!(
+            result,
+            Err("line 2: invalid string: `part1 = 24000`".to_owned())
+        );
+    }
+}
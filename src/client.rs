@@ -0,0 +1,297 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+use std::{fs, io};
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com/2022";
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// The server answered, but not with what was asked for
+    Http {
+        status: u16,
+        message: String,
+    },
+    /// The server couldn't be reached at all
+    Transport(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Self::Http { status, message } => write!(f, "server returned {}: {}", status, message),
+            Self::Transport(message) => write!(f, "request failed: {}", message),
+            Self::Io(e) => write!(f, "{}", e),
+        };
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<io::Error> for ClientError {
+    fn from(e: io::Error) -> Self {
+        return Self::Io(e);
+    }
+}
+
+impl From<ureq::Error> for ClientError {
+    fn from(e: ureq::Error) -> Self {
+        return match e {
+            ureq::Error::Status(status, response) => {
+                let body = response.into_string().unwrap_or_default();
+                // only the first line, error pages can be a whole HTML document
+                let message = body.lines().next().unwrap_or_default().trim().to_owned();
+                Self::Http { status, message }
+            }
+            ureq::Error::Transport(t) => Self::Transport(t.to_string()),
+        };
+    }
+}
+
+/// Talks to the Advent of Code website, or anything at `base_url` that
+/// behaves like it. Requests are spaced at least `delay` apart so running a
+/// command over every day doesn't hammer the server.
+pub struct Client {
+    base_url: String,
+    session: String,
+    delay: Duration,
+    last_request: Option<Instant>,
+    agent: ureq::Agent,
+}
+
+impl Client {
+    pub fn new(base_url: &str, session: &str, delay: Duration) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout(Duration::from_secs(30))
+            .user_agent(concat!("aoc2022/", env!("CARGO_PKG_VERSION")))
+            .build();
+        return Self {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            session: session.to_owned(),
+            delay,
+            last_request: None,
+            agent,
+        };
+    }
+
+    fn wait_turn(&mut self) {
+        if let Some(last) = self.last_request {
+            let elapsed = last.elapsed();
+            if elapsed < self.delay {
+                thread::sleep(self.delay - elapsed);
+            }
+        }
+        self.last_request = Some(Instant::now());
+    }
+
+    fn cookie(&self) -> String {
+        return format!("session={}", self.session);
+    }
+
+    /// Download the puzzle input for a day.
+    pub fn get_input(&mut self, day: u8) -> Result<String, ClientError> {
+        self.wait_turn();
+        let url = format!("{}/day/{}/input", self.base_url, day);
+        let response = self.agent.get(&url).set("Cookie", &self.cookie()).call()?;
+        return Ok(response.into_string()?);
+    }
+
+    /// Submit an answer, returning the page the server responds with.
+    pub fn post_answer(&mut self, day: u8, part: u8, answer: &str) -> Result<String, ClientError> {
+        self.wait_turn();
+        let url = format!("{}/day/{}/answer", self.base_url, day);
+        let level = part.to_string();
+        let response = self
+            .agent
+            .post(&url)
+            .set("Cookie", &self.cookie())
+            .send_form(&[("level", &level), ("answer", answer)])?;
+        return Ok(response.into_string()?);
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Fetched {
+    Downloaded(PathBuf),
+    /// The file was already there, so nothing was requested
+    AlreadyCached(PathBuf),
+}
+
+/// Make sure `dir` has the input for `day`, downloading it if it's missing.
+/// Inputs never change, so an existing file is never fetched again.
+pub fn fetch(client: &mut Client, dir: &Path, day: u8) -> Result<Fetched, ClientError> {
+    let path = dir.join(format!("day{}.txt", day));
+    if path.exists() {
+        return Ok(Fetched::AlreadyCached(path));
+    }
+    let input = client.get_input(day)?;
+    fs::create_dir_all(dir)?;
+    // written under another name first, so an interrupted download doesn't
+    // leave a partial file that would then count as cached
+    let partial = dir.join(format!(".day{}.txt.partial", day));
+    fs::write(&partial, input)?;
+    fs::rename(&partial, &path)?;
+    return Ok(Fetched::Downloaded(path));
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    /// A request as seen by `FakeServer`.
+    #[derive(Debug, Clone)]
+    pub struct Request {
+        pub method: String,
+        pub path: String,
+        pub cookie: Option<String>,
+        pub body: String,
+        pub at: Instant,
+    }
+
+    /// Stand-in for the website. Answers every request with whatever
+    /// `respond` returns and keeps a log of the requests.
+    pub struct FakeServer {
+        pub url: String,
+        pub requests: Arc<Mutex<Vec<Request>>>,
+    }
+
+    impl FakeServer {
+        pub fn start(respond: impl Fn(&Request) -> (u16, String) + Send + 'static) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}/2022", listener.local_addr().unwrap());
+            let requests = Arc::new(Mutex::new(vec![]));
+            let log = Arc::clone(&requests);
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let mut stream = stream.unwrap();
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let mut words = line.split_whitespace();
+                    let method = words.next().unwrap_or_default().to_owned();
+                    let path = words.next().unwrap_or_default().to_owned();
+                    let mut cookie = None;
+                    let mut length = 0;
+                    loop {
+                        let mut header = String::new();
+                        reader.read_line(&mut header).unwrap();
+                        let header = header.trim_end();
+                        if header.is_empty() {
+                            break;
+                        }
+                        let (name, value) = header.split_once(": ").unwrap();
+                        match name.to_ascii_lowercase().as_str() {
+                            "cookie" => cookie = Some(value.to_owned()),
+                            "content-length" => length = value.parse().unwrap(),
+                            _ => {}
+                        }
+                    }
+                    let mut body = vec![0; length];
+                    reader.read_exact(&mut body).unwrap();
+                    let request = Request {
+                        method,
+                        path,
+                        cookie,
+                        body: String::from_utf8(body).unwrap(),
+                        at: Instant::now(),
+                    };
+                    let (status, body) = respond(&request);
+                    log.lock().unwrap().push(request);
+                    write!(
+                        stream,
+                        "HTTP/1.1 {} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        body.len(),
+                        body
+                    )
+                    .unwrap();
+                }
+            });
+            return Self { url, requests };
+        }
+
+        pub fn requests(&self) -> Vec<Request> {
+            return self.requests.lock().unwrap().clone();
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc2022-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        return dir;
+    }
+
+    #[test]
+    fn fetches_input_with_session_cookie_into_dir() {
+        let server = FakeServer::start(|r| (200, format!("input for {}\n", r.path)));
+        let dir = temp_dir("fetch");
+        let mut client = Client::new(&server.url, "abc123", Duration::ZERO);
+        let result = fetch(&mut client, &dir, 3).unwrap();
+        let path = dir.join("day3.txt");
+        assert_eq!(result, Fetched::Downloaded(path.clone()));
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "input for /2022/day/3/input\n"
+        );
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].cookie.as_deref(), Some("session=abc123"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn cached_inputs_are_not_fetched_again() {
+        let server = FakeServer::start(|_r| (200, "new".to_owned()));
+        let dir = temp_dir("cached");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("day5.txt"), "old").unwrap();
+        let mut client = Client::new(&server.url, "abc123", Duration::ZERO);
+        let result = fetch(&mut client, &dir, 5).unwrap();
+        assert_eq!(result, Fetched::AlreadyCached(dir.join("day5.txt")));
+        assert_eq!(fs::read_to_string(dir.join("day5.txt")).unwrap(), "old");
+        assert!(server.requests().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_downloads_leave_nothing_behind() {
+        let server = FakeServer::start(|_r| {
+            (
+                400,
+                "Puzzle inputs differ by user.  Please log in.\n<p>more</p>".to_owned(),
+            )
+        });
+        let dir = temp_dir("failed");
+        let mut client = Client::new(&server.url, "expired", Duration::ZERO);
+        match fetch(&mut client, &dir, 1) {
+            Err(ClientError::Http { status, message }) => {
+                assert_eq!(status, 400);
+                assert_eq!(message, "Puzzle inputs differ by user.  Please log in.");
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(!dir.join("day1.txt").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn requests_are_spaced_out_by_the_delay() {
+        let server = FakeServer::start(|_r| (200, "x".to_owned()));
+        let delay = Duration::from_millis(100);
+        let mut client = Client::new(&server.url, "abc123", delay);
+        for day in 1..=3 {
+            client.get_input(day).unwrap();
+        }
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        for pair in requests.windows(2) {
+            assert!(pair[1].at - pair[0].at >= delay - Duration::from_millis(5));
+        }
+    }
+}
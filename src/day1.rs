@@ -1,18 +1,288 @@
-use crate::utils::divide_iterator_on_predicate;
+use crate::error::{Error, Result};
+use crate::solution::{Registered, Solution};
+use crate::utils::SplitWhen;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
+use std::io::BufRead;
 
-fn get_totals(file_contents: &str) -> Vec<u64> {
-    let entries: Vec<Vec<&str>> = divide_iterator_on_predicate(file_contents.lines(), |&s| s == "");
-    let numbers = entries.iter().map(|v| v.iter().map(|s| s.parse::<u64>().expect("invalid number")));
-    return numbers.map(|v| v.sum::<u64>()).collect();
+/// What one elf is carrying
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub struct Elf {
+    pub calories: u64,
+    pub items: usize,
 }
 
-pub fn part_1(file_contents: &str) -> String {
-    return get_totals(file_contents).iter().max().unwrap().to_string();
+/// Read the elves from `reader` one at a time, so the input never has to be
+/// in memory all at once
+pub fn elves(reader: impl BufRead) -> impl Iterator<Item = Result<Elf>> {
+    let lines = reader.lines().enumerate().map(|(i, line)| (i + 1, line));
+    return lines
+        .split_when(|(_i, line)| line.as_ref().is_ok_and(|l| l.is_empty()))
+        .map(|group| {
+            let mut elf = Elf::default();
+            for (i, line) in group {
+                let line = line.map_err(|e| {
+                    Error::parse(Day1::DAY, i, 1, "", &format!("couldn't read line: {}", e))
+                })?;
+                let calories = line
+                    .parse::<u64>()
+                    .map_err(|_| Error::parse(Day1::DAY, i, 1, &line, "invalid number"))?;
+                elf.calories = elf.calories.checked_add(calories).ok_or_else(|| {
+                    Error::parse(Day1::DAY, i, 1, &line, "elf's calorie total out of range")
+                })?;
+                elf.items += 1;
+            }
+            Ok(elf)
+        });
 }
 
-pub fn part_2(file_contents: &str) -> String {
-    let mut vec = get_totals(file_contents);
-    vec.sort();
-    vec.reverse();
-    return vec.iter().take(3).sum::<u64>().to_string();
+/// The `k` largest totals pushed so far, never holding more than `k` of them
+pub struct TopK {
+    k: usize,
+    heap: BinaryHeap<Reverse<u64>>,
+}
+
+impl TopK {
+    pub fn new(k: usize) -> Self {
+        return Self {
+            k,
+            heap: BinaryHeap::new(),
+        };
+    }
+
+    pub fn push(&mut self, total: u64) {
+        self.heap.push(Reverse(total));
+        if self.heap.len() > self.k {
+            self.heap.pop();
+        }
+    }
+
+    /// Largest first
+    pub fn into_vec(self) -> Vec<u64> {
+        let sorted = self.heap.into_sorted_vec();
+        return sorted.into_iter().map(|r| r.0).collect();
+    }
+}
+
+/// The `k` largest totals, largest first
+pub fn top_k(totals: impl IntoIterator<Item = u64>, k: usize) -> Vec<u64> {
+    let mut top = TopK::new(k);
+    for total in totals {
+        top.push(total);
+    }
+    return top.into_vec();
+}
+
+/// The middle of some totals, or the mean of the middle two
+fn median(totals: &mut [u64]) -> Option<f64> {
+    if totals.is_empty() {
+        return None;
+    }
+    let n = totals.len();
+    let (lower, middle, _upper) = totals.select_nth_unstable(n / 2);
+    let middle = *middle as f64;
+    return match lower.iter().max() {
+        Some(m) if n.is_multiple_of(2) => Some((*m as f64 + middle) / 2.0),
+        _ => Some(middle),
+    };
+}
+
+/// The largest calorie totals, and how the elves compare
+#[derive(PartialEq, Debug, Clone)]
+pub struct Summary {
+    /// Largest first
+    pub top: Vec<u64>,
+    pub elves: usize,
+    pub calories: u64,
+    pub items: usize,
+    /// Only worked out when asked for, see `with_median`
+    pub median: Option<f64>,
+    pub fewest_items: usize,
+    pub most_items: usize,
+}
+
+impl Summary {
+    /// The elves are read one at a time and only the `k` largest totals are
+    /// kept, so memory doesn't grow with the number of elves.
+    pub fn from_elves(elves: impl Iterator<Item = Result<Elf>>, k: usize) -> Result<Self> {
+        return Self::summarise(elves, k, None);
+    }
+
+    /// Like `from_elves`, but with the median too. That needs every elf's
+    /// total, so memory grows with the number of elves.
+    pub fn with_median(elves: impl Iterator<Item = Result<Elf>>, k: usize) -> Result<Self> {
+        let mut totals = vec![];
+        let mut summary = Self::summarise(elves, k, Some(&mut totals))?;
+        summary.median = median(&mut totals);
+        return Ok(summary);
+    }
+
+    fn summarise(
+        elves: impl Iterator<Item = Result<Elf>>,
+        k: usize,
+        mut totals: Option<&mut Vec<u64>>,
+    ) -> Result<Self> {
+        let mut top = TopK::new(k);
+        let (mut count, mut calories) = (0, 0u64);
+        let (mut items, mut fewest_items, mut most_items) = (0, usize::MAX, 0);
+        for elf in elves {
+            let elf = elf?;
+            top.push(elf.calories);
+            if let Some(totals) = totals.as_mut() {
+                totals.push(elf.calories);
+            }
+            count += 1;
+            calories = calories
+                .checked_add(elf.calories)
+                .ok_or_else(|| Error::solve(Day1::DAY, "total calories out of range"))?;
+            items += elf.items;
+            fewest_items = fewest_items.min(elf.items);
+            most_items = most_items.max(elf.items);
+        }
+        if count == 0 {
+            return Err(Error::solve(Day1::DAY, "no elves in input"));
+        }
+        return Ok(Self {
+            top: top.into_vec(),
+            elves: count,
+            calories,
+            items,
+            median: None,
+            fewest_items,
+            most_items,
+        });
+    }
+
+    pub fn from_reader(reader: impl BufRead, k: usize) -> Result<Self> {
+        return Self::from_elves(elves(reader), k);
+    }
+
+    pub fn mean(&self) -> f64 {
+        return self.calories as f64 / self.elves as f64;
+    }
+
+    pub fn mean_items(&self) -> f64 {
+        return self.items as f64 / self.elves as f64;
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let top: Vec<String> = self.top.iter().map(|t| t.to_string()).collect();
+        writeln!(
+            f,
+            "Top {} of {} elves: {} ({} in all)",
+            self.top.len(),
+            self.elves,
+            top.join(", "),
+            self.top.iter().sum::<u64>()
+        )?;
+        match self.median {
+            Some(median) => writeln!(
+                f,
+                "Calories per elf: mean {:.1}, median {:.1}",
+                self.mean(),
+                median
+            )?,
+            None => writeln!(f, "Calories per elf: mean {:.1}", self.mean())?,
+        }
+        return writeln!(
+            f,
+            "Items per elf: mean {:.1}, fewest {}, most {}",
+            self.mean_items(),
+            self.fewest_items,
+            self.most_items
+        );
+    }
+}
+
+pub fn parse(file_contents: &str) -> Result<Vec<u64>> {
+    return elves(file_contents.as_bytes())
+        .map(|elf| Ok(elf?.calories))
+        .collect();
+}
+
+pub fn part_1(totals: &[u64]) -> Result<String> {
+    return top_k(totals.iter().copied(), 1)
+        .first()
+        .map(|x| x.to_string())
+        .ok_or_else(|| Error::solve(Day1::DAY, "no elves in input"));
+}
+
+pub fn part_2(totals: &[u64]) -> Result<String> {
+    return Ok(top_k(totals.iter().copied(), 3)
+        .iter()
+        .sum::<u64>()
+        .to_string());
+}
+
+pub struct Day1;
+
+pub static SOLUTION: Registered<Day1> = Registered::new();
+
+impl Solution for Day1 {
+    type Input = Vec<u64>;
+
+    const DAY: u8 = 1;
+    const TITLE: &'static str = "Calorie Counting";
+
+    fn parse(file_contents: &str) -> Result<Self::Input> {
+        return parse(file_contents);
+    }
+
+    fn part_1(input: &Self::Input) -> Result<String> {
+        return part_1(input);
+    }
+
+    fn part_2(input: &Self::Input) -> Result<String> {
+        return part_2(input);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = include_str!("../tests/example_files/day1.txt");
+
+    #[test]
+    fn largest_totals_come_first() {
+        assert_eq!(top_k([3, 9, 1, 7, 9], 3), vec![9, 9, 7]);
+        assert_eq!(top_k([3, 1], 5), vec![3, 1]);
+        assert_eq!(top_k([3, 1], 0), vec![]);
+    }
+
+    #[test]
+    fn elves_are_summarised() {
+        let summary = Summary::from_reader(EXAMPLE_INPUT.as_bytes(), 2).unwrap();
+        assert_eq!(summary.top, vec![24000, 11000]);
+        assert_eq!((summary.elves, summary.items), (5, 10));
+        assert_eq!(summary.mean(), 11000.0);
+        assert_eq!(summary.median, None);
+        assert_eq!((summary.fewest_items, summary.most_items), (1, 3));
+
+        let odd = Summary::with_median(elves(EXAMPLE_INPUT.as_bytes()), 2).unwrap();
+        assert_eq!(odd.median, Some(10000.0));
+        assert_eq!(odd.top, summary.top);
+        let even = elves("1\n\n\n2\n3\n\n10\n\n4\n".as_bytes());
+        assert_eq!(Summary::with_median(even, 1).unwrap().median, Some(4.5));
+        assert_eq!(
+            Summary::from_reader("".as_bytes(), 3),
+            Err(Error::solve(1, "no elves in input"))
+        );
+    }
+
+    #[test]
+    fn overflowing_totals_are_errors() {
+        let big = u64::MAX.to_string();
+        let input = format!("{}\n1\n", big);
+        let expected = Error::parse(1, 2, 1, "1", "elf's calorie total out of range");
+        assert_eq!(elves(input.as_bytes()).next(), Some(Err(expected)));
+        let input = format!("{}\n\n1\n", big);
+        assert_eq!(
+            Summary::from_reader(input.as_bytes(), 1),
+            Err(Error::solve(1, "total calories out of range"))
+        );
+    }
 }
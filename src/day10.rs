@@ -1,13 +1,17 @@
+use crate::error::Result;
+use crate::export::{Image, Recorder, Rgb};
+use crate::parse::{literal, parse_line, signed, Parser};
+use crate::solution::{Registered, Solution};
+use crate::utils::{Grid, Point};
 use itertools::*;
-use regex::Regex;
 use std::collections::HashMap;
 
 const SCREEN_SIZE: usize = 40;
 
 #[derive(Hash, PartialEq, Eq, Debug)]
 enum Operation {
-    NOOP,
-    ADDX,
+    Noop,
+    Addx,
 }
 
 struct Instruction {
@@ -16,32 +20,31 @@ struct Instruction {
 }
 
 lazy_static! {
-    static ref RE: Regex = Regex::new(r"\w+ (-?\d+)").expect("invalid regex");
     static ref OP_TIMES: HashMap<Operation, usize> =
-        HashMap::from([(Operation::NOOP, 0), (Operation::ADDX, 1)]);
+        HashMap::from([(Operation::Noop, 0), (Operation::Addx, 1)]);
 }
 
-fn parse_cmd(line: &str) -> Instruction {
-    let caps = RE.captures(line);
-    return match caps {
-        Some(x) => Instruction {
-            op: Operation::ADDX,
-            number: x.get(1).unwrap().as_str().parse::<isize>().ok(),
-        },
-        None => Instruction {
-            op: Operation::NOOP,
-            number: None,
-        },
-    };
+fn instruction() -> impl Parser<Instruction> {
+    let noop = literal("noop").map(|_u| Instruction {
+        op: Operation::Noop,
+        number: None,
+    });
+    let addx = literal("addx ").ignore_then(signed()).map(|n| Instruction {
+        op: Operation::Addx,
+        number: Some(n),
+    });
+    return noop.or(addx).expecting("`noop` or `addx N`");
 }
 
-fn register_through_time(file_contents: &str) -> Vec<isize> {
+/// The value of the X register during each cycle
+pub fn parse(file_contents: &str) -> Result<Vec<isize>> {
     // 3* in case every operation is an add, largest possible size
     let mut register: Vec<isize> = Vec::with_capacity(file_contents.lines().count() * 3);
     register.push(1);
-    for line in file_contents.lines() {
-        let top = register.last().unwrap().clone();
-        let cmd = parse_cmd(line);
+    let instruction = instruction();
+    for (i, line) in file_contents.lines().enumerate() {
+        let top = *register.last().unwrap();
+        let cmd = parse_line(Day10::DAY, i + 1, line, &instruction)?;
         for _i in 0..OP_TIMES[&cmd.op] {
             register.push(top);
         }
@@ -50,19 +53,18 @@ fn register_through_time(file_contents: &str) -> Vec<isize> {
             None => register.push(top),
         }
     }
-    return register;
+    return Ok(register);
 }
 
-pub fn part_1(file_contents: &str) -> String {
-    let register = register_through_time(file_contents);
-    return register
+pub fn part_1(register: &[isize]) -> Result<String> {
+    return Ok(register
         .iter()
         .enumerate()
         .skip(19)
         .step_by(40)
         .map(|(i, acc)| ((i + 1) as isize) * acc)
         .sum::<isize>()
-        .to_string();
+        .to_string());
 }
 
 fn render_screen(screen: &[bool]) -> String {
@@ -75,18 +77,108 @@ fn render_screen(screen: &[bool]) -> String {
         .join("\n");
 }
 
-pub fn part_2(file_contents: &str) -> String {
-    let register = register_through_time(file_contents);
+/// Whether the pixel drawn during cycle `i` is lit
+fn is_lit(sprite_pos: isize, i: usize) -> bool {
+    return (sprite_pos - ((i % SCREEN_SIZE) as isize)).abs() <= 1;
+}
+
+pub fn part_2(register: &[isize]) -> Result<String> {
     let mut screen: Vec<bool> = vec![false; register.len()];
     for (i, sprite_pos) in register.iter().enumerate() {
-        let trial = ((*sprite_pos as isize) - ((i % SCREEN_SIZE) as isize)).abs();
-        if trial <= 1 {
+        if is_lit(*sprite_pos, i) {
             screen[i] = true;
         }
     }
     // not sure why but we get a spurious extra line with a single entry if we
     // don't cut off the last value
-    return "\n".to_owned() + &render_screen(&screen[0..screen.len() - 1]);
+    return Ok("\n".to_owned() + &render_screen(&screen[0..screen.len() - 1]));
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+enum Pixel {
+    Off,
+    On,
+    Sprite,
+    Beam,
+}
+
+impl Pixel {
+    fn colour(&self) -> Rgb {
+        return match self {
+            Self::Off => [10, 30, 10],
+            Self::On => [90, 255, 90],
+            Self::Sprite => [40, 80, 40],
+            Self::Beam => [255, 255, 255],
+        };
+    }
+}
+
+/// The screen so far, with the sprite and the beam drawn over it while it's
+/// still being drawn
+fn picture(screen: &[bool], rows: usize, drawing: Option<(usize, isize)>) -> Image {
+    let mut pixels = Grid::new(
+        Point(0, 0),
+        Point(SCREEN_SIZE as isize - 1, rows as isize - 1),
+        Pixel::Off,
+    );
+    for (i, on) in screen.iter().enumerate() {
+        if *on {
+            pixels[Point::from_index(i, SCREEN_SIZE)] = Pixel::On;
+        }
+    }
+    if let Some((i, sprite_pos)) = drawing {
+        let row = i / SCREEN_SIZE;
+        for x in (sprite_pos - 1..=sprite_pos + 1).filter(|x| (0..SCREEN_SIZE as isize).contains(x))
+        {
+            if !screen[row * SCREEN_SIZE + x as usize] {
+                pixels[Point(x, row as isize)] = Pixel::Sprite;
+            }
+        }
+        pixels[Point::from_index(i, SCREEN_SIZE)] = Pixel::Beam;
+    }
+    return Image::from_grid(&pixels, Pixel::colour);
+}
+
+/// Part 2, one frame per cycle.
+fn export(register: &[isize], images: &mut Recorder) -> Result<String> {
+    // the last value is after the final cycle, so nothing is drawn with it
+    let cycles = register.len().saturating_sub(1);
+    let rows = cycles.div_ceil(SCREEN_SIZE).max(1);
+    let mut screen = vec![false; rows * SCREEN_SIZE];
+    for (i, sprite_pos) in register.iter().take(cycles).enumerate() {
+        screen[i] = is_lit(*sprite_pos, i);
+        images.step(|| picture(&screen, rows, Some((i, *sprite_pos))));
+    }
+    images.last(picture(&screen, rows, None));
+    return part_2(register);
+}
+
+pub struct Day10;
+
+pub static SOLUTION: Registered<Day10> = Registered::new();
+
+impl Solution for Day10 {
+    type Input = Vec<isize>;
+
+    const DAY: u8 = 10;
+    const TITLE: &'static str = "Cathode-Ray Tube";
+
+    fn parse(file_contents: &str) -> Result<Self::Input> {
+        return parse(file_contents);
+    }
+
+    fn part_1(input: &Self::Input) -> Result<String> {
+        return part_1(input);
+    }
+
+    fn part_2(input: &Self::Input) -> Result<String> {
+        return part_2(input);
+    }
+
+    fn export(input: &Self::Input, part: u8, images: &mut Recorder) -> Option<Result<String>> {
+        // part 1 doesn't draw anything
+        return (part == 2).then(|| export(input, images));
+    }
 }
 
 #[cfg(test)]
@@ -98,6 +190,6 @@ mod tests {
         // remember to include the zeroth step
         let expected: Vec<isize> = vec![1, 1, 1, 4, 4, -1];
         let input = "noop\naddx 3\naddx -5";
-        assert_eq!(register_through_time(input), expected);
+        assert_eq!(parse(input), Ok(expected));
     }
 }
@@ -1,35 +1,21 @@
-use crate::utils;
-use regex::Regex;
+use crate::error::{Error, Result};
+use crate::parse::{blocks, literal, one_of, parse_line, spaces, unsigned, Parser};
+use crate::solution::{Registered, Solution};
 use std::collections::VecDeque;
 
-lazy_static! {
-    static ref RE_MONKEY: Regex = Regex::new(r"Monkey (\d+)").expect("invalid regex");
-    static ref RE_ITEMS: Regex = Regex::new(r"Starting items: (.+)").expect("invalid regex");
-    static ref RE_OPERATION: Regex =
-        Regex::new(r"Operation: new = old (.) (.+)").expect("invalid regex");
-    static ref RE_TEST: Regex = Regex::new(r"Test: divisible by (\d+)").expect("invalid regex");
-    static ref RE_TRUE_FALSE: Regex =
-        Regex::new(r"If (true|false): throw to monkey (\d+)").expect("invalid regex");
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
 }
 
-/// Convenience function to get a number out of a regex that we know is in
-/// there
-fn get_number_from_regex(re: &Regex, text: &str, match_pos: usize) -> usize {
-    return re
-        .captures(text)
-        .unwrap()
-        .get(match_pos)
-        .unwrap()
-        .as_str()
-        .parse::<usize>()
-        .unwrap();
-}
-
-#[derive(PartialEq, Eq, Debug)]
-struct Monkey<'a> {
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Monkey {
     number: usize,
     items: VecDeque<usize>,
-    operator: &'a str,
+    operator: Operator,
     rhs: Option<usize>,
     modulus: usize,
     true_target: usize,
@@ -37,58 +23,71 @@ struct Monkey<'a> {
     inspections: usize,
 }
 
-impl<'a> Monkey<'a> {
-    fn new(text: &Vec<&'a str>) -> Self {
-        let (operator, rhs) = Self::get_operation(text[2]);
-        return Monkey {
-            number: get_number_from_regex(&RE_MONKEY, text[0], 1) as usize,
-            items: Self::get_held_items(text[1]),
+/// Read one line of a monkey's definition, which may be indented
+fn monkey_line<T>(line: (usize, &str), parser: impl Parser<T>) -> Result<T> {
+    let (line_no, text) = line;
+    return parse_line(Day11::DAY, line_no, text, &spaces().ignore_then(parser));
+}
+
+impl Monkey {
+    /// Build a monkey from its block of input lines, each paired with its
+    /// line number
+    fn new(text: &[(usize, &str)]) -> Result<Self> {
+        if text.len() < 6 {
+            let (i, line) = text.last().copied().unwrap_or((1, ""));
+            return Err(Error::parse(
+                Day11::DAY,
+                i,
+                1,
+                line,
+                "incomplete monkey definition",
+            ));
+        }
+        let number = literal("Monkey ")
+            .ignore_then(unsigned())
+            .then_ignore(literal(":"));
+        let items = literal("Starting items: ").ignore_then(unsigned().separated_by(literal(", ")));
+        let operator = one_of([
+            ("+", Operator::Add),
+            ("-", Operator::Subtract),
+            ("*", Operator::Multiply),
+            ("/", Operator::Divide),
+        ]);
+        let rhs = literal("old").map(|_u| None).or(unsigned().map(Some));
+        let operation = literal("Operation: new = old ")
+            .ignore_then(operator)
+            .then_ignore(literal(" "))
+            .then(rhs)
+            .try_map(|operation| match operation {
+                (Operator::Divide, Some(0)) => Err("division by 0"),
+                x => Ok(x),
+            });
+        let test = literal("Test: divisible by ").ignore_then(unsigned().try_map(|n| match n {
+            0 => Err("can't test divisibility by 0"),
+            n => Ok(n),
+        }));
+        let target = |outcome| literal(outcome).ignore_then(unsigned());
+
+        let (operator, rhs) = monkey_line(text[2], operation)?;
+        return Ok(Monkey {
+            number: monkey_line(text[0], number)?,
+            items: VecDeque::from(monkey_line(text[1], items)?),
             operator,
             rhs,
-            modulus: get_number_from_regex(&RE_TEST, text[3], 1),
-            true_target: get_number_from_regex(&RE_TRUE_FALSE, text[4], 2) as usize,
-            false_target: get_number_from_regex(&RE_TRUE_FALSE, text[5], 2) as usize,
+            modulus: monkey_line(text[3], test)?,
+            true_target: monkey_line(text[4], target("If true: throw to monkey "))?,
+            false_target: monkey_line(text[5], target("If false: throw to monkey "))?,
             inspections: 0,
-        };
+        });
     }
 
-    fn get_held_items(text: &str) -> VecDeque<usize> {
-        let num_str = RE_ITEMS.captures(text).unwrap().get(1).unwrap().as_str();
-        return num_str
-            .split(", ")
-            .map(|s| s.parse::<usize>().unwrap())
-            .collect();
-    }
-
-    fn get_operation(text: &str) -> (&str, Option<usize>) {
-        let caps = RE_OPERATION.captures(text).unwrap();
-        let possible_number = caps.get(2).unwrap().as_str();
-        let validated_number: Option<usize> = if possible_number == "old" {
-            None
-        } else {
-            Some(possible_number.parse::<usize>().unwrap())
-        };
-
-        let operator = caps.get(1).unwrap().as_str();
-        return (operator, validated_number);
-    }
-
-    fn perform_op(item: usize, operator: &str, rhs: Option<usize>) -> usize {
-        return match rhs {
-            Some(x) => match operator {
-                "+" => item + x,
-                "-" => item - x,
-                "*" => item * x,
-                "/" => item / x,
-                _ => panic!("invalid operator"),
-            },
-            None => match operator {
-                "+" => item + item,
-                "-" => item - item,
-                "*" => item * item,
-                "/" => item / item,
-                _ => panic!("invalid operator"),
-            },
+    fn perform_op(item: usize, operator: Operator, rhs: Option<usize>) -> Option<usize> {
+        let rhs = rhs.unwrap_or(item);
+        return match operator {
+            Operator::Add => item.checked_add(rhs),
+            Operator::Subtract => item.checked_sub(rhs),
+            Operator::Multiply => item.checked_mul(rhs),
+            Operator::Divide => item.checked_div(rhs),
         };
     }
 
@@ -96,36 +95,43 @@ impl<'a> Monkey<'a> {
         &mut self,
         worry_reduction_factor: usize,
         least_common_multiple: usize,
-    ) -> Vec<(usize, usize)> {
+    ) -> Result<Vec<(usize, usize)>> {
         // first increment the number of inspections
-        self.inspections += self.items.len() as usize;
+        self.inspections += self.items.len();
 
-        // now the actual inspection procedure. we have to clone these values
+        // now the actual inspection procedure. we have to copy these values
         // so we can mutably borrow to drain the items from the vector
-        let op = self.operator.clone();
-        let rhs = self.rhs.clone();
-        let modulus = self.modulus.clone();
-        let true_target = self.true_target.clone();
-        let false_target = self.false_target.clone();
+        let op = self.operator;
+        let rhs = self.rhs;
+        let modulus = self.modulus;
+        let true_target = self.true_target;
+        let false_target = self.false_target;
+        let number = self.number;
 
         return self
             .items
             .drain(..)
             .map(|i| {
-                (Self::perform_op(i, op, rhs) / worry_reduction_factor) % least_common_multiple
+                let new = Self::perform_op(i, op, rhs).ok_or_else(|| {
+                    let message = format!("monkey {} can't apply its operation to {}", number, i);
+                    Error::solve(Day11::DAY, &message)
+                })?;
+                Ok((new / worry_reduction_factor) % least_common_multiple)
             })
             .map(|i| {
-                if (i as usize) % modulus == 0 {
-                    (true_target, i)
+                let i = i?;
+                if i % modulus == 0 {
+                    Ok((true_target, i))
                 } else {
-                    (false_target, i)
+                    Ok((false_target, i))
                 }
             })
             .collect();
     }
 
-    fn distribute_thrown_items(monkeys: &mut Vec<Monkey>, items: &Vec<(usize, usize)>) -> () {
+    fn distribute_thrown_items(monkeys: &mut [Monkey], items: &[(usize, usize)]) {
         for (target, item) in items.iter() {
+            // targets are checked during parsing
             let monkey = monkeys.get_mut(*target).unwrap();
             monkey.items.push_back(*item);
         }
@@ -133,60 +139,95 @@ impl<'a> Monkey<'a> {
 }
 
 fn single_round(
-    monkeys: &mut Vec<Monkey>,
+    monkeys: &mut [Monkey],
     worry_factor: usize,
     least_common_multiple: usize,
-) -> () {
+) -> Result<()> {
     for i in 0..monkeys.len() {
         let items = {
             let monkey = monkeys.get_mut(i).unwrap();
-            monkey.inspect_items_and_throw(worry_factor, least_common_multiple)
+            monkey.inspect_items_and_throw(worry_factor, least_common_multiple)?
         };
         Monkey::distribute_thrown_items(monkeys, &items);
     }
+    return Ok(());
 }
 
-fn parse_input_to_monkeys(file_contents: &str) -> Vec<Monkey> {
-    return utils::divide_iterator_on_predicate(file_contents.lines(), |&s| s == "")
+pub fn parse(file_contents: &str) -> Result<Vec<Monkey>> {
+    let blocks: Vec<Vec<(usize, &str)>> = blocks(file_contents).collect();
+    let monkeys: Vec<Monkey> = blocks
         .iter()
         .map(|v| Monkey::new(v))
-        .collect();
+        .collect::<Result<_>>()?;
+    for (block, monkey) in blocks.iter().zip(monkeys.iter()) {
+        for (target, (i, line)) in [
+            (monkey.true_target, block[4]),
+            (monkey.false_target, block[5]),
+        ] {
+            if target >= monkeys.len() {
+                return Err(Error::parse(Day11::DAY, i, 1, line, "no such monkey"));
+            }
+        }
+    }
+    return Ok(monkeys);
 }
 
-fn solver(file_contents: &str, num_rounds: usize, worry_factor: usize) -> String {
-    let mut monkeys = parse_input_to_monkeys(file_contents);
+fn solver(monkeys: &[Monkey], num_rounds: usize, worry_factor: usize) -> Result<String> {
+    let mut monkeys = monkeys.to_vec();
     let least_common_multiple = monkeys.iter().map(|m| m.modulus).product::<usize>();
     for _i in 0..num_rounds {
-        single_round(&mut monkeys, worry_factor, least_common_multiple);
+        single_round(&mut monkeys, worry_factor, least_common_multiple)?;
     }
     let mut inspections: Vec<usize> = monkeys.iter().map(|m| m.inspections).collect();
     inspections.sort();
     inspections.reverse();
-    return inspections.iter().take(2).product::<usize>().to_string();
+    return Ok(inspections.iter().take(2).product::<usize>().to_string());
 }
 
-pub fn part_1(file_contents: &str) -> String {
-    return solver(file_contents, 20, 3);
+pub fn part_1(monkeys: &[Monkey]) -> Result<String> {
+    return solver(monkeys, 20, 3);
 }
 
-pub fn part_2(file_contents: &str) -> String {
-    return solver(file_contents, 10000, 1);
+pub fn part_2(monkeys: &[Monkey]) -> Result<String> {
+    return solver(monkeys, 10000, 1);
+}
+
+pub struct Day11;
+
+pub static SOLUTION: Registered<Day11> = Registered::new();
+
+impl Solution for Day11 {
+    type Input = Vec<Monkey>;
+
+    const DAY: u8 = 11;
+    const TITLE: &'static str = "Monkey in the Middle";
+
+    fn parse(file_contents: &str) -> Result<Self::Input> {
+        return parse(file_contents);
+    }
+
+    fn part_1(input: &Self::Input) -> Result<String> {
+        return part_1(input);
+    }
+
+    fn part_2(input: &Self::Input) -> Result<String> {
+        return part_2(input);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::utils;
 
     #[test]
     fn monkey_construction() {
         let example_str = include_str!("../tests/example_files/day11.txt");
-        let input_vec = utils::divide_iterator_on_predicate(example_str.lines(), |&s| s == "");
+        let input_vec = blocks(example_str);
         let expected_monkeys = [
             Monkey {
                 number: 0,
                 items: VecDeque::from(vec![79, 98]),
-                operator: "*",
+                operator: Operator::Multiply,
                 rhs: Some(19),
                 modulus: 23,
                 true_target: 2,
@@ -196,7 +237,7 @@ mod tests {
             Monkey {
                 number: 1,
                 items: VecDeque::from(vec![54, 65, 75, 74]),
-                operator: "+",
+                operator: Operator::Add,
                 rhs: Some(6),
                 modulus: 19,
                 true_target: 2,
@@ -206,7 +247,7 @@ mod tests {
             Monkey {
                 number: 2,
                 items: VecDeque::from(vec![79, 60, 97]),
-                operator: "*",
+                operator: Operator::Multiply,
                 rhs: None,
                 modulus: 13,
                 true_target: 1,
@@ -216,7 +257,7 @@ mod tests {
             Monkey {
                 number: 3,
                 items: VecDeque::from(vec![74]),
-                operator: "+",
+                operator: Operator::Add,
                 rhs: Some(3),
                 modulus: 17,
                 true_target: 0,
@@ -224,8 +265,8 @@ mod tests {
                 inspections: 0,
             },
         ];
-        for (input, expected) in input_vec.iter().zip(expected_monkeys.iter()) {
-            assert_eq!(Monkey::new(&input), *expected);
+        for (input, expected) in input_vec.zip(expected_monkeys.iter()) {
+            assert_eq!(Monkey::new(&input), Ok(expected.clone()));
         }
     }
 }
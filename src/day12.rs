@@ -1,35 +1,26 @@
-use std::collections::VecDeque;
-
-#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
-struct Point(usize, usize, char);
-
-fn find_start_and_end_positions(surf: &Vec<Vec<char>>) -> (Point, Point) {
-    let mut start = None;
-    let mut end = None;
-    for (i, v) in surf.iter().enumerate() {
-        for (j, c) in v.iter().enumerate() {
-            if *c == 'S' {
-                start = Some(Point(i, j, *c));
-            } else if *c == 'E' {
-                end = Some(Point(i, j, *c));
-            }
-
-            if start.is_some() && end.is_some() {
-                break;
-            }
-        }
-        if start.is_some() && end.is_some() {
-            break;
-        }
-    }
-    return (
-        start.expect("failed to find start point"),
-        end.expect("failed to find end point"),
-    );
-}
-
-fn point_in_bounds(x: &isize, y: &isize, max_x: usize, max_y: usize) -> bool {
-    return *x >= 0 && *x < (max_x as isize) && *y >= 0 && *y < (max_y as isize);
+use crate::error::{Error, Result};
+use crate::search::{breadth_first, BreadthFirst, Search};
+use crate::solution::{Registered, Solution};
+use crate::utils::{Grid, Point};
+use crate::visualise::{Colour, Frame, Glyph, Visualiser};
+
+fn find_start_and_end_positions(surf: &Grid<char>) -> Result<(Point, Point)> {
+    let find = |c: char| {
+        return surf
+            .iter_points()
+            .find(|(_p, s)| **s == c)
+            .map(|(p, _s)| p)
+            .ok_or_else(|| {
+                Error::parse(
+                    Day12::DAY,
+                    1,
+                    1,
+                    &c.to_string(),
+                    "failed to find point in input",
+                )
+            });
+    };
+    return Ok((find('S')?, find('E')?));
 }
 
 fn valid_step_in_chars(source_char: char, target_char: char) -> bool {
@@ -47,113 +38,145 @@ fn valid_step_in_chars(source_char: char, target_char: char) -> bool {
     return target - source <= 1;
 }
 
-fn determine_valid_neighbours(
-    surf: &Vec<Vec<char>>,
-    cur: Point,
-    max_x: usize,
-    max_y: usize,
-) -> Vec<Point> {
-    let Point(x, y, in_char) = cur;
-    return [(0, 1), (1, 0), (0, -1), (-1, 0)]
-        .iter()
-        .map(|(i, j)| (x as isize + i, y as isize + j))
-        .filter(|(x, y)| point_in_bounds(x, y, max_x, max_y))
-        .map(|(x, y)| (x as usize, y as usize, surf[x as usize][y as usize]))
-        .filter(|(_x, _y, c)| valid_step_in_chars(in_char, *c))
-        .map(|(x, y, c)| Point(x, y, c))
+fn determine_valid_neighbours(surf: &Grid<char>, cur: Point) -> Vec<Point> {
+    return surf
+        .neighbours4(cur)
+        .filter(|p| valid_step_in_chars(surf[cur], surf[*p]))
         .collect();
 }
 
-fn breadth_first_search_single_iteration(
-    surf: &Vec<Vec<char>>,
-    end: &Point,
-    visited: &mut Vec<Vec<bool>>,
-    distances: &mut Vec<Vec<Option<usize>>>,
-    queue: &mut VecDeque<Point>,
-    predecessors: &mut Vec<Vec<Option<Point>>>,
-    max_x: usize,
-    max_y: usize,
-) -> Option<usize> {
-    let cur_point = queue.pop_front().unwrap();
-    let Point(x, y, _c) = cur_point;
-    let cur_distance = distances[x][y].unwrap_or(0);
-    let neighbours = determine_valid_neighbours(&surf, cur_point, max_x, max_y);
-    for point in neighbours.iter() {
-        if point == end {
-            return Some(cur_distance + 1);
-        }
-        let Point(nx, ny, _c) = point;
-        if visited[*nx][*ny] {
-            continue;
-        }
-        visited[*nx][*ny] = true;
-        distances[*nx][*ny] = Some(cur_distance + 1);
-        predecessors[*nx][*ny] = Some(cur_point);
-        queue.push_back(*point);
+/// Where the climb can start from in each part
+fn sources(map: &Heightmap, part: u8) -> Vec<Point> {
+    if part == 1 {
+        return vec![map.start];
     }
-    return None;
+    return map
+        .surf
+        .iter_points()
+        .filter(|(_p, &c)| c == 'a')
+        .map(|(p, _c)| p)
+        .chain([map.start])
+        .collect();
 }
 
-fn breadth_first_search(
-    surf: &Vec<Vec<char>>,
-    start: &Point,
-    end: &Point,
-    max_x: usize,
-    max_y: usize,
-) -> Option<usize> {
-    let mut visited: Vec<Vec<bool>> = vec![vec![false; max_y]; max_x];
-    let mut distances: Vec<Vec<Option<usize>>> = vec![vec![None; max_y]; max_x];
-    let mut queue: VecDeque<Point> = VecDeque::from_iter([*start].iter().cloned());
-    let mut predecessors: Vec<Vec<Option<Point>>> = vec![vec![None; max_y]; max_x];
-    while !queue.is_empty() {
-        match breadth_first_search_single_iteration(
-            &surf,
-            &end,
-            &mut visited,
-            &mut distances,
-            &mut queue,
-            &mut predecessors,
-            max_x,
-            max_y,
-        ) {
-            Some(x) => return Some(x),
-            None => (),
-        }
+fn uphill(map: &Heightmap) -> impl FnMut(&Point) -> Vec<Point> + '_ {
+    return |p: &Point| determine_valid_neighbours(&map.surf, *p);
+}
+
+/// Searching from every source at once finds the nearest of them to the end
+fn shortest_climb(map: &Heightmap, part: u8) -> Search<Point, usize> {
+    return breadth_first(sources(map, part), uphill(map), |p| *p == map.end);
+}
+
+fn answer(distance: Option<usize>, part: u8) -> Result<String> {
+    let problem = if part == 1 {
+        "no path from start to end"
+    } else {
+        "no path from any lowest point to end"
+    };
+    return distance
+        .map(|d| d.to_string())
+        .ok_or_else(|| Error::solve(Day12::DAY, problem));
+}
+
+pub struct Heightmap {
+    surf: Grid<char>,
+    start: Point,
+    end: Point,
+}
+
+pub fn parse(file_contents: &str) -> Result<Heightmap> {
+    let surf = Grid::from_str(Day12::DAY, file_contents, "a height", |c| {
+        (c.is_ascii_lowercase() || c == 'S' || c == 'E').then_some(c)
+    })?;
+    let (start, end) = find_start_and_end_positions(&surf)?;
+    return Ok(Heightmap { surf, start, end });
+}
+
+pub fn part_1(map: &Heightmap) -> Result<String> {
+    return answer(shortest_climb(map, 1).goal_distance(), 1);
+}
+
+pub fn part_2(map: &Heightmap) -> Result<String> {
+    return answer(shortest_climb(map, 2).goal_distance(), 2);
+}
+
+fn draw_search<'a>(
+    vis: &Visualiser,
+    map: &Heightmap,
+    search: &Search<Point, usize>,
+    frontier: impl Iterator<Item = &'a Point>,
+    path: &[Point],
+    caption: &str,
+) -> Frame {
+    let unvisited = vis.glyph("unvisited", Glyph::new('.', Colour::Grey));
+    let seen = vis.glyph("visited", Glyph::new('.', Colour::Blue));
+    let edge = vis.glyph("frontier", Glyph::new('.', Colour::Yellow));
+    let route = vis.glyph("path", Glyph::new('.', Colour::Green));
+    let ends = vis.glyph("ends", Glyph::new('.', Colour::Red));
+    let mut frame = vis.frame(map.surf.min_point, map.surf.max_point);
+    for (p, c) in map.surf.iter_points() {
+        let glyph = if search.reached(&p) { seen } else { unvisited };
+        frame.plot(p, glyph.with_char(*c));
+    }
+    for p in frontier {
+        frame.plot(*p, edge.with_char(map.surf[*p]));
+    }
+    for p in path {
+        frame.plot(*p, route.with_char(map.surf[*p]));
+    }
+    for p in [map.start, map.end] {
+        frame.plot(p, ends.with_char(map.surf[p]));
     }
-    return None;
+    frame.caption(caption);
+    return frame;
 }
 
-pub fn part_1(file_contents: &str) -> String {
-    let surf: Vec<Vec<char>> = file_contents
-        .lines()
-        .map(|line| line.chars().collect())
-        .collect();
-    let max_x = surf.len();
-    let max_y = surf[0].len();
-    let (start, end) = find_start_and_end_positions(&surf);
-    let distance = breadth_first_search(&surf, &start, &end, max_x, max_y);
-    return distance.unwrap().to_string();
+fn visualise(map: &Heightmap, part: u8, vis: &mut Visualiser) -> Result<String> {
+    let mut bfs = BreadthFirst::new(sources(map, part), uphill(map));
+    while let Some(p) = bfs.next() {
+        if p == map.end {
+            break;
+        }
+        vis.step(|vis| draw_search(vis, map, bfs.search(), bfs.frontier(), &[], "searching"));
+    }
+    let search = bfs.into_search();
+    let distance = search.distance(&map.end);
+    let path = search.path_to(&map.end).unwrap_or_default();
+    let caption = match distance {
+        Some(d) => format!("{} steps to the end", d),
+        None => "no way to the end".to_owned(),
+    };
+    let none = std::iter::empty();
+    vis.last(draw_search(vis, map, &search, none, &path, &caption));
+    return answer(distance, part);
 }
 
-pub fn part_2(file_contents: &str) -> String {
-    let surf: Vec<Vec<char>> = file_contents
-        .lines()
-        .map(|line| line.chars().collect())
-        .collect();
-    let max_x = surf.len();
-    let max_y = surf[0].len();
-    let (start, end) = find_start_and_end_positions(&surf);
-    let possible_starts = surf
-        .iter()
-        .enumerate()
-        .flat_map(move |(i, line)| line.iter().enumerate().map(move |(j, c)| (i, j, c)))
-        .filter(|(_i, _j, &c)| c == 'a')
-        .map(|(i, j, c)| Point(i, j, *c))
-        .chain([start]);
-    let distance = possible_starts
-        .filter_map(|s| breadth_first_search(&surf, &s, &end, max_x, max_y))
-        .min();
-    return distance.unwrap().to_string();
+pub struct Day12;
+
+pub static SOLUTION: Registered<Day12> = Registered::new();
+
+impl Solution for Day12 {
+    type Input = Heightmap;
+
+    const DAY: u8 = 12;
+    const TITLE: &'static str = "Hill Climbing Algorithm";
+
+    fn parse(file_contents: &str) -> Result<Self::Input> {
+        return parse(file_contents);
+    }
+
+    fn part_1(input: &Self::Input) -> Result<String> {
+        return part_1(input);
+    }
+
+    fn part_2(input: &Self::Input) -> Result<String> {
+        return part_2(input);
+    }
+
+    fn visualise(input: &Self::Input, part: u8, vis: &mut Visualiser) -> Option<Result<String>> {
+        return Some(visualise(input, part, vis));
+    }
 }
 
 #[cfg(test)]
@@ -166,58 +189,52 @@ abcryxxl
 accszExk
 acctuvwj
 abdefghi";
-    const EXAMPLE_MAX_X: usize = 5;
-    const EXAMPLE_MAX_Y: usize = 8;
     lazy_static! {
-        static ref EXAMPLE_SURF: Vec<Vec<char>> = EXAMPLE_INPUT
-            .lines()
-            .map(|line| line.chars().collect())
-            .collect();
+        static ref EXAMPLE_SURF: Grid<char> = parse(EXAMPLE_INPUT).unwrap().surf;
     }
 
     #[test]
     fn finds_start_and_end_in_example_surface() {
-        let expected_start = Point(0, 0, 'S');
-        let expected_end = Point(2, 5, 'E');
-        let (result_start, result_end) = find_start_and_end_positions(&EXAMPLE_SURF);
+        let expected_start = Point(0, 0);
+        let expected_end = Point(5, 2);
+        let (result_start, result_end) = find_start_and_end_positions(&EXAMPLE_SURF).unwrap();
         assert_eq!(result_start, expected_start);
         assert_eq!(result_end, expected_end);
     }
 
     #[test]
     fn valid_neighbours_are_returned() {
-        let input_point = Point(0, 0, 'S');
-        let expected = HashSet::from([Point(0, 1, 'a'), Point(1, 0, 'a')]);
-        let result =
-            determine_valid_neighbours(&EXAMPLE_SURF, input_point, EXAMPLE_MAX_X, EXAMPLE_MAX_Y);
-        assert_eq!(HashSet::from_iter(result.iter().cloned()), expected);
-
-        let input_point = Point(1, 1, 'b');
-        let expected = HashSet::from([
-            Point(1, 2, 'c'),
-            Point(0, 1, 'a'),
-            Point(1, 0, 'a'),
-            Point(2, 1, 'c'),
-        ]);
-        let result =
-            determine_valid_neighbours(&EXAMPLE_SURF, input_point, EXAMPLE_MAX_X, EXAMPLE_MAX_Y);
-        assert_eq!(HashSet::from_iter(result.iter().cloned()), expected);
-
-        let input_point = Point(2, 4, 'z');
-        let expected = HashSet::from([
-            Point(2, 5, 'E'),
-            Point(1, 4, 'y'),
-            Point(2, 3, 's'),
-            Point(3, 4, 'u'),
-        ]);
-        let result =
-            determine_valid_neighbours(&EXAMPLE_SURF, input_point, EXAMPLE_MAX_X, EXAMPLE_MAX_Y);
-        assert_eq!(HashSet::from_iter(result.iter().cloned()), expected);
-
-        let input_point = Point(2, 6, 'x');
-        let expected = HashSet::from([Point(2, 7, 'k'), Point(1, 6, 'x'), Point(3, 6, 'w')]);
-        let result =
-            determine_valid_neighbours(&EXAMPLE_SURF, input_point, EXAMPLE_MAX_X, EXAMPLE_MAX_Y);
-        assert_eq!(HashSet::from_iter(result.iter().cloned()), expected);
+        let cases = [
+            (Point(0, 0), vec![Point(1, 0), Point(0, 1)]),
+            (
+                Point(1, 1),
+                vec![Point(2, 1), Point(1, 0), Point(0, 1), Point(1, 2)],
+            ),
+            (
+                Point(4, 2),
+                vec![Point(5, 2), Point(4, 1), Point(3, 2), Point(4, 3)],
+            ),
+            (Point(6, 2), vec![Point(7, 2), Point(6, 1), Point(6, 3)]),
+        ];
+        for (input_point, expected) in cases {
+            let result = determine_valid_neighbours(&EXAMPLE_SURF, input_point);
+            assert_eq!(
+                HashSet::from_iter(result.iter().cloned()),
+                HashSet::<Point>::from_iter(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn the_shortest_climb_can_be_retraced() {
+        let map = parse(EXAMPLE_INPUT).unwrap();
+        let search = shortest_climb(&map, 1);
+        assert_eq!(search.goal_distance(), Some(31));
+        let path = search.goal_path().unwrap();
+        assert_eq!((path[0], path[31]), (map.start, map.end));
+        assert!(path
+            .windows(2)
+            .all(|w| determine_valid_neighbours(&map.surf, w[0]).contains(&w[1])));
+        assert_eq!(shortest_climb(&map, 2).goal_distance(), Some(29));
     }
 }
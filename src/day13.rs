@@ -1,9 +1,10 @@
-use crate::utils;
+use crate::error::{Error, Result};
+use crate::solution::{Registered, Solution};
 use std::cmp::Ordering;
 use std::collections::VecDeque;
 
 #[derive(PartialEq, Eq, Debug, Clone)]
-enum Node {
+pub enum Node {
     Leaf(usize),
     Branch(Vec<Node>),
 }
@@ -38,89 +39,131 @@ fn in_order(left: &Node, right: &Node) -> Ordering {
     };
 }
 
+/// Why a packet couldn't be read, and how many chars before where reading
+/// stopped the problem starts, or `None` to blame the whole packet
+type Unreadable = (&'static str, Option<usize>);
+
 /// Build the tree from the input
 ///
 /// I couldn't figure out how to avoid this, but you need to drop the first
 /// '[' char to get the function to work. Quite annoying.
-fn build_tree(input: &mut VecDeque<char>) -> Node {
-    let mut branch: Node = Node::Branch(vec![]);
+///
+/// On an unrecognised char, `input` is left just after it. Running out of
+/// input before the closing `]` is an error too.
+fn build_tree(input: &mut VecDeque<char>) -> std::result::Result<Node, Unreadable> {
+    let mut branch: Vec<Node> = vec![];
     let mut num_to_parse: Vec<char> = vec![];
-    let parse_num =
-        |b: &mut Node, n: &mut Vec<char>| match n.iter().collect::<String>().parse::<usize>() {
-            Ok(x) => {
-                if let Node::Branch(y) = b {
-                    y.push(Node::Leaf(x));
-                }
-            }
-            Err(_) => (),
-        };
-    while !input.is_empty() {
-        let c = input.pop_front();
-        match c.unwrap() {
-            '0'..='9' => num_to_parse.push(c.unwrap()),
-            ',' => {
-                parse_num(&mut branch, &mut num_to_parse);
-                num_to_parse.clear();
-            }
-            '[' => {
-                if let Node::Branch(ref mut x) = branch {
-                    x.push(build_tree(input));
-                }
-            }
+    let parse_num = |b: &mut Vec<Node>, n: &mut Vec<char>| {
+        if !n.is_empty() {
+            let x = n.iter().collect::<String>().parse::<usize>();
+            let x = x.map_err(|_e| ("number out of range", Some(n.len())))?;
+            b.push(Node::Leaf(x));
+            n.clear();
+        }
+        return Ok(());
+    };
+    while let Some(c) = input.pop_front() {
+        match c {
+            '0'..='9' => num_to_parse.push(c),
+            ',' => parse_num(&mut branch, &mut num_to_parse)?,
+            '[' => branch.push(build_tree(input)?),
             ']' => {
-                parse_num(&mut branch, &mut num_to_parse);
-                return branch;
+                parse_num(&mut branch, &mut num_to_parse)?;
+                return Ok(Node::Branch(branch));
             }
-            _ => panic!("unrecognised input char!"),
+            _ => return Err(("unrecognised input char", Some(0))),
         }
     }
-    return branch;
+    return Err(("packet is missing a `]`", None));
 }
 
-fn break_into_pairs(file_contents: &str) -> Vec<Vec<&str>> {
-    return utils::divide_iterator_on_predicate(file_contents.lines(), |&s| s == "");
+fn parse_packet(line_no: usize, line: &str) -> Result<Node> {
+    if !line.starts_with('[') {
+        return Err(Error::parse(
+            Day13::DAY,
+            line_no,
+            1,
+            line,
+            "packets must start with `[`",
+        ));
+    }
+    let mut chars: VecDeque<char> = line.chars().skip(1).collect();
+    let tree = build_tree(&mut chars);
+    // work out where parsing stopped from how much of the line is left
+    let column = line.chars().count() - chars.len();
+    let err = |column: usize, message: &str| {
+        let text: String = line.chars().skip(column - 1).collect();
+        Error::parse(Day13::DAY, line_no, column, &text, message)
+    };
+    return match tree {
+        Ok(_) if !chars.is_empty() => Err(err(column + 1, "unexpected text after packet")),
+        Ok(x) => Ok(x),
+        Err((message, Some(back))) => Err(err(column - back, message)),
+        Err((message, None)) => Err(err(1, message)),
+    };
 }
 
-pub fn part_1(file_contents: &str) -> String {
-    return break_into_pairs(file_contents)
-        .iter()
+pub fn parse(file_contents: &str) -> Result<Vec<Node>> {
+    return file_contents
+        .lines()
+        .enumerate()
+        .filter(|(_i, s)| !s.is_empty())
+        .map(|(i, s)| parse_packet(i + 1, s))
+        .collect();
+}
+
+pub fn part_1(packets: &[Node]) -> Result<String> {
+    if !packets.len().is_multiple_of(2) {
+        return Err(Error::solve(Day13::DAY, "packets don't split into pairs"));
+    }
+    return Ok(packets
+        .chunks(2)
         .enumerate()
-        .map(|(i, p)| {
-            let trans = |s: &str| {
-                let mut v = VecDeque::from(s.chars().collect::<Vec<char>>());
-                v.pop_front();
-                v
-            };
-            let t1 = build_tree(&mut trans(p[0]));
-            let t2 = build_tree(&mut trans(p[1]));
-            // remember that we need to be one-indexed
-            return (i + 1, in_order(&t1, &t2));
-        })
+        // remember that we need to be one-indexed
+        .map(|(i, p)| (i + 1, in_order(&p[0], &p[1])))
         .filter(|(_i, b)| *b == Ordering::Less)
         .map(|(i, _b)| i)
         .sum::<usize>()
-        .to_string();
+        .to_string());
 }
 
-pub fn part_2(file_contents: &str) -> String {
+pub fn part_2(packets: &[Node]) -> Result<String> {
     let decoder_packet_1: Node = Node::Branch(vec![Node::Branch(vec![Node::Leaf(2)])]);
     let decoder_packet_2: Node = Node::Branch(vec![Node::Branch(vec![Node::Leaf(6)])]);
-    let mut vec: Vec<Node> = file_contents
-        .lines()
-        .filter(|&s| s != "")
-        .map(|s| VecDeque::from(s.chars().collect::<Vec<char>>()))
-        .map(|mut v| build_tree(&mut v))
-        .collect();
+    let mut vec: Vec<Node> = packets.to_vec();
     vec.push(decoder_packet_1.clone());
     vec.push(decoder_packet_2.clone());
     vec.sort_by(in_order);
-    return vec
+    return Ok(vec
         .iter()
         .enumerate()
         .filter(|(_i, p)| **p == decoder_packet_1 || **p == decoder_packet_2)
         .map(|(i, _p)| i + 1)
         .product::<usize>()
-        .to_string();
+        .to_string());
+}
+
+pub struct Day13;
+
+pub static SOLUTION: Registered<Day13> = Registered::new();
+
+impl Solution for Day13 {
+    type Input = Vec<Node>;
+
+    const DAY: u8 = 13;
+    const TITLE: &'static str = "Distress Signal";
+
+    fn parse(file_contents: &str) -> Result<Self::Input> {
+        return parse(file_contents);
+    }
+
+    fn part_1(input: &Self::Input) -> Result<String> {
+        return part_1(input);
+    }
+
+    fn part_2(input: &Self::Input) -> Result<String> {
+        return part_2(input);
+    }
 }
 
 #[cfg(test)]
@@ -138,7 +181,7 @@ mod tests {
         ]);
         assert_eq!(
             build_tree(&mut "1,1,3,1,1]".chars().collect::<VecDeque<char>>()),
-            expected
+            Ok(expected)
         );
 
         let expected = Node::Branch(vec![
@@ -148,17 +191,34 @@ mod tests {
 
         assert_eq!(
             build_tree(&mut "[1],[2,3,4]]".chars().collect::<VecDeque<char>>()),
-            expected
+            Ok(expected)
         );
 
         let expected = Node::Branch(vec![Node::Branch(vec![Node::Branch(vec![])])]);
 
         assert_eq!(
             build_tree(&mut "[[]]]".chars().collect::<VecDeque<char>>()),
-            expected
+            Ok(expected)
         );
     }
 
+    #[test]
+    fn reports_position_of_unrecognised_char() {
+        let expected = Error::parse(13, 4, 5, "x,3]]", "unrecognised input char");
+        assert_eq!(parse("[1]\n[2]\n\n[[1,x,3]]"), Err(expected));
+    }
+
+    #[test]
+    fn reports_unterminated_packets_and_huge_numbers() {
+        let expected = Error::parse(13, 1, 1, "[1,2", "packet is missing a `]`");
+        assert_eq!(parse("[1,2"), Err(expected));
+        let expected = Error::parse(13, 1, 1, "[[1]", "packet is missing a `]`");
+        assert_eq!(parse("[[1]"), Err(expected));
+        let huge = "[1,99999999999999999999999]";
+        let expected = Error::parse(13, 1, 4, &huge[3..], "number out of range");
+        assert_eq!(parse(huge), Err(expected));
+    }
+
     #[test]
     fn in_order_on_example_1() {
         let left = Node::Branch(vec![
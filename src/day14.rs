@@ -1,171 +1,245 @@
+use crate::error::{Error, Result};
+use crate::export::{Image, Recorder, Rgb};
+use crate::parse::{literal, parse_lines, unsigned, Parser};
+use crate::solution::{Registered, Solution};
+use crate::utils::{range_inclusive, Grid, Point, SparseGrid};
+use crate::visualise::{Colour, Frame, Glyph, Visualiser};
 use itertools::*;
-use regex::Captures;
-use regex::Regex;
-use crate::utils::{Point, Grid, range_inclusive};
-
-lazy_static! {
-    static ref POINT_RE: Regex = Regex::new(r"(\d+),(\d+)").expect("invalid regex");
-}
-
-impl Point {
-    fn next_sand_location(
-        &self,
-        walls: &Grid<bool>,
-        existing_sand: &Grid<bool>,
-    ) -> Option<Self> {
-        let Self(x, y) = *self;
-        for p in [Point(x, y + 1), Point(x - 1, y + 1), Point(x + 1, y + 1)] {
-            if !walls.get_value(p) && !existing_sand.get_value(p) {
-                return Some(p);
-            }
-        }
+
+/// What's in the cave at a point
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Tile {
+    Air,
+    Wall,
+    Sand,
+}
+
+fn next_sand_location(sand: Point, cave: &SparseGrid<Tile>, floor: Option<isize>) -> Option<Point> {
+    let below = sand + Point::DOWN;
+    if floor == Some(below.1) {
         return None;
     }
+    return [below, below + Point::LEFT, below + Point::RIGHT]
+        .into_iter()
+        .find(|&p| !cave.contains(p));
 }
 
 const SAND_ORIGIN: Point = Point(500, 0);
 
-fn parse_wall_lines(input: &str) -> Vec<Vec<Point>> {
-    let parser = |r: &Captures, i| r.get(i).unwrap().as_str().parse::<isize>().unwrap();
-    return input
-        .lines()
-        .map(|line| {
-            line.split(" -> ")
-                .map(|p| POINT_RE.captures(p).unwrap())
-                .map(|r| Point(parser(&r, 1), parser(&r, 2)))
-                .collect()
-        })
-        .collect();
-}
-
-fn construct_walls(lines: &Vec<Vec<Point>>) -> Grid<bool> {
-    // find minima and maxima
-    // sand can form a pyramid that covers the source. The tallest it can be is
-    // 2 below the lowest wall. This height also determines the width of the
-    // pyramid at the base
-
-    let Point(x, y) = SAND_ORIGIN;
-    let min_y = y;
-    let max_y = lines
-        .iter()
-        .map(|line| {
-            line.iter()
-                .map(|p| {
-                    let Point(_x, y) = p;
-                    y
-                })
-                .max()
-                .unwrap()
-        })
-        .max()
-        .unwrap();
-    // subtract one to make sure we don't go out of bounds during simulations
-    let min_x = x - max_y - 1;
-    let max_x = x + max_y + 1;
-    // initialise the grid, with some padding to allow sand to fall off
-    // the edges
-    let mut array = Grid::new(
-        // can't subtract 1 from min_y since it's always 0
-        Point(min_x - 1, min_y),
-        Point(max_x + 1, max_y + 1),
-        false,
-    );
-
-    // now flag the walls
+fn parse_wall_lines(input: &str) -> Result<Vec<Vec<Point>>> {
+    let point = unsigned()
+        .then_ignore(literal(","))
+        .then(unsigned())
+        .map(|(x, y)| Point(x, y))
+        .expecting("`x,y`");
+    return parse_lines(Day14::DAY, input, &point.separated_by(literal(" -> ")));
+}
+
+fn construct_walls(lines: &[Vec<Point>]) -> Result<SparseGrid<Tile>> {
+    let mut walls = SparseGrid::new();
     for line in lines.iter() {
         for (p1, p2) in line.iter().tuple_windows() {
             let Point(x1, y1) = *p1;
             let Point(x2, y2) = *p2;
             for i in range_inclusive(x1, x2).iter() {
                 for j in range_inclusive(y1, y2).iter() {
-                    array.set_value(Point(*i, *j), true);
+                    walls.insert(Point(*i, *j), Tile::Wall);
                 }
             }
         }
     }
-    return array;
+    if walls.is_empty() {
+        return Err(Error::parse(Day14::DAY, 1, 1, "", "no walls in input"));
+    }
+    return Ok(walls);
 }
 
 fn simulate_sand_particle_dropping(
-    walls: &Grid<bool>,
-    existing_sand: &mut Grid<bool>,
-    allowed_to_touch_floor: bool,
+    cave: &mut SparseGrid<Tile>,
+    lowest: isize,
+    floor: Option<isize>,
 ) -> Option<Point> {
-    let mut next_sand = SAND_ORIGIN.clone();
-    let mut potential_next = next_sand.next_sand_location(&walls, &existing_sand);
-    while potential_next.is_some() {
-        next_sand = potential_next.unwrap();
-        let Point(x, y) = next_sand;
-        let Point(min_x, _min_y) = walls.min_point;
-        let Point(max_x, max_y) = walls.max_point;
-        if !allowed_to_touch_floor && (x == min_x || x == max_x || y == max_y - 1) {
+    let mut next_sand = SAND_ORIGIN;
+    while let Some(p) = next_sand_location(next_sand, cave, floor) {
+        next_sand = p;
+        // with no floor, there's nothing below the lowest wall to stop it
+        if floor.is_none() && next_sand.1 > lowest {
             return None;
         }
-        potential_next = next_sand.next_sand_location(&walls, &existing_sand);
     }
-    existing_sand.set_value(next_sand, true);
+    cave.insert(next_sand, Tile::Sand);
     return Some(next_sand);
 }
 
-pub fn part_1(file_contents: &str) -> String {
-    let wall_lines = parse_wall_lines(file_contents);
-    let walls = construct_walls(&wall_lines);
-    let mut existing_sand = Grid::new(walls.min_point, walls.max_point, false);
-    let mut counter = 0;
-    while simulate_sand_particle_dropping(&walls, &mut existing_sand, false).is_some() {
-        counter += 1;
-    }
-    return counter.to_string();
+pub fn parse(file_contents: &str) -> Result<SparseGrid<Tile>> {
+    let wall_lines = parse_wall_lines(file_contents)?;
+    return construct_walls(&wall_lines);
 }
 
-pub fn part_2(file_contents: &str) -> String {
-    let wall_lines = parse_wall_lines(file_contents);
-    let mut walls = construct_walls(&wall_lines);
-    // add the "floor"
-    let j = walls.array[0].len();
-    for i in 0..walls.array.len() {
-        walls.array[i][j - 1] = true;
-    }
-    let mut existing_sand = Grid::new(walls.min_point, walls.max_point, false);
+/// Drop sand until a grain falls past the lowest wall or, with a floor 2
+/// below it, until the source is covered. `on_rest` sees the cave after each
+/// grain comes to rest. Returns how many grains came to rest.
+fn pour_sand(
+    walls: &SparseGrid<Tile>,
+    floor: bool,
+    mut on_rest: impl FnMut(&SparseGrid<Tile>, usize),
+) -> (usize, SparseGrid<Tile>) {
+    // parsing guarantees there's at least one wall
+    let (_min, Point(_x, lowest)) = walls.bounds().unwrap();
+    let floor = floor.then_some(lowest + 2);
+    let mut cave = walls.clone();
     let mut counter = 0;
-    while let Some(x) = simulate_sand_particle_dropping(&walls, &mut existing_sand, true) {
+    while let Some(x) = simulate_sand_particle_dropping(&mut cave, lowest, floor) {
         counter += 1;
+        on_rest(&cave, counter);
         if x == SAND_ORIGIN {
             break;
         }
     }
-    return counter.to_string();
+    return (counter, cave);
+}
+
+pub fn part_1(walls: &SparseGrid<Tile>) -> Result<String> {
+    let (counter, _cave) = pour_sand(walls, false, |_c, _n| {});
+    return Ok(counter.to_string());
+}
+
+pub fn part_2(walls: &SparseGrid<Tile>) -> Result<String> {
+    let (counter, _cave) = pour_sand(walls, true, |_c, _n| {});
+    return Ok(counter.to_string());
+}
+
+/// The corners of everything there'll be to draw once the sand has settled,
+/// so that every picture can be the same size
+fn extent(walls: &SparseGrid<Tile>, floor: bool) -> (Point, Point) {
+    let (_counter, mut cave) = pour_sand(walls, floor, |_c, _n| {});
+    cave.insert(SAND_ORIGIN, Tile::Air);
+    let (min, max) = cave.bounds().unwrap();
+    if floor {
+        // sand rests just above the floor
+        return (min, max + Point::DOWN);
+    }
+    return (min, max);
+}
+
+fn draw_sand(
+    vis: &Visualiser,
+    bounds: (Point, Point),
+    cave: &SparseGrid<Tile>,
+    floor: bool,
+    caption: &str,
+) -> Frame {
+    let wall = vis.glyph("wall", Glyph::new('#', Colour::White));
+    let grain = vis.glyph("sand", Glyph::new('o', Colour::Yellow));
+    let source = vis.glyph("source", Glyph::new('+', Colour::Red));
+    let (min, max) = bounds;
+    let mut frame = vis.frame(min, max);
+    for (p, t) in cave.iter_points() {
+        match t {
+            Tile::Air => {}
+            Tile::Wall => frame.plot(p, wall),
+            Tile::Sand => frame.plot(p, grain),
+        }
+    }
+    if floor {
+        frame.plot_all((min.0..=max.0).map(|x| Point(x, max.1)), wall);
+    }
+    frame.plot(SAND_ORIGIN, source);
+    frame.caption(caption);
+    return frame;
+}
+
+fn visualise(walls: &SparseGrid<Tile>, part: u8, vis: &mut Visualiser) -> Result<String> {
+    let floor = part == 2;
+    let bounds = extent(walls, floor);
+    let (counter, cave) = pour_sand(walls, floor, |cave, counter| {
+        let caption = format!("{} grains of sand", counter);
+        vis.step(|vis| draw_sand(vis, bounds, cave, floor, &caption));
+    });
+    let caption = format!("{} grains of sand at rest", counter);
+    vis.last(draw_sand(vis, bounds, &cave, floor, &caption));
+    return Ok(counter.to_string());
+}
+
+impl Tile {
+    fn colour(&self) -> Rgb {
+        return match self {
+            Self::Air => [20, 20, 40],
+            Self::Wall => [130, 130, 130],
+            Self::Sand => [230, 190, 80],
+        };
+    }
+}
+
+fn picture(bounds: (Point, Point), cave: &SparseGrid<Tile>, floor: bool) -> Image {
+    let (min, max) = bounds;
+    let mut tiles = Grid::new(min, max, Tile::Air);
+    for (p, t) in cave.iter_points() {
+        if let Some(tile) = tiles.get_mut(p) {
+            *tile = *t;
+        }
+    }
+    if floor {
+        for x in min.0..=max.0 {
+            tiles[Point(x, max.1)] = Tile::Wall;
+        }
+    }
+    return Image::from_grid(&tiles, Tile::colour);
+}
+
+fn export(walls: &SparseGrid<Tile>, part: u8, images: &mut Recorder) -> Result<String> {
+    let floor = part == 2;
+    let bounds = extent(walls, floor);
+    let (counter, cave) = pour_sand(walls, floor, |cave, _counter| {
+        images.step(|| picture(bounds, cave, floor));
+    });
+    images.last(picture(bounds, &cave, floor));
+    return Ok(counter.to_string());
+}
+
+pub struct Day14;
+
+pub static SOLUTION: Registered<Day14> = Registered::new();
+
+impl Solution for Day14 {
+    type Input = SparseGrid<Tile>;
+
+    const DAY: u8 = 14;
+    const TITLE: &'static str = "Regolith Reservoir";
+
+    fn parse(file_contents: &str) -> Result<Self::Input> {
+        return parse(file_contents);
+    }
+
+    fn part_1(input: &Self::Input) -> Result<String> {
+        return part_1(input);
+    }
+
+    fn part_2(input: &Self::Input) -> Result<String> {
+        return part_2(input);
+    }
+
+    fn visualise(input: &Self::Input, part: u8, vis: &mut Visualiser) -> Option<Result<String>> {
+        return Some(visualise(input, part, vis));
+    }
+
+    fn export(input: &Self::Input, part: u8, images: &mut Recorder) -> Option<Result<String>> {
+        return Some(export(input, part, images));
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    // https://stackoverflow.com/a/64499219
-    fn transpose<T>(v: Vec<Vec<T>>) -> Vec<Vec<T>> {
-        assert!(!v.is_empty());
-        let len = v[0].len();
-        let mut iters: Vec<_> = v.into_iter().map(|n| n.into_iter()).collect();
-        (0..len)
-            .map(|_| {
-                iters
-                    .iter_mut()
-                    .map(|n| n.next().unwrap())
-                    .collect::<Vec<T>>()
-            })
-            .collect()
-    }
-
-    fn print_array(array: &Grid<bool>) -> () {
+    fn print_cave(cave: &SparseGrid<Tile>) {
         println!(
             "{}",
-            transpose(array.array.clone())
-                .iter()
-                .map(|line| line
-                    .iter()
-                    .map(|&b| if b { '#' } else { '.' })
-                    .collect::<String>())
-                .join("\n")
+            cave.map(|t| match t {
+                Tile::Air => '+',
+                Tile::Wall => '#',
+                Tile::Sand => 'o',
+            })
         );
     }
 
@@ -177,19 +251,15 @@ mod tests {
             vec![Point(498, 4), Point(498, 6), Point(496, 6)],
             vec![Point(503, 4), Point(502, 4), Point(502, 9), Point(494, 9)],
         ];
-        assert_eq!(parse_wall_lines(input), expected);
+        assert_eq!(parse_wall_lines(input), Ok(expected));
     }
 
     #[test]
     fn construct_array_with_coords() {
         let result = Grid::new(Point(400, 0), Point(500, 10), false);
-        assert_eq!(result.array.len(), 102);
-        assert_eq!(result.array[0].len(), 12);
-        assert!(result
-            .array
-            .iter()
-            .map(|line| line.iter().all(|&b| b == false))
-            .all(|b| b == true));
+        assert_eq!(result.array.len(), 101);
+        assert_eq!(result.array[0].len(), 11);
+        assert!(result.array.iter().all(|line| line.iter().all(|&b| !b)));
     }
 
     #[test]
@@ -197,35 +267,36 @@ mod tests {
         let input = parse_wall_lines(
             "498,4 -> 498,6 -> 496,6
 503,4 -> 502,4 -> 502,9 -> 494,9",
-        );
-        let mut expected = Grid::new(Point(489, 0), Point(511, 10), false);
+        )
+        .unwrap();
+        let mut expected = SparseGrid::new();
         // first wall
-        expected.set_value(Point(496, 6), true);
-        expected.set_value(Point(497, 6), true);
-        expected.set_value(Point(498, 6), true);
-        expected.set_value(Point(498, 5), true);
-        expected.set_value(Point(498, 4), true);
+        expected.insert(Point(496, 6), Tile::Wall);
+        expected.insert(Point(497, 6), Tile::Wall);
+        expected.insert(Point(498, 6), Tile::Wall);
+        expected.insert(Point(498, 5), Tile::Wall);
+        expected.insert(Point(498, 4), Tile::Wall);
         // second wall
-        expected.set_value(Point(494, 9), true);
-        expected.set_value(Point(495, 9), true);
-        expected.set_value(Point(496, 9), true);
-        expected.set_value(Point(497, 9), true);
-        expected.set_value(Point(498, 9), true);
-        expected.set_value(Point(499, 9), true);
-        expected.set_value(Point(500, 9), true);
-        expected.set_value(Point(501, 9), true);
-        expected.set_value(Point(502, 9), true);
-        expected.set_value(Point(502, 8), true);
-        expected.set_value(Point(502, 7), true);
-        expected.set_value(Point(502, 6), true);
-        expected.set_value(Point(502, 5), true);
-        expected.set_value(Point(502, 4), true);
-        expected.set_value(Point(503, 4), true);
-
-        print_array(&expected);
-        println!("");
-        print_array(&construct_walls(&input));
-
-        assert_eq!(construct_walls(&input), expected);
+        expected.insert(Point(494, 9), Tile::Wall);
+        expected.insert(Point(495, 9), Tile::Wall);
+        expected.insert(Point(496, 9), Tile::Wall);
+        expected.insert(Point(497, 9), Tile::Wall);
+        expected.insert(Point(498, 9), Tile::Wall);
+        expected.insert(Point(499, 9), Tile::Wall);
+        expected.insert(Point(500, 9), Tile::Wall);
+        expected.insert(Point(501, 9), Tile::Wall);
+        expected.insert(Point(502, 9), Tile::Wall);
+        expected.insert(Point(502, 8), Tile::Wall);
+        expected.insert(Point(502, 7), Tile::Wall);
+        expected.insert(Point(502, 6), Tile::Wall);
+        expected.insert(Point(502, 5), Tile::Wall);
+        expected.insert(Point(502, 4), Tile::Wall);
+        expected.insert(Point(503, 4), Tile::Wall);
+
+        print_cave(&expected);
+        println!();
+        print_cave(&construct_walls(&input).unwrap());
+
+        assert_eq!(construct_walls(&input), Ok(expected));
     }
 }
@@ -1,12 +1,9 @@
-use crate::utils::{range_inclusive, Point};
-use regex::Captures;
-use regex::Regex;
-
-lazy_static! {
-    static ref SENSOR_RE: Regex =
-        Regex::new(r"Sensor at x=(-?\d+), y=(-?\d+): closest beacon is at x=(-?\d+), y=(-?\d+)")
-            .expect("invalid regex");
-}
+use crate::error::{Error, Result};
+use crate::parse::{literal, parse_lines, signed, Parser};
+use crate::solution::{Registered, Solution};
+use crate::utils::{range_inclusive, IntervalSet, Point};
+use crate::visualise::{Colour, Frame, Glyph, Visualiser};
+use std::ops::RangeInclusive;
 
 #[derive(PartialEq, Eq, Debug)]
 enum BeaconExists {
@@ -15,15 +12,7 @@ enum BeaconExists {
     Maybe,
 }
 
-impl Point {
-    fn manhattan_distance(&self, other: &Self) -> isize {
-        let Point(x1, y1) = self;
-        let Point(x2, y2) = other;
-        return (x2 - x1).abs() + (y2 - y1).abs();
-    }
-}
-
-struct Sensor {
+pub struct Sensor {
     location: Point,
     nearest_beacon: Point,
     manhattan_distance: isize,
@@ -31,7 +20,7 @@ struct Sensor {
 
 impl Sensor {
     fn new(location: Point, nearest_beacon: Point) -> Self {
-        let manhattan_distance = location.manhattan_distance(&nearest_beacon);
+        let manhattan_distance = location.manhattan(nearest_beacon);
         return Self {
             location,
             nearest_beacon,
@@ -42,43 +31,65 @@ impl Sensor {
     fn beacon_at_location(&self, point: &Point) -> BeaconExists {
         if self.nearest_beacon == *point {
             return BeaconExists::Yes;
-        } else if self.location.manhattan_distance(point) <= self.manhattan_distance {
+        } else if self.location.manhattan(*point) <= self.manhattan_distance {
             return BeaconExists::No;
         } else {
             return BeaconExists::Maybe;
         }
     }
 
+    /// The part of row `y` the sensor can see, which is empty if the row is
+    /// out of its range
+    fn coverage(&self, y: isize) -> RangeInclusive<isize> {
+        let Point(x, sy) = self.location;
+        let reach = self.manhattan_distance - (sy - y).abs();
+        return x - reach..=x + reach;
+    }
+
     fn points_just_outside_circle(&self) -> Vec<Point> {
         let radius = self.manhattan_distance + 1;
         return range_inclusive(0, radius)
             .iter()
             .chain(range_inclusive(radius - 1, -radius).iter())
             .zip(-radius..radius)
-            .map(|(i, j)| Point(*i + self.location.0, j + self.location.1))
+            .map(|(i, j)| self.location + Point(*i, j))
             .collect();
     }
 }
 
-fn parse_lines(input: &str) -> (Vec<Sensor>, Point, Point) {
-    let caps: Vec<Captures> = input
-        .lines()
-        .map(|line| SENSOR_RE.captures(line).unwrap())
-        .collect();
-    let parse_num = |c: &Captures, i: usize| c.get(i).unwrap().as_str().parse::<isize>().unwrap();
+/// The sensors, along with a bounding box large enough to contain every point
+/// any of them can see
+pub struct Scan {
+    sensors: Vec<Sensor>,
+    min_point: Point,
+    max_point: Point,
+}
+
+fn position() -> impl Parser<Point> {
+    return literal("x=")
+        .ignore_then(signed())
+        .then_ignore(literal(", y="))
+        .then(signed())
+        .map(|(x, y)| Point(x, y));
+}
+
+pub fn parse(input: &str) -> Result<Scan> {
+    let report = literal("Sensor at ")
+        .ignore_then(position())
+        .then_ignore(literal(": closest beacon is at "))
+        .then(position());
+    let reports = parse_lines(Day15::DAY, input, &report)?;
     let mut vec: Vec<Sensor> = vec![];
-    let mut min_x: isize = isize::max_value();
-    let mut min_y: isize = isize::max_value();
-    let mut max_x: isize = isize::min_value();
-    let mut max_y: isize = isize::min_value();
+    let mut min_x: isize = isize::MAX;
+    let mut min_y: isize = isize::MAX;
+    let mut max_x: isize = isize::MIN;
+    let mut max_y: isize = isize::MIN;
     let mut max_manhattan: isize = 0;
-    for cap in caps.iter() {
-        let sx = parse_num(cap, 1);
-        let sy = parse_num(cap, 2);
-        let bx = parse_num(cap, 3);
-        let by = parse_num(cap, 4);
+    for (sensor, beacon) in reports {
+        let Point(sx, sy) = sensor;
+        let Point(bx, by) = beacon;
 
-        let p = Sensor::new(Point(sx, sy), Point(bx, by));
+        let p = Sensor::new(sensor, beacon);
         if max_manhattan < p.manhattan_distance {
             max_manhattan = p.manhattan_distance;
         }
@@ -112,36 +123,31 @@ fn parse_lines(input: &str) -> (Vec<Sensor>, Point, Point) {
             max_y = by;
         }
     }
-    return (
-        vec,
-        Point(min_x - max_manhattan, min_y - max_manhattan),
-        Point(max_x + max_manhattan, max_y + max_manhattan),
-    );
+    return Ok(Scan {
+        sensors: vec,
+        min_point: Point(min_x - max_manhattan, min_y - max_manhattan),
+        max_point: Point(max_x + max_manhattan, max_y + max_manhattan),
+    });
 }
 
-pub fn part_1_solver(file_contents: &str, target_y: isize) -> String {
-    let (sensors, min_point, max_point) = parse_lines(file_contents);
-    let Point(min_x, _min_y) = min_point;
-    let Point(max_x, _max_y) = max_point;
-    let mut counter = 0;
-    for i in min_x..max_x {
-        if sensors
-            .iter()
-            .map(|s| s.beacon_at_location(&Point(i, target_y)))
-            .any(|b| b == BeaconExists::No)
-        {
-            counter += 1;
-        }
-    }
-    return counter.to_string();
+pub fn part_1_solver(scan: &Scan, target_y: isize) -> Result<String> {
+    let covered: IntervalSet = scan.sensors.iter().map(|s| s.coverage(target_y)).collect();
+    let beacons: IntervalSet = scan
+        .sensors
+        .iter()
+        .map(|s| s.nearest_beacon)
+        .filter(|Point(_x, y)| *y == target_y)
+        .map(|Point(x, _y)| x..=x)
+        .collect();
+    return Ok(covered.difference(&beacons).len().to_string());
 }
 
-pub fn part_1(file_contents: &str) -> String {
-    return part_1_solver(file_contents, 2_000_000);
+pub fn part_1(scan: &Scan) -> Result<String> {
+    return part_1_solver(scan, 2_000_000);
 }
 
-pub fn part_2_solver(file_contents: &str, coord_limit: isize) -> String {
-    let (sensors, _min_point, _max_point) = parse_lines(file_contents);
+fn find_distress_beacon(scan: &Scan, coord_limit: isize) -> Option<Point> {
+    let sensors = &scan.sensors;
     let mut point: Option<Point> = None;
     let potential_points = sensors.iter().flat_map(|s| s.points_just_outside_circle()).filter(|p| {
         let Point(x, y) = p;
@@ -153,10 +159,109 @@ pub fn part_2_solver(file_contents: &str, coord_limit: isize) -> String {
             break;
         }
     }
-    let Point(x, y) = point.expect("could not find valid point!");
-    return (x * 4_000_000 + y).to_string();
+    return point;
+}
+
+pub fn part_2_solver(scan: &Scan, coord_limit: isize) -> Result<String> {
+    let Point(x, y) = find_distress_beacon(scan, coord_limit)
+        .ok_or_else(|| Error::solve(Day15::DAY, "could not find valid point!"))?;
+    return Ok((x * 4_000_000 + y).to_string());
+}
+
+pub fn part_2(scan: &Scan) -> Result<String> {
+    return part_2_solver(scan, 4_000_000);
+}
+
+fn draw_scan(
+    vis: &Visualiser,
+    bounds: (Point, Point),
+    sensors: &[Sensor],
+    row: Option<isize>,
+    found: Option<Point>,
+    caption: &str,
+) -> Frame {
+    let covered = vis.glyph("covered", Glyph::new('#', Colour::Grey));
+    let row_covered = vis.glyph("row-covered", Glyph::new('#', Colour::Cyan));
+    let row_clear = vis.glyph("row", Glyph::new('-', Colour::Cyan));
+    let mut frame = vis.frame(bounds.0, bounds.1);
+    let scale = frame.scale();
+    // far too many points to look at one by one, so each cell is checked
+    frame.fill(|p| {
+        let on_row = row.is_some_and(|r| p.1 <= r && r < p.1 + scale);
+        let seen = sensors
+            .iter()
+            .any(|s| s.location.manhattan(p) <= s.manhattan_distance);
+        return match (seen, on_row) {
+            (true, true) => Some(row_covered),
+            (true, false) => Some(covered),
+            (false, true) => Some(row_clear),
+            (false, false) => None,
+        };
+    });
+    let beacon = vis.glyph("beacon", Glyph::new('B', Colour::Blue));
+    let sensor = vis.glyph("sensor", Glyph::new('S', Colour::Red));
+    for s in sensors.iter() {
+        frame.plot(s.nearest_beacon, beacon);
+        frame.plot(s.location, sensor);
+    }
+    if let Some(p) = found {
+        frame.plot(p, vis.glyph("distress", Glyph::new('*', Colour::Green)));
+    }
+    frame.caption(caption);
+    return frame;
 }
 
-pub fn part_2(file_contents: &str) -> String {
-    return part_2_solver(file_contents, 4_000_000);
+fn visualise(scan: &Scan, part: u8, vis: &mut Visualiser) -> Result<String> {
+    let (bounds, row) = match part {
+        1 => ((scan.min_point, scan.max_point), Some(2_000_000)),
+        _ => ((Point(0, 0), Point(4_000_000, 4_000_000)), None),
+    };
+    for i in 1..=scan.sensors.len() {
+        let caption = format!("{} of {} sensors", i, scan.sensors.len());
+        vis.step(|vis| draw_scan(vis, bounds, &scan.sensors[..i], row, None, &caption));
+    }
+    let (answer, found) = match part {
+        1 => (part_1(scan), None),
+        _ => {
+            let found = find_distress_beacon(scan, 4_000_000);
+            let answer = found
+                .map(|Point(x, y)| (x * 4_000_000 + y).to_string())
+                .ok_or_else(|| Error::solve(Day15::DAY, "could not find valid point!"));
+            (answer, found)
+        }
+    };
+    let caption = match (&answer, found) {
+        (Ok(_x), Some(Point(x, y))) => format!("the distress beacon is at {}, {}", x, y),
+        (Ok(x), None) => format!("{} places on the row can't have a beacon", x),
+        (Err(e), _) => e.to_string(),
+    };
+    vis.last(draw_scan(vis, bounds, &scan.sensors, row, found, &caption));
+    return answer;
+}
+
+pub struct Day15;
+
+pub static SOLUTION: Registered<Day15> = Registered::new();
+
+impl Solution for Day15 {
+    type Input = Scan;
+
+    const DAY: u8 = 15;
+    const TITLE: &'static str = "Beacon Exclusion Zone";
+
+    fn parse(file_contents: &str) -> Result<Self::Input> {
+        return parse(file_contents);
+    }
+
+    fn part_1(input: &Self::Input) -> Result<String> {
+        return part_1(input);
+    }
+
+    fn part_2(input: &Self::Input) -> Result<String> {
+        return part_2(input);
+    }
+
+    fn visualise(input: &Self::Input, part: u8, vis: &mut Visualiser) -> Option<Result<String>> {
+        return Some(visualise(input, part, vis));
+    }
 }
@@ -1,107 +1,453 @@
-use std::collections::HashMap;
+use crate::error::{Error, Result};
+use crate::parse::{literal, one_of, parse_lines, Parser};
+use crate::solution::{Registered, Solution};
 
 #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
-enum GameState {
+pub enum GameState {
     Lose,
     Draw,
     Win,
 }
 
-#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
-enum Hands {
-    Rock,
-    Paper,
-    Scissors,
+/// A hand that can be played, with its score and the letters for it in each
+/// column of the strategy guide
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Hand {
+    pub name: String,
+    pub score: u64,
+    pub opponent: char,
+    /// Only used when the second column is read as a hand
+    pub response: char,
 }
 
-lazy_static! {
-    static ref SCORES_FOR_GAME_STATE: HashMap<GameState, u64> = {
-        return HashMap::from([
-            (GameState::Lose, 0),
-            (GameState::Draw, 3),
-            (GameState::Win, 6),
-        ]);
-    };
-    static ref SCORES_FOR_HAND: HashMap<Hands, u64> = {
-        return HashMap::from([(Hands::Rock, 1), (Hands::Paper, 2), (Hands::Scissors, 3)]);
-    };
-    static ref WINS_AGAINST: HashMap<Hands, Hands> = {
-        return HashMap::from([
-            (Hands::Rock, Hands::Scissors),
-            (Hands::Paper, Hands::Rock),
-            (Hands::Scissors, Hands::Paper),
-        ]);
-    };
-    static ref LOSES_AGAINST: HashMap<Hands, Hands> = {
-        return HashMap::from([
-            (Hands::Rock, Hands::Paper),
-            (Hands::Paper, Hands::Scissors),
-            (Hands::Scissors, Hands::Rock),
-        ]);
-    };
+/// A way for a round to end, with its score and its letter in the second
+/// column when that's read as an outcome
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Outcome {
+    pub letter: char,
+    pub score: u64,
 }
 
-fn parse_chars_to_hands(c: &char) -> Hands {
-    return match c {
-        'A' | 'X' => Hands::Rock,
-        'B' | 'Y' => Hands::Paper,
-        'C' | 'Z' => Hands::Scissors,
-        _ => panic!("invalid input!"),
-    };
+/// Any game like rock paper scissors, where every two different hands have a
+/// winner between them. Hands are referred to by where they are in the list
+/// the rules were made with.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Rules {
+    hands: Vec<Hand>,
+    /// `beats[a][b]` when `a` beats `b`
+    beats: Vec<Vec<bool>>,
+    /// For losing, drawing and winning, in that order
+    outcomes: [Outcome; 3],
 }
 
-fn parse_chars_to_game_state(c: &char) -> GameState {
-    return match c {
-        'X' => GameState::Lose,
-        'Y' => GameState::Draw,
-        'Z' => GameState::Win,
-        _ => panic!("invalid input!"),
+impl Rules {
+    /// Check that the rules make sense: every hand can be told apart from
+    /// the others in both columns, and every pair of hands has exactly one
+    /// winner.
+    pub fn new(
+        hands: &[Hand],
+        beats: &[(&str, &str)],
+        outcomes: [Outcome; 3],
+    ) -> std::result::Result<Self, String> {
+        if hands.is_empty() {
+            return Err("there must be at least one hand".to_owned());
+        }
+        for (i, hand) in hands.iter().enumerate() {
+            for other in hands[..i].iter() {
+                if other.name == hand.name {
+                    return Err(format!("there are two hands called {}", hand.name));
+                }
+                if other.opponent == hand.opponent || other.response == hand.response {
+                    let message = format!("{} and {} have the same letter", other.name, hand.name);
+                    return Err(message);
+                }
+            }
+        }
+        let [lose, draw, win] = outcomes.map(|o| o.letter);
+        if lose == draw || draw == win || win == lose {
+            return Err("outcomes must all have different letters".to_owned());
+        }
+
+        let index = |name: &str| {
+            return hands
+                .iter()
+                .position(|h| h.name == name)
+                .ok_or_else(|| format!("there's no hand called {}", name));
+        };
+        let mut matrix = vec![vec![false; hands.len()]; hands.len()];
+        for (winner, loser) in beats.iter() {
+            let (w, l) = (index(winner)?, index(loser)?);
+            if w == l {
+                return Err(format!("{} can't beat itself", winner));
+            }
+            if matrix[l][w] {
+                return Err(format!(
+                    "{} and {} can't both beat each other",
+                    winner, loser
+                ));
+            }
+            matrix[w][l] = true;
+        }
+        for a in 0..hands.len() {
+            for b in 0..a {
+                if !matrix[a][b] && !matrix[b][a] {
+                    let message = format!(
+                        "nothing says whether {} or {} wins",
+                        hands[a].name, hands[b].name
+                    );
+                    return Err(message);
+                }
+            }
+        }
+        return Ok(Self {
+            hands: hands.to_vec(),
+            beats: matrix,
+            outcomes,
+        });
+    }
+
+    pub fn hand(&self, name: &str) -> Option<usize> {
+        return self.hands.iter().position(|h| h.name == name);
+    }
+
+    fn response(&self, letter: char) -> Option<usize> {
+        return self.hands.iter().position(|h| h.response == letter);
+    }
+
+    fn outcome_of(&self, state: GameState) -> Outcome {
+        return match state {
+            GameState::Lose => self.outcomes[0],
+            GameState::Draw => self.outcomes[1],
+            GameState::Win => self.outcomes[2],
+        };
+    }
+
+    fn outcome(&self, letter: char) -> Option<GameState> {
+        return [GameState::Lose, GameState::Draw, GameState::Win]
+            .into_iter()
+            .find(|s| self.outcome_of(*s).letter == letter);
+    }
+
+    /// How it goes for whoever plays `mine` against `theirs`
+    pub fn play(&self, mine: usize, theirs: usize) -> GameState {
+        if mine == theirs {
+            return GameState::Draw;
+        } else if self.beats[mine][theirs] {
+            return GameState::Win;
+        } else {
+            return GameState::Lose;
+        }
+    }
+
+    pub fn score(&self, mine: usize, theirs: usize) -> u64 {
+        let outcome = self.outcome_of(self.play(mine, theirs));
+        return outcome.score + self.hands[mine].score;
+    }
+
+    /// The hand to play against `theirs` to get `outcome`. When more than
+    /// one would do it's the one that scores most.
+    pub fn hand_for(&self, theirs: usize, outcome: GameState) -> Option<usize> {
+        return (0..self.hands.len())
+            .filter(|&h| self.play(h, theirs) == outcome)
+            .max_by_key(|&h| self.hands[h].score);
     }
 }
 
-fn score_game(opponent_hand: &Hands, my_hand: &Hands) -> u64 {
-    let game_state = if opponent_hand == my_hand {
-        GameState::Draw
-    } else if WINS_AGAINST[my_hand] == *opponent_hand {
-        GameState::Win
-    } else {
-        GameState::Lose
+lazy_static! {
+    /// The game the elves play, with the guide read the same way for both
+    /// parts
+    pub static ref STANDARD_RULES: Rules = {
+        let hand = |name: &str, score, opponent, response| Hand {
+            name: name.to_owned(),
+            score,
+            opponent,
+            response,
+        };
+        let outcome = |letter, score| Outcome { letter, score };
+        return Rules::new(
+            &[
+                hand("rock", 1, 'A', 'X'),
+                hand("paper", 2, 'B', 'Y'),
+                hand("scissors", 3, 'C', 'Z'),
+            ],
+            &[("rock", "scissors"), ("paper", "rock"), ("scissors", "paper")],
+            [outcome('X', 0), outcome('Y', 3), outcome('Z', 6)],
+        )
+        .expect("the standard rules are consistent");
     };
-    return SCORES_FOR_GAME_STATE[&game_state] + SCORES_FOR_HAND[my_hand];
 }
 
-pub fn part_1(file_contents: &str) -> String {
-    let games: Vec<(Hands, Hands)> = Vec::from_iter(file_contents.lines().map(|line| {
-        (
-            parse_chars_to_hands(&line.chars().nth(0).expect("not enough chars on line")),
-            parse_chars_to_hands(&line.chars().nth(2).expect("not enough chars on line")),
-        )
-    }));
-    return games
+/// One line of the strategy guide. The second column means a hand in part 1
+/// and a desired outcome in part 2, so both readings are kept, when it has
+/// them.
+#[derive(PartialEq, Eq, Debug)]
+pub struct Round {
+    opponent: usize,
+    response: Option<usize>,
+    outcome: Option<GameState>,
+}
+
+pub fn parse_with_rules(rules: &Rules, file_contents: &str) -> Result<Vec<Round>> {
+    let opponent = one_of(
+        rules
+            .hands
+            .iter()
+            .enumerate()
+            .map(|(i, h)| (h.opponent.to_string(), i)),
+    );
+    // every letter the second column can have, with what it means each way
+    let mut letters: Vec<char> = rules
+        .hands
         .iter()
-        .map(|(h1, h2)| score_game(h1, h2))
-        .sum::<u64>()
-        .to_string();
+        .map(|h| h.response)
+        .chain(rules.outcomes.iter().map(|o| o.letter))
+        .collect();
+    letters.sort();
+    letters.dedup();
+    let second = one_of(
+        letters
+            .into_iter()
+            .map(|c| (c.to_string(), (rules.response(c), rules.outcome(c)))),
+    );
+    let round = opponent.then_ignore(literal(" ")).then(second);
+    let round = round.map(|(opponent, (response, outcome))| Round {
+        opponent,
+        response,
+        outcome,
+    });
+    return parse_lines(Day2::DAY, file_contents, &round);
 }
 
-fn hand_to_pick_for_desired_game_state(opponent_hand: &Hands, game_state: &GameState) -> Hands {
-    return match game_state {
-        GameState::Draw => opponent_hand.clone(),
-        GameState::Win => LOSES_AGAINST[opponent_hand],
-        GameState::Lose => WINS_AGAINST[opponent_hand],
-    };
+pub fn parse(file_contents: &str) -> Result<Vec<Round>> {
+    return parse_with_rules(&STANDARD_RULES, file_contents);
 }
 
-pub fn part_2(file_contents: &str) -> String {
-    let games: Vec<(Hands, GameState)> = Vec::from_iter(file_contents.lines().map(|line| {
-        (
-            parse_chars_to_hands(&line.chars().nth(0).expect("not enough chars on line")),
-            parse_chars_to_game_state(&line.chars().nth(2).expect("not enough chars on line")),
-        )
-    }));
-    let hands = games.iter().map(|(h, g)| (h, hand_to_pick_for_desired_game_state(h, g)));
-    return hands
-        .map(|(h1, h2)| score_game(h1, &h2))
-        .sum::<u64>()
-        .to_string();
+/// The total score from playing the second column as hands
+pub fn play_responses(rules: &Rules, rounds: &[Round]) -> Result<u64> {
+    return rounds
+        .iter()
+        .map(|r| {
+            let response = r
+                .response
+                .ok_or_else(|| Error::solve(Day2::DAY, "a round's second column isn't a hand"))?;
+            Ok(rules.score(response, r.opponent))
+        })
+        .sum();
+}
+
+/// The total score from playing for the outcomes in the second column
+pub fn play_outcomes(rules: &Rules, rounds: &[Round]) -> Result<u64> {
+    return rounds
+        .iter()
+        .map(|r| {
+            let outcome = r.outcome.ok_or_else(|| {
+                Error::solve(Day2::DAY, "a round's second column isn't an outcome")
+            })?;
+            let hand = rules.hand_for(r.opponent, outcome).ok_or_else(|| {
+                let name = &rules.hands[r.opponent].name;
+                let message = format!("no hand gets a {:?} against {}", outcome, name);
+                Error::solve(Day2::DAY, &message)
+            })?;
+            Ok(rules.score(hand, r.opponent))
+        })
+        .sum();
+}
+
+pub fn part_1(rounds: &[Round]) -> Result<String> {
+    return Ok(play_responses(&STANDARD_RULES, rounds)?.to_string());
+}
+
+pub fn part_2(rounds: &[Round]) -> Result<String> {
+    return Ok(play_outcomes(&STANDARD_RULES, rounds)?.to_string());
+}
+
+pub struct Day2;
+
+pub static SOLUTION: Registered<Day2> = Registered::new();
+
+impl Solution for Day2 {
+    type Input = Vec<Round>;
+
+    const DAY: u8 = 2;
+    const TITLE: &'static str = "Rock Paper Scissors";
+
+    fn parse(file_contents: &str) -> Result<Self::Input> {
+        return parse(file_contents);
+    }
+
+    fn part_1(input: &Self::Input) -> Result<String> {
+        return part_1(input);
+    }
+
+    fn part_2(input: &Self::Input) -> Result<String> {
+        return part_2(input);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(rules: &Rules, name: &str) -> usize {
+        return rules.hand(name).unwrap();
+    }
+
+    /// Rock paper scissors lizard Spock, with the opponent's hands as A to E
+    /// and the responses as V to Z. The names are read from text, as they
+    /// would be from a file of rules.
+    fn lizard_spock() -> std::result::Result<Rules, String> {
+        let names = "rock paper scissors lizard spock".split(' ');
+        let hands: Vec<Hand> = names
+            .map(|name| name.to_owned())
+            .zip("ABCDE".chars().zip("VWXYZ".chars()))
+            .enumerate()
+            .map(|(i, (name, (opponent, response)))| Hand {
+                name,
+                score: i as u64 + 1,
+                opponent,
+                response,
+            })
+            .collect();
+        let beats = [
+            ("scissors", "paper"),
+            ("paper", "rock"),
+            ("rock", "lizard"),
+            ("lizard", "spock"),
+            ("spock", "scissors"),
+            ("scissors", "lizard"),
+            ("lizard", "paper"),
+            ("paper", "spock"),
+            ("spock", "rock"),
+            ("rock", "scissors"),
+        ];
+        let outcomes =
+            [('X', 0), ('Y', 3), ('Z', 6)].map(|(letter, score)| Outcome { letter, score });
+        return Rules::new(&hands, &beats, outcomes);
+    }
+
+    #[test]
+    fn parses_both_readings_of_second_column() {
+        let rules = &STANDARD_RULES;
+        let expected = vec![
+            Round {
+                opponent: hand(rules, "rock"),
+                response: Some(hand(rules, "paper")),
+                outcome: Some(GameState::Draw),
+            },
+            Round {
+                opponent: hand(rules, "scissors"),
+                response: Some(hand(rules, "scissors")),
+                outcome: Some(GameState::Win),
+            },
+        ];
+        assert_eq!(parse("A Y\nC Z"), Ok(expected));
+    }
+
+    #[test]
+    fn reports_position_of_invalid_hand() {
+        let expected = Error::parse(2, 2, 3, "Q", "expected one of X, Y or Z");
+        assert_eq!(parse("A Y\nB Q"), Err(expected));
+        let expected = Error::parse(2, 1, 1, "D", "expected one of A, B or C");
+        assert_eq!(parse("D Y"), Err(expected));
+        let expected = Error::parse(2, 1, 2, "?X", "expected ` `");
+        assert_eq!(parse("A?X"), Err(expected));
+        let expected = Error::parse(2, 1, 4, "YZ", "unexpected text at the end of the line");
+        assert_eq!(parse("A XYZ"), Err(expected));
+    }
+
+    #[test]
+    fn more_hands_can_be_played() {
+        let rules = lizard_spock().unwrap();
+        let (spock, lizard) = (hand(&rules, "spock"), hand(&rules, "lizard"));
+        assert_eq!(rules.play(lizard, spock), GameState::Win);
+        assert_eq!(rules.play(spock, lizard), GameState::Lose);
+        assert_eq!(rules.score(lizard, spock), 6 + 4);
+        // losing scores nothing, so it's only spock's own score
+        assert_eq!(rules.score(spock, lizard), 5);
+        // paper and lizard both beat spock, and lizard scores more
+        assert_eq!(rules.hand_for(spock, GameState::Win), Some(lizard));
+
+        let rounds = parse_with_rules(&rules, "E Y\nA V\nC W").unwrap();
+        assert_eq!(play_responses(&rules, &rounds), Ok(10 + 4 + 2));
+        assert_eq!(
+            play_outcomes(&rules, &rounds),
+            Err(Error::solve(2, "a round's second column isn't an outcome"))
+        );
+        let expected = Error::parse(2, 1, 3, "U", "expected one of V, W, X, Y or Z");
+        assert_eq!(parse_with_rules(&rules, "A U"), Err(expected));
+    }
+
+    /// Rules for the hands `(name, opponent, response)`, all scoring 1
+    fn rules(
+        hands: &[(&str, char, char)],
+        beats: &[(&str, &str)],
+        outcome_letters: [char; 3],
+    ) -> std::result::Result<Rules, String> {
+        let hands: Vec<Hand> = hands
+            .iter()
+            .map(|&(name, opponent, response)| Hand {
+                name: name.to_owned(),
+                score: 1,
+                opponent,
+                response,
+            })
+            .collect();
+        let outcomes = outcome_letters.map(|letter| Outcome { letter, score: 0 });
+        return Rules::new(&hands, beats, outcomes);
+    }
+
+    #[test]
+    fn inconsistent_rules_are_refused() {
+        let two = [("rock", 'A', 'X'), ("paper", 'B', 'Y')];
+        let with_beats = |beats: &[(&str, &str)]| rules(&two, beats, ['X', 'Y', 'Z']);
+        assert!(with_beats(&[("paper", "rock")]).is_ok());
+        assert_eq!(
+            with_beats(&[]),
+            Err("nothing says whether paper or rock wins".to_owned())
+        );
+        assert_eq!(
+            with_beats(&[("paper", "rock"), ("rock", "paper")]),
+            Err("rock and paper can't both beat each other".to_owned())
+        );
+        assert_eq!(
+            with_beats(&[("paper", "rock"), ("rock", "rock")]),
+            Err("rock can't beat itself".to_owned())
+        );
+        assert_eq!(
+            with_beats(&[("paper", "stone")]),
+            Err("there's no hand called stone".to_owned())
+        );
+        assert!(lizard_spock().is_ok());
+    }
+
+    #[test]
+    fn hands_and_outcomes_must_be_told_apart() {
+        let beats = [("paper", "rock")];
+        let letters = ['X', 'Y', 'Z'];
+        assert_eq!(
+            rules(&[], &[], letters),
+            Err("there must be at least one hand".to_owned())
+        );
+        assert_eq!(
+            rules(&[("rock", 'A', 'X'), ("rock", 'B', 'Y')], &[], letters),
+            Err("there are two hands called rock".to_owned())
+        );
+        let same_opponent = [("rock", 'A', 'X'), ("paper", 'A', 'Y')];
+        assert_eq!(
+            rules(&same_opponent, &beats, letters),
+            Err("rock and paper have the same letter".to_owned())
+        );
+        let same_response = [("rock", 'A', 'X'), ("paper", 'B', 'X')];
+        assert_eq!(
+            rules(&same_response, &beats, letters),
+            Err("rock and paper have the same letter".to_owned())
+        );
+        let two = [("rock", 'A', 'X'), ("paper", 'B', 'Y')];
+        assert_eq!(
+            rules(&two, &beats, ['X', 'Y', 'X']),
+            Err("outcomes must all have different letters".to_owned())
+        );
+        // a hand's letter can be shared with an outcome, as the elves' is
+        assert!(rules(&two, &beats, letters).is_ok());
+    }
 }
@@ -1,7 +1,6 @@
-pub fn part_1(_file_contents: &str) -> String {
-    unimplemented!("solution not implemented!");
-}
+use crate::solution::Unimplemented;
 
-pub fn part_2(_file_contents: &str) -> String {
-    unimplemented!("solution not implemented!");
-}
+pub static SOLUTION: Unimplemented = Unimplemented {
+    day: 20,
+    title: "Grove Positioning System",
+};
@@ -1,3 +1,5 @@
+use crate::error::{Error, Result};
+use crate::solution::{Registered, Solution};
 use std::collections::{HashMap, HashSet};
 
 #[derive(Debug)]
@@ -11,33 +13,54 @@ lazy_static! {
         HashMap::from_iter(('a'..='z').zip(1..27).chain(('A'..='Z').zip(27..53)));
 }
 
-fn get_sets(file_contents: &str) -> Vec<(HashSet<char>, HashSet<char>)> {
-    let rucksacks = file_contents.lines().map(|line| Rucksack {
+fn parse_rucksack(line_no: usize, line: &str) -> Result<Rucksack<'_>> {
+    if let Some((j, c)) = line
+        .char_indices()
+        .find(|(_j, c)| !PRIORITIES.contains_key(c))
+    {
+        let text = &line[j..j + c.len_utf8()];
+        return Err(Error::parse(
+            Day3::DAY,
+            line_no,
+            j + 1,
+            text,
+            "invalid item",
+        ));
+    }
+    if !line.len().is_multiple_of(2) {
+        let message = "compartments must hold the same number of items";
+        return Err(Error::parse(Day3::DAY, line_no, 1, line, message));
+    }
+    return Ok(Rucksack {
         first: &line[..line.len() / 2],
         second: &line[line.len() / 2..],
     });
+}
+
+pub fn parse(file_contents: &str) -> Result<Vec<(HashSet<char>, HashSet<char>)>> {
     let build_set = |s: &str| HashSet::<char>::from_iter(s.chars());
-    return rucksacks
-        .map(|r| (build_set(r.first), (build_set(r.second))))
+    return file_contents
+        .lines()
+        .enumerate()
+        .map(|(i, line)| parse_rucksack(i + 1, line))
+        .map(|r| r.map(|r| (build_set(r.first), (build_set(r.second)))))
         .collect();
 }
 
-pub fn part_1(file_contents: &str) -> String {
-    let sets = get_sets(file_contents);
+pub fn part_1(sets: &[(HashSet<char>, HashSet<char>)]) -> Result<String> {
     let common_items: Vec<HashSet<char>> = sets
         .iter()
         // .map(|(s1, s2)| s1.intersection(&s2).cloned().collect())
         .map(|(s1, s2)| s1 & s2)
         .collect();
-    return common_items
+    return Ok(common_items
         .iter()
         .map(|s| s.iter().map(|c| PRIORITIES[c]).sum::<u32>())
         .sum::<u32>()
-        .to_string();
+        .to_string());
 }
 
-pub fn part_2(file_contents: &str) -> String {
-    let sets = get_sets(file_contents);
+pub fn part_2(sets: &[(HashSet<char>, HashSet<char>)]) -> Result<String> {
     let united_sets: Vec<HashSet<char>> = sets.iter().map(|(s1, s2)| s1 | s2).collect();
     let badges = united_sets.chunks(3).map(|v| {
         // using reduce here would make more sense but I couldn't get it to
@@ -50,8 +73,31 @@ pub fn part_2(file_contents: &str) -> String {
             }
         })
     });
-    return badges
+    return Ok(badges
         .map(|s| s.iter().map(|c| PRIORITIES[c]).sum::<u32>())
         .sum::<u32>()
-        .to_string();
+        .to_string());
+}
+
+pub struct Day3;
+
+pub static SOLUTION: Registered<Day3> = Registered::new();
+
+impl Solution for Day3 {
+    type Input = Vec<(HashSet<char>, HashSet<char>)>;
+
+    const DAY: u8 = 3;
+    const TITLE: &'static str = "Rucksack Reorganization";
+
+    fn parse(file_contents: &str) -> Result<Self::Input> {
+        return parse(file_contents);
+    }
+
+    fn part_1(input: &Self::Input) -> Result<String> {
+        return part_1(input);
+    }
+
+    fn part_2(input: &Self::Input) -> Result<String> {
+        return part_2(input);
+    }
 }
@@ -1,31 +1,32 @@
-use lazy_static::lazy_static;
-use regex::Regex;
-use std::collections::HashSet;
+use crate::error::Result;
+use crate::parse::{literal, parse_lines, unsigned, Parser};
+use crate::solution::{Registered, Solution};
+use crate::utils::IntervalSet;
 
-lazy_static! {
-    static ref RE: Regex = Regex::new(r"(\d+)-(\d+),(\d+)-(\d+)").expect("invalid regex");
+#[derive(Debug)]
+pub struct Pair {
+    first: IntervalSet,
+    second: IntervalSet,
 }
 
-#[derive(Debug)]
-struct Pair {
-    first: HashSet<u32>,
-    second: HashSet<u32>,
+fn sections() -> impl Parser<IntervalSet> {
+    return unsigned()
+        .then_ignore(literal("-"))
+        .then(unsigned())
+        .map(|(start, end)| IntervalSet::from(start..=end));
 }
 
-fn build_pairs<'a>(file_contents: &'a str) -> impl Iterator<Item = Pair> + 'a {
-    return file_contents.lines().map(|line| {
-        let c = RE.captures(line).unwrap();
-        let p = |s: &str| s.parse::<u32>().unwrap();
-        Pair {
-            first: HashSet::from_iter(p(&c[1])..=p(&c[2])),
-            second: HashSet::from_iter(p(&c[3])..=p(&c[4])),
-        }
-    });
+pub fn parse(file_contents: &str) -> Result<Vec<Pair>> {
+    let pair = sections()
+        .then_ignore(literal(","))
+        .then(sections())
+        .map(|(first, second)| Pair { first, second });
+    return parse_lines(Day4::DAY, file_contents, &pair);
 }
 
-pub fn part_1(file_contents: &str) -> String {
-    let pairs = build_pairs(&file_contents);
-    return pairs
+pub fn part_1(pairs: &[Pair]) -> Result<String> {
+    return Ok(pairs
+        .iter()
         .filter(
             |Pair {
                  first: a,
@@ -33,13 +34,36 @@ pub fn part_1(file_contents: &str) -> String {
              }| a.is_subset(b) || b.is_subset(a),
         )
         .count()
-        .to_string();
+        .to_string());
 }
 
-pub fn part_2(file_contents: &str) -> String {
-    let pairs = build_pairs(&file_contents);
-    return pairs
-        .filter(|Pair { first, second }| !(first & second).is_empty())
+pub fn part_2(pairs: &[Pair]) -> Result<String> {
+    return Ok(pairs
+        .iter()
+        .filter(|Pair { first, second }| first.overlaps(second))
         .count()
-        .to_string();
+        .to_string());
+}
+
+pub struct Day4;
+
+pub static SOLUTION: Registered<Day4> = Registered::new();
+
+impl Solution for Day4 {
+    type Input = Vec<Pair>;
+
+    const DAY: u8 = 4;
+    const TITLE: &'static str = "Camp Cleanup";
+
+    fn parse(file_contents: &str) -> Result<Self::Input> {
+        return parse(file_contents);
+    }
+
+    fn part_1(input: &Self::Input) -> Result<String> {
+        return part_1(input);
+    }
+
+    fn part_2(input: &Self::Input) -> Result<String> {
+        return part_2(input);
+    }
 }
@@ -1,9 +1,6 @@
-use crate::utils::divide_iterator_on_predicate;
-use regex::Regex;
-
-lazy_static! {
-    static ref RE: Regex = Regex::new(r"move (\d+) from (\d+) to (\d+)").expect("invalid regex");
-}
+use crate::error::{Error, Result};
+use crate::parse::{blocks, literal, parse_line, unsigned, Parser};
+use crate::solution::{Registered, Solution};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 struct Moves {
@@ -12,15 +9,19 @@ struct Moves {
     count: u32,
 }
 
-fn build_stack(stack_strings: &Vec<&str>) -> Vec<Vec<char>> {
-    let num_stacks_line = stack_strings.last().unwrap();
-    let n_stacks = num_stacks_line
-        .trim()
-        .split(' ')
+fn build_stack(stack_strings: &[&str]) -> Result<Vec<Vec<char>>> {
+    // the stack drawing always starts on the first line of the input
+    let num_stacks_line = stack_strings
         .last()
-        .unwrap()
-        .parse::<u32>()
-        .unwrap();
+        .ok_or_else(|| Error::parse(Day5::DAY, 1, 1, "", "missing stack drawing"))?;
+    let numbers = num_stacks_line.trim_end();
+    let last_number = numbers.trim_start().split(' ').next_back().unwrap_or("");
+    let n_stacks = last_number.parse::<u32>().map_err(|_| {
+        // the last number ends the line, so count the chars before it
+        let before = &numbers[..numbers.len() - last_number.len()];
+        let column = before.chars().count() + 1;
+        Error::parse(Day5::DAY, stack_strings.len(), column, last_number, "invalid stack count")
+    })?;
     let mut stack_vecs: Vec<Vec<char>> = (0..n_stacks).map(|_n| Vec::new()).collect();
     for line in stack_strings.iter().rev().skip(1) {
         for (c, v) in line.chars().skip(1).step_by(4).zip(stack_vecs.iter_mut()) {
@@ -29,69 +30,142 @@ fn build_stack(stack_strings: &Vec<&str>) -> Vec<Vec<char>> {
             }
         }
     }
-    return stack_vecs;
+    return Ok(stack_vecs);
 }
 
-fn parse_moves<'a>(instructions: &'a Vec<&str>) -> impl Iterator<Item = Moves> + 'a {
+fn parse_moves<'a>(
+    instructions: &'a [&str],
+    first_line: usize,
+) -> impl Iterator<Item = Result<Moves>> + 'a {
+    // subtract one to make stacks zero-indexed
+    let stack =
+        || unsigned::<u32>().try_map(|n| n.checked_sub(1).ok_or("stacks are numbered from 1"));
+    let moves = literal("move ")
+        .ignore_then(unsigned())
+        .then_ignore(literal(" from "))
+        .then(stack())
+        .then_ignore(literal(" to "))
+        .then(stack())
+        .map(|((count, source), dest)| Moves {
+            source,
+            dest,
+            count,
+        });
     return instructions
         .iter()
-        .map(|i| RE.captures(i).unwrap())
-        .map(|c| Moves {
-            // subtract one to make stsacks zero-indexed
-            source: c[2].parse::<u32>().unwrap() - 1,
-            dest: c[3].parse::<u32>().unwrap() - 1,
-            count: c[1].parse::<u32>().unwrap(),
-        });
+        .enumerate()
+        .map(move |(i, line)| parse_line(Day5::DAY, first_line + i, line, &moves));
 }
 
-fn perform_moves_part_1(stacks: &mut Vec<Vec<char>>, moves: &Vec<Moves>) -> () 
-{
-    for m in moves.into_iter() {
+fn stack_error(index: u32, message: &str) -> Error {
+    // report stacks one-indexed, as they are in the input
+    return Error::solve(Day5::DAY, &format!("stack {} {}", index + 1, message));
+}
+
+fn perform_moves_part_1(stacks: &mut [Vec<char>], moves: &[Moves]) -> Result<()> {
+    for m in moves.iter() {
         for _i in 1..=m.count {
             // can't borrow both source and dest at the same time
             let tmp = {
-                let source = stacks.get_mut(m.source as usize).unwrap();
-                source.pop().unwrap()
+                let source = stacks
+                    .get_mut(m.source as usize)
+                    .ok_or_else(|| stack_error(m.source, "doesn't exist"))?;
+                source
+                    .pop()
+                    .ok_or_else(|| stack_error(m.source, "ran out of crates"))?
             };
-            let dest = stacks.get_mut(m.dest as usize).unwrap();
+            let dest = stacks
+                .get_mut(m.dest as usize)
+                .ok_or_else(|| stack_error(m.dest, "doesn't exist"))?;
             dest.push(tmp);
         }
     }
+    return Ok(());
+}
+
+/// The starting crate stacks and the rearrangement procedure
+pub struct Procedure {
+    stacks: Vec<Vec<char>>,
+    moves: Vec<Moves>,
+}
+
+pub fn parse(file_contents: &str) -> Result<Procedure> {
+    let mut blocks = blocks(file_contents);
+    let stack_block = blocks.next().unwrap_or_default();
+    let instruction_block = blocks.next().unwrap_or_default();
+    let stack_strings: Vec<&str> = stack_block.iter().map(|(_i, s)| *s).collect();
+    let instruction_strings: Vec<&str> = instruction_block.iter().map(|(_i, s)| *s).collect();
+    let first_move_line = instruction_block.first().map_or(1, |(i, _s)| *i);
+    return Ok(Procedure {
+        stacks: build_stack(&stack_strings)?,
+        moves: parse_moves(&instruction_strings, first_move_line).collect::<Result<_>>()?,
+    });
 }
 
-fn get_stacks_and_moves(file_contents: &str) -> (Vec<Vec<char>>, Vec<Moves>) 
-{
-    let v = divide_iterator_on_predicate(file_contents.lines(), |&s| s == "");
-    let (stack_strings, instruction_strings) = (&v[0], &v[1]);
-    return (build_stack(&stack_strings), parse_moves(&instruction_strings).collect());
+fn top_crates(stacks: &[Vec<char>]) -> Result<String> {
+    return stacks
+        .iter()
+        .enumerate()
+        .map(|(i, v)| v.last().ok_or_else(|| stack_error(i as u32, "ended up empty")))
+        .collect();
 }
 
-pub fn part_1(file_contents: &str) -> String {
-    let (mut stacks, moves) = get_stacks_and_moves(file_contents);
-    perform_moves_part_1(&mut stacks, &moves);
-    return stacks.iter().map(|v| v.last().unwrap()).collect();
+pub fn part_1(procedure: &Procedure) -> Result<String> {
+    let mut stacks = procedure.stacks.clone();
+    perform_moves_part_1(&mut stacks, &procedure.moves)?;
+    return top_crates(&stacks);
 }
 
-fn perform_moves_part_2(stacks: &mut Vec<Vec<char>>, moves: &Vec<Moves>) -> () 
-{
-    for m in moves.into_iter() {
+fn perform_moves_part_2(stacks: &mut [Vec<char>], moves: &[Moves]) -> Result<()> {
+    for m in moves.iter() {
         // can't borrow both source and dest at the same time
         let tmp = {
-            let source = stacks.get_mut(m.source as usize).unwrap();
-            let new_len = source.len() - m.count as usize;
+            let source = stacks
+                .get_mut(m.source as usize)
+                .ok_or_else(|| stack_error(m.source, "doesn't exist"))?;
+            let new_len = source
+                .len()
+                .checked_sub(m.count as usize)
+                .ok_or_else(|| stack_error(m.source, "ran out of crates"))?;
             let copies = source[new_len..].to_owned();
             source.resize(new_len, '_');
             copies
         };
-        let dest = stacks.get_mut(m.dest as usize).unwrap();
+        let dest = stacks
+            .get_mut(m.dest as usize)
+            .ok_or_else(|| stack_error(m.dest, "doesn't exist"))?;
         dest.extend(tmp);
     }
+    return Ok(());
 }
 
-pub fn part_2(file_contents: &str) -> String {
-    let (mut stacks, moves) = get_stacks_and_moves(file_contents);
-    perform_moves_part_2(&mut stacks, &moves);
-    return stacks.iter().map(|v| v.last().unwrap()).collect();
+pub fn part_2(procedure: &Procedure) -> Result<String> {
+    let mut stacks = procedure.stacks.clone();
+    perform_moves_part_2(&mut stacks, &procedure.moves)?;
+    return top_crates(&stacks);
+}
+
+pub struct Day5;
+
+pub static SOLUTION: Registered<Day5> = Registered::new();
+
+impl Solution for Day5 {
+    type Input = Procedure;
+
+    const DAY: u8 = 5;
+    const TITLE: &'static str = "Supply Stacks";
+
+    fn parse(file_contents: &str) -> Result<Self::Input> {
+        return parse(file_contents);
+    }
+
+    fn part_1(input: &Self::Input) -> Result<String> {
+        return part_1(input);
+    }
+
+    fn part_2(input: &Self::Input) -> Result<String> {
+        return part_2(input);
+    }
 }
 
 #[cfg(test)]
@@ -102,7 +176,14 @@ mod tests {
     fn test_stack_build() {
         let stack_strings = vec!["    [D]    ", "[N] [C]    ", "[Z] [M] [P]", " 1   2   3 "];
         let expected = vec![vec!['Z', 'N'], vec!['M', 'C', 'D'], vec!['P']];
-        assert_eq!(build_stack(&stack_strings), expected);
+        assert_eq!(build_stack(&stack_strings), Ok(expected));
+    }
+
+    #[test]
+    fn test_stack_count_error_column() {
+        let stack_strings = vec!["[Z] [M]", " 1  é x "];
+        let expected = Error::parse(5, 2, 7, "x", "invalid stack count");
+        assert_eq!(build_stack(&stack_strings), Err(expected));
     }
 
     #[test]
@@ -119,7 +200,7 @@ mod tests {
             Moves{source: 1, dest: 0, count: 2},
             Moves{source: 0, dest: 1, count: 1},
         ];
-        assert_eq!(parse_moves(&move_strings).collect::<Vec<Moves>>(), expected);
+        assert_eq!(parse_moves(&move_strings, 1).collect::<Result<Vec<Moves>>>(), Ok(expected));
     }
 
     #[test]
@@ -131,7 +212,7 @@ mod tests {
             Moves{source: 1, dest: 0, count: 2},
             Moves{source: 0, dest: 1, count: 1},
         ];
-        perform_moves_part_1(&mut stacks, &moves);
+        perform_moves_part_1(&mut stacks, &moves).unwrap();
         let expected = vec![vec!['C'], vec!['M'], vec!['P', 'D', 'N', 'Z']];
         assert_eq!(stacks, expected);
     }
@@ -145,8 +226,16 @@ mod tests {
             Moves{source: 1, dest: 0, count: 2},
             Moves{source: 0, dest: 1, count: 1},
         ];
-        perform_moves_part_2(&mut stacks, &moves);
+        perform_moves_part_2(&mut stacks, &moves).unwrap();
         let expected = vec![vec!['M'], vec!['C'], vec!['P', 'Z', 'N', 'D']];
         assert_eq!(stacks, expected);
     }
+
+    #[test]
+    fn moving_from_an_empty_stack_is_an_error() {
+        let mut stacks = vec![vec!['Z'], vec![]];
+        let moves = vec![Moves{source: 0, dest: 1, count: 2}];
+        let result = perform_moves_part_1(&mut stacks, &moves);
+        assert_eq!(result, Err(Error::solve(5, "stack 1 ran out of crates")));
+    }
 }
@@ -1,23 +1,49 @@
+use crate::error::{Error, Result};
+use crate::solution::{Registered, Solution};
 use std::collections::HashSet;
 
-fn find_unique_marker_of_length(file_contents: &str, length: usize) -> String {
-    let mut index = None;
-    for (i, _c) in file_contents.chars().enumerate() {
-        let set: HashSet<char> = HashSet::from_iter(file_contents[i..i + length].chars());
-        if set.len() == length {
-            index = Some(i + length);
-            break;
-        }
-    }
-    return index
-        .expect(format!("failed to find unique string of {} chars", length).as_str())
-        .to_string();
+fn find_unique_marker_of_length(signal: &str, length: usize) -> Result<String> {
+    let chars: Vec<char> = signal.chars().collect();
+    let index = chars
+        .windows(length)
+        .position(|w| HashSet::<&char>::from_iter(w).len() == length);
+    return index.map(|i| (i + length).to_string()).ok_or_else(|| {
+        let message = format!("failed to find unique string of {} chars", length);
+        Error::solve(Day6::DAY, &message)
+    });
+}
+
+pub fn parse(file_contents: &str) -> Result<String> {
+    return Ok(file_contents.trim().to_owned());
 }
 
-pub fn part_1(file_contents: &str) -> String {
-    return find_unique_marker_of_length(file_contents, 4);
+pub fn part_1(signal: &str) -> Result<String> {
+    return find_unique_marker_of_length(signal, 4);
 }
 
-pub fn part_2(file_contents: &str) -> String {
-    return find_unique_marker_of_length(file_contents, 14);
+pub fn part_2(signal: &str) -> Result<String> {
+    return find_unique_marker_of_length(signal, 14);
+}
+
+pub struct Day6;
+
+pub static SOLUTION: Registered<Day6> = Registered::new();
+
+impl Solution for Day6 {
+    type Input = String;
+
+    const DAY: u8 = 6;
+    const TITLE: &'static str = "Tuning Trouble";
+
+    fn parse(file_contents: &str) -> Result<Self::Input> {
+        return parse(file_contents);
+    }
+
+    fn part_1(input: &Self::Input) -> Result<String> {
+        return part_1(input);
+    }
+
+    fn part_2(input: &Self::Input) -> Result<String> {
+        return part_2(input);
+    }
 }
@@ -1,42 +1,48 @@
-use lazy_static::lazy_static;
-use regex::Regex;
-
-lazy_static! {
-    static ref CMD_RE: Regex = Regex::new(r"\$").expect("invalid regex");
-    static ref CD_RE: Regex = Regex::new(r"\$ cd (\S+)").expect("invalid regex");
-    static ref LS_RE: Regex = Regex::new(r"\$ ls").expect("invalid regex");
-    static ref DIR_RE: Regex = Regex::new(r"dir (\S+)").expect("invalid regex");
-    static ref FILE_RE: Regex = Regex::new(r"(\d+) (\S+)").expect("invalid regex");
-}
+use crate::error::{Error, Result};
+use crate::parse::{literal, parse_line, unsigned, word, Parser};
+use crate::solution::{Registered, Solution};
 
 #[derive(Debug, PartialEq, Eq)]
-struct File<'a> {
-    name: &'a str,
+struct File {
+    name: String,
     size: u32,
 }
 
-impl<'a> File<'a> {
-    fn new(line: &'a str) -> Self {
-        let caps = FILE_RE.captures(line).unwrap();
-        return Self {
-            name: caps.get(2).unwrap().as_str(),
-            size: caps.get(1).unwrap().as_str().parse::<u32>().unwrap(),
-        };
-    }
+/// One line of the terminal
+enum Output {
+    Cd(String),
+    Ls,
+    Dir,
+    File(File),
+}
+
+fn output() -> impl Parser<Output> {
+    let cd = literal("$ cd ").ignore_then(word()).map(Output::Cd);
+    let ls = literal("$ ls").map(|_u| Output::Ls);
+    let dir = literal("dir ").ignore_then(word()).map(|_name| Output::Dir);
+    let file = unsigned()
+        .then_ignore(literal(" "))
+        .then(word())
+        .map(|(size, name)| Output::File(File { name, size }));
+    return cd
+        .or(ls)
+        .or(dir)
+        .or(file)
+        .expecting("a command, a directory or a file");
 }
 
 #[derive(Debug, PartialEq, Eq)]
-struct Directory<'a> {
-    name: &'a str,
-    sub_dirs: Vec<Directory<'a>>,
-    files: Vec<File<'a>>,
+pub struct Directory {
+    name: String,
+    sub_dirs: Vec<Directory>,
+    files: Vec<File>,
     size: u32,
 }
 
-impl<'a> Directory<'a> {
-    fn new(name: &'a str) -> Self {
+impl Directory {
+    fn new(name: &str) -> Self {
         Self {
-            name,
+            name: name.to_owned(),
             sub_dirs: vec![],
             files: vec![],
             size: 0,
@@ -51,131 +57,161 @@ impl<'a> Directory<'a> {
     }
 }
 
-fn process_cmds<'a>(lines: &Vec<&'a str>, start: usize) -> (Directory<'a>, usize) {
-    let first_line = lines[start];
-    let mut dir: Directory =
-        Directory::new(CD_RE.captures(first_line).unwrap().get(1).unwrap().as_str());
+fn process_cmds(lines: &[&str], start: usize) -> Result<(Directory, usize)> {
+    let output = output();
+    let first_line = lines.get(start).copied().unwrap_or("");
+    let mut dir = match parse_line(Day7::DAY, start + 1, first_line, &output)? {
+        Output::Cd(name) => Directory::new(&name),
+        _ => {
+            let message = "expected `$ cd` into a directory";
+            return Err(Error::parse(Day7::DAY, start + 1, 1, first_line, message));
+        }
+    };
 
     let mut i: usize = start + 1;
     let len = lines.len();
     while i < len {
-        let line = lines[i];
-        let cd_caps = CD_RE.captures(line);
-        if cd_caps.is_some() {
-            if cd_caps.unwrap().get(1).unwrap().as_str() != ".." {
-                let (d, tmp) = process_cmds(lines, i);
+        match parse_line(Day7::DAY, i + 1, lines[i], &output)? {
+            Output::Cd(name) if name == ".." => return Ok((dir, i)),
+            Output::Cd(_name) => {
+                let (d, tmp) = process_cmds(lines, i)?;
                 i = tmp + 1;
+                dir.size += d.size;
                 dir.sub_dirs.push(d);
-                dir.size += dir.sub_dirs.last().unwrap().size;
                 continue;
-            } else {
-                return (dir, i);
             }
-        }
-        if FILE_RE.is_match(line) {
-            dir.files.push(File::new(line));
-            dir.size += dir.files.last().unwrap().size;
-            i += 1;
-            continue;
+            Output::File(file) => {
+                dir.size += file.size;
+                dir.files.push(file);
+            }
+            Output::Ls | Output::Dir => {}
         }
         i += 1;
     }
-    return (dir, i);
+    return Ok((dir, i));
 }
 
-pub fn part_1(file_contents: &str) -> String {
+pub fn parse(file_contents: &str) -> Result<Directory> {
     let lines: Vec<&str> = file_contents.lines().collect();
-    let (root_dir, _i) = process_cmds(&lines, 0);
-    return root_dir
+    let (root_dir, _i) = process_cmds(&lines, 0)?;
+    return Ok(root_dir);
+}
+
+pub fn part_1(root_dir: &Directory) -> Result<String> {
+    return Ok(root_dir
         .get_all_sub_dirs()
         .iter()
         .map(|d| d.size)
         .filter(|&s| s <= 100000)
         .sum::<u32>()
-        .to_string();
+        .to_string());
 }
 
-pub fn part_2(file_contents: &str) -> String {
-    let lines: Vec<&str> = file_contents.lines().collect();
-    let (root_dir, _i) = process_cmds(&lines, 0);
-    let target_to_free = root_dir.size - 40000000;
+pub fn part_2(root_dir: &Directory) -> Result<String> {
+    let target_to_free = root_dir
+        .size
+        .checked_sub(40000000)
+        .ok_or_else(|| Error::solve(Day7::DAY, "there is already enough free space"))?;
     return root_dir
         .get_all_sub_dirs()
         .iter()
         .map(|d| d.size)
         .filter(|&s| s > target_to_free)
         .min()
-        .unwrap()
-        .to_string();
+        .map(|s| s.to_string())
+        .ok_or_else(|| Error::solve(Day7::DAY, "no directory is large enough to delete"));
+}
+
+pub struct Day7;
+
+pub static SOLUTION: Registered<Day7> = Registered::new();
+
+impl Solution for Day7 {
+    type Input = Directory;
+
+    const DAY: u8 = 7;
+    const TITLE: &'static str = "No Space Left On Device";
+
+    fn parse(file_contents: &str) -> Result<Self::Input> {
+        return parse(file_contents);
+    }
+
+    fn part_1(input: &Self::Input) -> Result<String> {
+        return part_1(input);
+    }
+
+    fn part_2(input: &Self::Input) -> Result<String> {
+        return part_2(input);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn example_dir_structure() -> Directory<'static> {
+    fn example_dir_structure() -> Directory {
         // files
         let i = File {
-            name: "i",
+            name: "i".to_owned(),
             size: 584,
         };
         let f = File {
-            name: "f",
+            name: "f".to_owned(),
             size: 29116,
         };
         let g = File {
-            name: "g",
+            name: "g".to_owned(),
             size: 2557,
         };
         let h_lst = File {
-            name: "h.lst",
+            name: "h.lst".to_owned(),
             size: 62596,
         };
         let b_txt = File {
-            name: "b.txt",
+            name: "b.txt".to_owned(),
             size: 14848514,
         };
         let c_dat = File {
-            name: "c.dat",
+            name: "c.dat".to_owned(),
             size: 8504156,
         };
         let j = File {
-            name: "j",
+            name: "j".to_owned(),
             size: 4060174,
         };
         let d_log = File {
-            name: "d.log",
+            name: "d.log".to_owned(),
             size: 8033020,
         };
         let d_ext = File {
-            name: "d.ext",
+            name: "d.ext".to_owned(),
             size: 5626152,
         };
         let k = File {
-            name: "k",
+            name: "k".to_owned(),
             size: 7214296,
         };
         // dirs
         let e_dir = Directory {
-            name: "e",
+            name: "e".to_owned(),
             sub_dirs: vec![],
             files: vec![i],
             size: 584,
         };
         let a_dir = Directory {
-            name: "a",
+            name: "a".to_owned(),
             sub_dirs: vec![e_dir],
             files: vec![f, g, h_lst],
             size: 94853,
         };
         let d_dir = Directory {
-            name: "d",
+            name: "d".to_owned(),
             sub_dirs: vec![],
             files: vec![j, d_log, d_ext, k],
             size: 24933642,
         };
         let root = Directory {
-            name: "/",
+            name: "/".to_owned(),
             sub_dirs: vec![a_dir, d_dir],
             files: vec![b_txt, c_dat],
             size: 48381165,
@@ -186,8 +222,6 @@ mod tests {
     #[test]
     fn constructs_directory_tree() {
         let file_contents = include_str!("../tests/example_files/day7.txt");
-        let lines: Vec<&str> = file_contents.lines().collect();
-        let (root_dir, _i) = process_cmds(&lines, 0);
-        assert_eq!(root_dir, example_dir_structure());
+        assert_eq!(parse(file_contents), Ok(example_dir_structure()));
     }
 }
@@ -1,53 +1,40 @@
+use crate::error::Result;
+use crate::solution::{Registered, Solution};
+use crate::utils::{Grid, Point};
+
 const TALLEST_POSSIBLE_TREE: u8 = 9;
 
-fn parse_input(contents: &str) -> Vec<Vec<u8>> {
-    return contents
-        .lines()
-        .map(|line| {
-            line.chars()
-                .map(|c| c.to_digit(10).unwrap() as u8)
-                .collect()
-        })
-        .collect();
+pub fn parse(contents: &str) -> Result<Grid<u8>> {
+    return Grid::from_str(Day8::DAY, contents, "a tree height", |c| {
+        c.to_digit(10).map(|d| d as u8)
+    });
 }
 
-fn produce_indices(max_x: usize, max_y: usize) -> Vec<Vec<(usize, usize)>> {
-    let i1: Vec<Vec<(usize, usize)>> = (0..max_x)
-        .map(move |i| (0..max_y).map(move |j| (i, j)).collect())
-        .collect();
-    let i2: Vec<Vec<(usize, usize)>> = (0..max_x)
-        .map(move |i| (0..max_y).map(move |j| (i, j)).rev().collect())
-        .rev()
-        .collect();
-    let i3: Vec<Vec<(usize, usize)>> = (0..max_y)
-        .map(move |j| (0..max_x).map(move |i| (i, j)).collect())
-        .collect();
-    let i4: Vec<Vec<(usize, usize)>> = (0..max_y)
-        .map(move |j| (0..max_x).map(move |i| (i, j)).rev().collect())
-        .rev()
-        .collect();
-
-    let mut v: Vec<Vec<(usize, usize)>> = vec![];
-    v.extend(i1);
-    v.extend(i2);
-    v.extend(i3);
-    v.extend(i4);
-    return v;
+/// Every line of trees looking in from an edge, from all four sides
+fn lines_of_sight(trees: &Grid<u8>) -> Vec<Vec<Point>> {
+    let Point(min_x, min_y) = trees.min_point;
+    let Point(max_x, max_y) = trees.max_point;
+    let mut lines: Vec<Vec<Point>> = vec![];
+    for y in min_y..=max_y {
+        lines.push(trees.row(y).map(|(p, _t)| p).collect());
+        lines.push(trees.row(y).rev().map(|(p, _t)| p).collect());
+    }
+    for x in min_x..=max_x {
+        lines.push(trees.column(x).map(|(p, _t)| p).collect());
+        lines.push(trees.column(x).rev().map(|(p, _t)| p).collect());
+    }
+    return lines;
 }
 
-fn find_visible_trees(
-    marks: &mut Vec<Vec<bool>>,
-    trees: &Vec<Vec<u8>>,
-    max_x: usize,
-    max_y: usize,
-) -> () {
-    for iter in produce_indices(max_x, max_y).iter() {
+fn find_visible_trees(trees: &Grid<u8>) -> Grid<bool> {
+    let mut marks = trees.map(|_t| false);
+    for line in lines_of_sight(trees).iter() {
         let mut max: isize = -1;
-        for (i, j) in iter {
-            let t = trees[*i][*j];
+        for p in line {
+            let t = trees[*p];
             if t as isize > max {
                 max = t as isize;
-                marks[*i][*j] = true;
+                marks[*p] = true;
             }
             // we won't be finding anymore trees in this case
             if t == TALLEST_POSSIBLE_TREE {
@@ -55,164 +42,161 @@ fn find_visible_trees(
             }
         }
     }
+    return marks;
 }
 
-pub fn part_1(file_contents: &str) -> String {
-    let trees = parse_input(file_contents);
-    let max_x = trees.len();
-    let max_y = trees[0].len();
-    let mut marks: Vec<Vec<bool>> = vec![vec![false; max_y]; max_x];
-
-    find_visible_trees(&mut marks, &trees, max_x, max_y);
-
-    return marks
-        .into_iter()
-        .flatten()
-        .filter(|m| *m)
+pub fn part_1(trees: &Grid<u8>) -> Result<String> {
+    let marks = find_visible_trees(trees);
+    return Ok(marks
+        .iter_points()
+        .filter(|(_p, m)| **m)
         .count()
-        .to_string();
+        .to_string());
 }
 
-fn score_line(iter: impl Iterator<Item = (usize, usize)>, trees: &Vec<Vec<u8>>, cur_height: &u8) -> usize {
+fn score_line<'a>(line: impl Iterator<Item = (Point, &'a u8)>, cur_height: &u8) -> usize {
     let mut acc = 0;
-    for (i, j) in iter {
+    for (_p, t) in line {
         acc += 1;
-        if trees[i][j] >= *cur_height {
+        if t >= cur_height {
             break;
         }
     }
     return acc;
 }
 
-fn score_tree(trees: &Vec<Vec<u8>>, i: usize, j: usize, max_x: usize, max_y: usize) -> usize {
-    let cur_height = trees[i][j];
-
-    let up = score_line((0..i).rev().map(|x| (x, j)), &trees, &cur_height);
-    let left = score_line((0..j).rev().map(|x| (i, x)), &trees, &cur_height);
-    let right = score_line((j+1..max_y).map(|x| (i, x)), &trees, &cur_height);
-    let down = score_line((i+1..max_x).map(|x| (x, j)), &trees, &cur_height);
+fn score_tree(trees: &Grid<u8>, p: Point) -> usize {
+    let cur_height = trees[p];
 
-    return left * right * up * down;
+    return Point::DIRECTIONS
+        .iter()
+        .map(|d| score_line(trees.ray(p, *d), &cur_height))
+        .product();
 }
 
-fn score_trees(scores: &mut Vec<Vec<usize>>, trees: &Vec<Vec<u8>>, max_x: usize, max_y: usize) -> () {
-    // all edges can be skipped, given one of their directions will be 0 and
-    // therefore will always score 0
-    for i in 1..max_x - 1 {
-        for j in 1..max_y - 1 {
-            scores[i][j] = score_tree(&trees, i, j, max_x, max_y);
-        }
+fn score_trees(trees: &Grid<u8>) -> Grid<usize> {
+    // trees on the edges can see nothing in one direction, so score 0
+    // without needing to be skipped
+    let mut scores = trees.map(|_t| 0);
+    for p in trees.points() {
+        scores[p] = score_tree(trees, p);
     }
+    return scores;
 }
 
-pub fn part_2(file_contents: &str) -> String {
-    let trees = parse_input(file_contents);
-    let max_x = trees.len();
-    let max_y = trees[0].len();
-    let mut scores: Vec<Vec<usize>> = vec![vec![0; max_y]; max_x];
+pub fn part_2(trees: &Grid<u8>) -> Result<String> {
+    let scores = score_trees(trees);
 
-    score_trees(&mut scores, &trees, max_x, max_y);
+    // parsing guarantees there's at least one tree
+    return Ok(scores
+        .iter_points()
+        .map(|(_p, s)| s)
+        .max()
+        .unwrap()
+        .to_string());
+}
 
-    return scores.iter().flatten().max().unwrap().to_string();
+pub struct Day8;
+
+pub static SOLUTION: Registered<Day8> = Registered::new();
+
+impl Solution for Day8 {
+    type Input = Grid<u8>;
+
+    const DAY: u8 = 8;
+    const TITLE: &'static str = "Treetop Tree House";
+
+    fn parse(file_contents: &str) -> Result<Self::Input> {
+        return parse(file_contents);
+    }
+
+    fn part_1(input: &Self::Input) -> Result<String> {
+        return part_1(input);
+    }
+
+    fn part_2(input: &Self::Input) -> Result<String> {
+        return part_2(input);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::Error;
+
+    const EXAMPLE_INPUT: &str = "30373
+25512
+65332
+33549
+35390";
 
     #[test]
-    fn test_produce_iterators() {
-        let result: Vec<Vec<(usize, usize)>> = produce_indices(3, 3);
+    fn test_lines_of_sight() {
+        let trees = parse("123\n456\n789").unwrap();
+        let result: Vec<Vec<Point>> = lines_of_sight(&trees);
         let expected = vec![
-            // first pass forward
-            vec![(0, 0), (0, 1), (0, 2)],
-            vec![(1, 0), (1, 1), (1, 2)],
-            vec![(2, 0), (2, 1), (2, 2)],
-            // first pass backward
-            vec![(2, 2), (2, 1), (2, 0)],
-            vec![(1, 2), (1, 1), (1, 0)],
-            vec![(0, 2), (0, 1), (0, 0)],
-            // second pass forward
-            vec![(0, 0), (1, 0), (2, 0)],
-            vec![(0, 1), (1, 1), (2, 1)],
-            vec![(0, 2), (1, 2), (2, 2)],
-            // second pass backward
-            vec![(2, 2), (1, 2), (0, 2)],
-            vec![(2, 1), (1, 1), (0, 1)],
-            vec![(2, 0), (1, 0), (0, 0)],
+            // rows, each way
+            vec![Point(0, 0), Point(1, 0), Point(2, 0)],
+            vec![Point(2, 0), Point(1, 0), Point(0, 0)],
+            vec![Point(0, 1), Point(1, 1), Point(2, 1)],
+            vec![Point(2, 1), Point(1, 1), Point(0, 1)],
+            vec![Point(0, 2), Point(1, 2), Point(2, 2)],
+            vec![Point(2, 2), Point(1, 2), Point(0, 2)],
+            // columns, each way
+            vec![Point(0, 0), Point(0, 1), Point(0, 2)],
+            vec![Point(0, 2), Point(0, 1), Point(0, 0)],
+            vec![Point(1, 0), Point(1, 1), Point(1, 2)],
+            vec![Point(1, 2), Point(1, 1), Point(1, 0)],
+            vec![Point(2, 0), Point(2, 1), Point(2, 2)],
+            vec![Point(2, 2), Point(2, 1), Point(2, 0)],
         ];
         assert_eq!(result, expected);
     }
 
     #[test]
     fn test_mark_trees() {
-        let trees: Vec<Vec<u8>> = vec![
-            vec![3,0,3,7,3],
-            vec![2,5,5,1,2],
-            vec![6,5,3,3,2],
-            vec![3,3,5,4,9],
-            vec![3,5,3,9,0],
-        ];
-        let expected = vec![
-            vec![true, true, true, true, true],
-            vec![true, true, true, false, true],
-            vec![true, true, false, true, true],
-            vec![true, false, true, false, true],
-            vec![true, true, true, true, true],
-        ];
-        let mut marks = vec![vec![false; 5]; 5];
-        find_visible_trees(&mut marks, &trees, 5, 5);
-        assert_eq!(marks, expected);
+        let trees = parse(EXAMPLE_INPUT).unwrap();
+        let expected = "#####
+###.#
+##.##
+#.#.#
+#####";
+        let marks = find_visible_trees(&trees);
+        assert_eq!(
+            marks.map(|&m| if m { '#' } else { '.' }).to_string(),
+            expected
+        );
     }
 
     #[test]
     fn test_score_tree_provided_example_1() {
-        let trees: Vec<Vec<u8>> = vec![
-            vec![3,0,3,7,3],
-            vec![2,5,5,1,2],
-            vec![6,5,3,3,2],
-            vec![3,3,5,4,9],
-            vec![3,5,3,9,0],
-        ];
-        assert_eq!(score_tree(&trees, 1, 2, 5, 5), 4);
+        let trees = parse(EXAMPLE_INPUT).unwrap();
+        assert_eq!(score_tree(&trees, Point(2, 1)), 4);
     }
 
     #[test]
     fn test_score_tree_provided_example_2() {
-        let trees: Vec<Vec<u8>> = vec![
-            vec![3,0,3,7,3],
-            vec![2,5,5,1,2],
-            vec![6,5,3,3,2],
-            vec![3,3,5,4,9],
-            vec![3,5,3,9,0],
-        ];
-        assert_eq!(score_tree(&trees, 3, 2, 5, 5), 8);
+        let trees = parse(EXAMPLE_INPUT).unwrap();
+        assert_eq!(score_tree(&trees, Point(2, 3)), 8);
     }
 
     #[test]
     fn test_score_trees() {
-        let trees: Vec<Vec<u8>> = vec![
-            vec![3,0,3,7,3],
-            vec![2,5,5,1,2],
-            vec![6,5,3,3,2],
-            vec![3,3,5,4,9],
-            vec![3,5,3,9,0],
-        ];
-        let expected: Vec<Vec<usize>> = vec![
-            vec![0,0,0,0,0],
-            vec![0,1,4,1,0],
-            vec![0,6,1,2,0],
-            vec![0,1,8,3,0],
-            vec![0,0,0,0,0],
-        ];
-        let mut scores: Vec<Vec<usize>> = vec![
-            vec![0,0,0,0,0],
-            vec![0,0,0,0,0],
-            vec![0,0,0,0,0],
-            vec![0,0,0,0,0],
-            vec![0,0,0,0,0],
-        ];
-        score_trees(&mut scores, &trees, 5, 5);
-        assert_eq!(scores, expected);
+        let trees = parse(EXAMPLE_INPUT).unwrap();
+        let expected = "00000
+01410
+06120
+01830
+00000";
+        assert_eq!(score_trees(&trees).to_string(), expected);
+    }
+
+    #[test]
+    fn ragged_rows_are_rejected() {
+        let error = parse("303\n25\n").unwrap_err();
+        assert_eq!(
+            error,
+            Error::parse(Day8::DAY, 2, 1, "25", "rows must all be the same length")
+        );
     }
 }
@@ -1,13 +1,11 @@
-use impl_ops::*;
-use regex::Regex;
-use std::collections::HashSet;
-use std::ops;
-
-lazy_static! {
-    static ref RE: Regex = Regex::new(r"(\w) (\d+)").expect("invalid regex");
-}
-
-#[derive(PartialEq, Eq, Debug)]
+use crate::error::Result;
+use crate::export::{Image, Recorder, Rgb};
+use crate::parse::{literal, one_of, parse_lines, unsigned, Parser};
+use crate::solution::{Registered, Solution};
+use crate::utils::{Grid, Point, SparseGrid};
+use crate::visualise::{Colour, Frame, Glyph, Visualiser};
+
+#[derive(PartialEq, Eq, Debug, Clone)]
 enum Direction {
     Left,
     Right,
@@ -15,155 +13,284 @@ enum Direction {
     Down,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
-struct Vector(isize, isize);
-
-impl Vector {
-    fn r#move(&self, direction: &Self) -> Self {
-        return Self(self.0 + direction.0, self.1 + direction.1);
-    }
-
-    fn move_mutable(&mut self, direction: &Self) -> () {
-        self.0 += direction.0;
-        self.1 += direction.1;
-    }
-
-    fn is_touching(&self, other: &Self) -> bool {
-        let x = (self.0 - other.0).abs();
-        let y = (self.1 - other.1).abs();
-        return x <= 1 && y <= 1;
-    }
-
-    fn determine_tail_vector(&self) -> Self {
-        let x = if self.0 == 0 {
-            0
-        } else {
-            self.0 / self.0 * self.0.signum()
-        };
-        let y = if self.1 == 0 {
-            0
-        } else {
-            self.1 / self.1 * self.1.signum()
-        };
-        return Self(x, y);
-    }
-}
-
-impl_op_ex!(+ |a: &Vector, b: &Vector| -> Vector {Vector(a.0 + b.0, a.1 + b.1)});
-impl_op_ex!(-|a: &Vector, b: &Vector| -> Vector { Vector(a.0 - b.0, a.1 - b.1) });
-
 #[derive(PartialEq, Eq, Debug)]
-struct Motion {
+pub struct Motion {
     direction: Direction,
-    vector: Vector,
+    vector: Point,
     amount: usize,
 }
 
-impl Motion {
-    fn new(line: &str) -> Self {
-        let caps = RE.captures(line).unwrap();
-        let (direction, vector) = match caps.get(1).unwrap().as_str() {
-            "L" => (Direction::Left, Vector(-1, 0)),
-            "R" => (Direction::Right, Vector(1, 0)),
-            "U" => (Direction::Up, Vector(0, 1)),
-            "D" => (Direction::Down, Vector(0, -1)),
-            _ => panic!("Unrecognised input!"),
-        };
-        let amount = caps.get(2).unwrap().as_str().parse::<usize>().unwrap();
-        return Self {
+fn motion() -> impl Parser<Motion> {
+    let direction = one_of([
+        ("L", (Direction::Left, Point(-1, 0))),
+        ("R", (Direction::Right, Point(1, 0))),
+        ("U", (Direction::Up, Point(0, 1))),
+        ("D", (Direction::Down, Point(0, -1))),
+    ]);
+    return direction.then_ignore(literal(" ")).then(unsigned()).map(
+        |((direction, vector), amount)| Motion {
             direction,
             vector,
             amount,
-        };
-    }
+        },
+    );
 }
 
 #[derive(PartialEq, Eq, Debug)]
 struct Rope {
-    knots: Vec<Vector>,
+    knots: Vec<Point>,
 }
 
 impl Rope {
     fn new(length: usize) -> Self {
         return Self {
-            knots: vec![Vector(0, 0); length],
+            knots: vec![Point::ORIGIN; length],
         };
     }
 
-    fn r#move(&self, motion: &Motion) -> (Self, HashSet<Vector>) {
+    /// Make a whole motion, marking everywhere the tail goes in `visited`
+    fn r#move(&self, motion: &Motion, visited: &mut SparseGrid<Tile>) -> Self {
         let Motion {
             direction: _,
             vector,
             amount,
         } = motion;
-        let mut knots = self.knots.clone();
-        let mut set = HashSet::from([*self.knots.last().unwrap()]);
+        let mut rope = Self {
+            knots: self.knots.clone(),
+        };
+        rope.mark_tail(visited);
         for _i in 0..*amount {
-            let mut new_knots: Vec<Vector> = vec![];
-            {
-                let head = knots.get_mut(0).unwrap();
-                head.move_mutable(&vector);
-                new_knots.push(*head);
-            }
-            for t in knots.iter().skip(1) {
-                let h = new_knots.last().unwrap();
-                if !h.is_touching(&t) {
-                    let diff = h - t;
-                    let tail_vector = diff.determine_tail_vector();
-                    new_knots.push(t.r#move(&tail_vector));
-                } else {
-                    new_knots.push(*t);
-                }
+            rope = rope.step(vector);
+            rope.mark_tail(visited);
+        }
+        return rope;
+    }
+
+    fn mark_tail(&self, visited: &mut SparseGrid<Tile>) {
+        visited.insert(on_screen(self.knots.last().unwrap()), Tile::Visited);
+    }
+
+    /// Move the head one space, and the rest of the rope after it
+    fn step(&self, vector: &Point) -> Self {
+        let mut new_knots: Vec<Point> = vec![self.knots[0] + *vector];
+        for t in self.knots.iter().skip(1) {
+            let h = new_knots.last().unwrap();
+            // not touching, so move a space straight, or diagonally, towards it
+            if h.chebyshev(*t) > 1 {
+                new_knots.push(*t + (*h - *t).signum());
+            } else {
+                new_knots.push(*t);
             }
-            set.insert(*new_knots.last().unwrap());
-            knots = new_knots;
         }
-        return (Self { knots }, set);
+        return Self { knots: new_knots };
     }
 }
 
-fn parse_input(contents: &str) -> Vec<Motion> {
-    return contents.lines().map(|line| Motion::new(line)).collect();
+pub fn parse(contents: &str) -> Result<Vec<Motion>> {
+    return parse_lines(Day9::DAY, contents, &motion());
+}
+
+fn solver(motions: &[Motion], rope_length: usize) -> Result<String> {
+    let mut visited = SparseGrid::new();
+    let _r = motions
+        .iter()
+        .fold(Rope::new(rope_length), |r, m| r.r#move(m, &mut visited));
+    return Ok(visited.len().to_string());
 }
 
-fn solver(file_contents: &str, rope_length: usize) -> String {
-    let motions = parse_input(file_contents);
-    let (_r, visited) =
-        motions
-            .iter()
-            .fold((Rope::new(rope_length), HashSet::new()), |(r, s), m| {
-                let (new_r, new_s) = r.r#move(&m);
-                (new_r, s.union(&new_s).cloned().collect())
-            });
-    return visited.len().to_string();
+pub fn part_1(motions: &[Motion]) -> Result<String> {
+    return solver(motions, 2);
 }
 
-pub fn part_1(file_contents: &str) -> String {
-    return solver(file_contents, 2);
+pub fn part_2(motions: &[Motion]) -> Result<String> {
+    return solver(motions, 10);
 }
 
-pub fn part_2(file_contents: &str) -> String {
-    return solver(file_contents, 10);
+/// Where a position is drawn. Up is positive here but down on the screen.
+fn on_screen(v: &Point) -> Point {
+    return Point(v.0, -v.1);
+}
+
+fn draw_rope(
+    vis: &Visualiser,
+    bounds: (Point, Point),
+    rope: &Rope,
+    visited: &SparseGrid<Tile>,
+) -> Frame {
+    let mut frame = vis.frame(bounds.0, bounds.1);
+    frame.plot_all(
+        visited.points(),
+        vis.glyph("visited", Glyph::new('#', Colour::Grey)),
+    );
+    frame.plot(
+        Point::ORIGIN,
+        vis.glyph("start", Glyph::new('s', Colour::Default)),
+    );
+    let knot = vis.glyph("knot", Glyph::new('o', Colour::Yellow));
+    let last = rope.knots.len() - 1;
+    // back to front, so knots further up the rope are drawn over those behind
+    for (i, k) in rope.knots.iter().enumerate().rev() {
+        let glyph = match i {
+            0 => vis.glyph("head", Glyph::new('H', Colour::Red)),
+            i if i == last => vis.glyph("tail", Glyph::new('T', Colour::Green)),
+            i => knot.with_char(char::from_digit(i as u32 % 10, 10).unwrap()),
+        };
+        frame.plot(on_screen(k), glyph);
+    }
+    frame.caption(&format!("{} positions visited by the tail", visited.len()));
+    return frame;
+}
+
+/// The corners of everywhere the rope goes, as drawn. The rest of the rope
+/// never strays outside where the head has been, so only the head is needed.
+fn bounds(motions: &[Motion]) -> (Point, Point) {
+    let mut head = Point::ORIGIN;
+    let (mut min, mut max) = (Point::ORIGIN, Point::ORIGIN);
+    for m in motions.iter() {
+        head += m.vector * m.amount as isize;
+        let Point(x, y) = on_screen(&head);
+        min = Point(min.0.min(x), min.1.min(y));
+        max = Point(max.0.max(x), max.1.max(y));
+    }
+    return (min, max);
+}
+
+/// Pull a rope of `length` knots through every motion, calling `on_step`
+/// after each step. Returns the rope at the end and everywhere its tail went.
+fn pull_rope(
+    motions: &[Motion],
+    length: usize,
+    mut on_step: impl FnMut(&Rope, &SparseGrid<Tile>),
+) -> (Rope, SparseGrid<Tile>) {
+    let mut rope = Rope::new(length);
+    let mut visited = SparseGrid::new();
+    rope.mark_tail(&mut visited);
+    for m in motions.iter() {
+        for _i in 0..m.amount {
+            rope = rope.step(&m.vector);
+            rope.mark_tail(&mut visited);
+            on_step(&rope, &visited);
+        }
+    }
+    return (rope, visited);
+}
+
+fn visualise(motions: &[Motion], part: u8, vis: &mut Visualiser) -> Result<String> {
+    let length = if part == 1 { 2 } else { 10 };
+    let bounds = bounds(motions);
+    let (rope, visited) = pull_rope(motions, length, |rope, visited| {
+        vis.step(|vis| draw_rope(vis, bounds, rope, visited));
+    });
+    vis.last(draw_rope(vis, bounds, &rope, &visited));
+    return Ok(visited.len().to_string());
+}
+
+/// What's at a point on the map of where the rope has been
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+enum Tile {
+    Empty,
+    Visited,
+    Start,
+    Knot,
+    Tail,
+    Head,
+}
+
+impl Tile {
+    fn colour(&self) -> Rgb {
+        return match self {
+            Self::Empty => [20, 20, 40],
+            Self::Visited => [70, 70, 110],
+            Self::Start => [255, 255, 255],
+            Self::Knot => [230, 190, 80],
+            Self::Tail => [80, 200, 100],
+            Self::Head => [220, 60, 60],
+        };
+    }
+}
+
+fn picture(bounds: (Point, Point), rope: &Rope, visited: &SparseGrid<Tile>) -> Image {
+    let mut tiles = Grid::new(bounds.0, bounds.1, Tile::Empty);
+    for (p, t) in visited.iter_points() {
+        tiles[p] = *t;
+    }
+    tiles[Point::ORIGIN] = Tile::Start;
+    let last = rope.knots.len() - 1;
+    for (i, k) in rope.knots.iter().enumerate().rev() {
+        let tile = match i {
+            0 => Tile::Head,
+            i if i == last => Tile::Tail,
+            _i => Tile::Knot,
+        };
+        tiles[on_screen(k)] = tile;
+    }
+    return Image::from_grid(&tiles, Tile::colour);
+}
+
+fn export(motions: &[Motion], part: u8, images: &mut Recorder) -> Result<String> {
+    let length = if part == 1 { 2 } else { 10 };
+    let bounds = bounds(motions);
+    let (rope, visited) = pull_rope(motions, length, |rope, visited| {
+        images.step(|| picture(bounds, rope, visited));
+    });
+    images.last(picture(bounds, &rope, &visited));
+    return Ok(visited.len().to_string());
+}
+
+pub struct Day9;
+
+pub static SOLUTION: Registered<Day9> = Registered::new();
+
+impl Solution for Day9 {
+    type Input = Vec<Motion>;
+
+    const DAY: u8 = 9;
+    const TITLE: &'static str = "Rope Bridge";
+
+    fn parse(file_contents: &str) -> Result<Self::Input> {
+        return parse(file_contents);
+    }
+
+    fn part_1(input: &Self::Input) -> Result<String> {
+        return part_1(input);
+    }
+
+    fn part_2(input: &Self::Input) -> Result<String> {
+        return part_2(input);
+    }
+
+    fn visualise(input: &Self::Input, part: u8, vis: &mut Visualiser) -> Option<Result<String>> {
+        return Some(visualise(input, part, vis));
+    }
+
+    fn export(input: &Self::Input, part: u8, images: &mut Recorder) -> Option<Result<String>> {
+        return Some(export(input, part, images));
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashSet;
+
+    /// Where the tail went, the right way up
+    fn trail(visited: &SparseGrid<Tile>) -> HashSet<Point> {
+        return visited.points().map(|Point(x, y)| Point(x, -y)).collect();
+    }
 
     #[test]
     fn vector_operations() {
-        let v1 = Vector(1, 5);
-        let v2 = Vector(7, 16);
-
-        assert_eq!(v1 + v2, Vector(8, 21));
-        assert_eq!(&v1 + v2, Vector(8, 21));
-        assert_eq!(v1 + &v2, Vector(8, 21));
-        assert_eq!(&v1 + &v2, Vector(8, 21));
-
-        assert_eq!(v1 - v2, Vector(-6, -11));
-        assert_eq!(&v1 - v2, Vector(-6, -11));
-        assert_eq!(v1 - &v2, Vector(-6, -11));
-        assert_eq!(&v1 - &v2, Vector(-6, -11));
+        let v1 = Point(1, 5);
+        let v2 = Point(7, 16);
+
+        assert_eq!(v1 + v2, Point(8, 21));
+        assert_eq!(v1 - v2, Point(-6, -11));
+
+        let mut v = v1;
+        v += v2;
+        assert_eq!(v, Point(8, 21));
+        v -= v2;
+        assert_eq!(v, v1);
     }
 
     #[test]
@@ -171,367 +298,389 @@ mod tests {
         let expected = vec![
             Motion {
                 direction: Direction::Right,
-                vector: Vector(1, 0),
+                vector: Point(1, 0),
                 amount: 4,
             },
             Motion {
                 direction: Direction::Up,
-                vector: Vector(0, 1),
+                vector: Point(0, 1),
                 amount: 4,
             },
             Motion {
                 direction: Direction::Left,
-                vector: Vector(-1, 0),
+                vector: Point(-1, 0),
                 amount: 3,
             },
             Motion {
                 direction: Direction::Down,
-                vector: Vector(0, -1),
+                vector: Point(0, -1),
                 amount: 1,
             },
             Motion {
                 direction: Direction::Right,
-                vector: Vector(1, 0),
+                vector: Point(1, 0),
                 amount: 4,
             },
             Motion {
                 direction: Direction::Down,
-                vector: Vector(0, -1),
+                vector: Point(0, -1),
                 amount: 1,
             },
             Motion {
                 direction: Direction::Left,
-                vector: Vector(-1, 0),
+                vector: Point(-1, 0),
                 amount: 5,
             },
             Motion {
                 direction: Direction::Right,
-                vector: Vector(1, 0),
+                vector: Point(1, 0),
                 amount: 2,
             },
         ];
         let input = include_str!("../tests/example_files/day9_part1.txt");
-        assert_eq!(parse_input(input), expected);
+        assert_eq!(parse(input), Ok(expected));
     }
 
     #[test]
     fn rope_moves_correctly_example_part_1() {
         let r = Rope::new(2);
 
-        let m = Motion::new("R 4");
-        let (r, s) = r.r#move(&m);
+        let m = parse("R 4").unwrap().remove(0);
+        let mut s = SparseGrid::new();
+        let r = r.r#move(&m, &mut s);
         assert_eq!(
             r,
             Rope {
-                knots: vec![Vector(4, 0), Vector(3, 0)]
+                knots: vec![Point(4, 0), Point(3, 0)]
             }
         );
         assert_eq!(
-            s,
-            HashSet::from([Vector(0, 0), Vector(1, 0), Vector(2, 0), Vector(3, 0)])
+            trail(&s),
+            HashSet::from([Point(0, 0), Point(1, 0), Point(2, 0), Point(3, 0)])
         );
 
-        let m = Motion::new("U 4");
-        let (r, s) = r.r#move(&m);
+        let m = parse("U 4").unwrap().remove(0);
+        let mut s = SparseGrid::new();
+        let r = r.r#move(&m, &mut s);
         assert_eq!(
             r,
             Rope {
-                knots: vec![Vector(4, 4), Vector(4, 3)]
+                knots: vec![Point(4, 4), Point(4, 3)]
             }
         );
         assert_eq!(
-            s,
-            HashSet::from([Vector(3, 0), Vector(4, 1), Vector(4, 2), Vector(4, 3)])
+            trail(&s),
+            HashSet::from([Point(3, 0), Point(4, 1), Point(4, 2), Point(4, 3)])
         );
 
-        let m = Motion::new("L 3");
-        let (r, s) = r.r#move(&m);
+        let m = parse("L 3").unwrap().remove(0);
+        let mut s = SparseGrid::new();
+        let r = r.r#move(&m, &mut s);
         assert_eq!(
             r,
             Rope {
-                knots: vec![Vector(1, 4), Vector(2, 4)]
+                knots: vec![Point(1, 4), Point(2, 4)]
             }
         );
-        assert_eq!(s, HashSet::from([Vector(4, 3), Vector(3, 4), Vector(2, 4)]));
+        assert_eq!(
+            trail(&s),
+            HashSet::from([Point(4, 3), Point(3, 4), Point(2, 4)])
+        );
 
-        let m = Motion::new("D 1");
-        let (r, s) = r.r#move(&m);
+        let m = parse("D 1").unwrap().remove(0);
+        let mut s = SparseGrid::new();
+        let r = r.r#move(&m, &mut s);
         assert_eq!(
             r,
             Rope {
-                knots: vec![Vector(1, 3), Vector(2, 4)]
+                knots: vec![Point(1, 3), Point(2, 4)]
             }
         );
-        assert_eq!(s, HashSet::from([Vector(2, 4)]));
+        assert_eq!(trail(&s), HashSet::from([Point(2, 4)]));
 
-        let m = Motion::new("R 4");
-        let (r, s) = r.r#move(&m);
+        let m = parse("R 4").unwrap().remove(0);
+        let mut s = SparseGrid::new();
+        let r = r.r#move(&m, &mut s);
         assert_eq!(
             r,
             Rope {
-                knots: vec![Vector(5, 3), Vector(4, 3)]
+                knots: vec![Point(5, 3), Point(4, 3)]
             }
         );
-        assert_eq!(s, HashSet::from([Vector(2, 4), Vector(3, 3), Vector(4, 3)]));
+        assert_eq!(
+            trail(&s),
+            HashSet::from([Point(2, 4), Point(3, 3), Point(4, 3)])
+        );
 
-        let m = Motion::new("D 1");
-        let (r, s) = r.r#move(&m);
+        let m = parse("D 1").unwrap().remove(0);
+        let mut s = SparseGrid::new();
+        let r = r.r#move(&m, &mut s);
         assert_eq!(
             r,
             Rope {
-                knots: vec![Vector(5, 2), Vector(4, 3)]
+                knots: vec![Point(5, 2), Point(4, 3)]
             }
         );
-        assert_eq!(s, HashSet::from([Vector(4, 3)]));
+        assert_eq!(trail(&s), HashSet::from([Point(4, 3)]));
 
-        let m = Motion::new("L 5");
-        let (r, s) = r.r#move(&m);
+        let m = parse("L 5").unwrap().remove(0);
+        let mut s = SparseGrid::new();
+        let r = r.r#move(&m, &mut s);
         assert_eq!(
             r,
             Rope {
-                knots: vec![Vector(0, 2), Vector(1, 2)]
+                knots: vec![Point(0, 2), Point(1, 2)]
             }
         );
         assert_eq!(
-            s,
-            HashSet::from([Vector(4, 3), Vector(3, 2), Vector(2, 2), Vector(1, 2)])
+            trail(&s),
+            HashSet::from([Point(4, 3), Point(3, 2), Point(2, 2), Point(1, 2)])
         );
 
-        let m = Motion::new("R 2");
-        let (r, s) = r.r#move(&m);
+        let m = parse("R 2").unwrap().remove(0);
+        let mut s = SparseGrid::new();
+        let r = r.r#move(&m, &mut s);
         assert_eq!(
             r,
             Rope {
-                knots: vec![Vector(2, 2), Vector(1, 2)]
+                knots: vec![Point(2, 2), Point(1, 2)]
             }
         );
-        assert_eq!(s, HashSet::from([Vector(1, 2)]));
+        assert_eq!(trail(&s), HashSet::from([Point(1, 2)]));
     }
 
     #[test]
     fn rope_moves_correctly_example_part_2() {
         let r = Rope::new(10);
 
-        let m = Motion::new("R 5");
-        let (r, s) = r.r#move(&m);
+        let m = parse("R 5").unwrap().remove(0);
+        let mut s = SparseGrid::new();
+        let r = r.r#move(&m, &mut s);
         assert_eq!(
             r,
             Rope {
                 knots: vec![
-                    Vector(5, 0),
-                    Vector(4, 0),
-                    Vector(3, 0),
-                    Vector(2, 0),
-                    Vector(1, 0),
-                    Vector(0, 0),
-                    Vector(0, 0),
-                    Vector(0, 0),
-                    Vector(0, 0),
-                    Vector(0, 0),
+                    Point(5, 0),
+                    Point(4, 0),
+                    Point(3, 0),
+                    Point(2, 0),
+                    Point(1, 0),
+                    Point(0, 0),
+                    Point(0, 0),
+                    Point(0, 0),
+                    Point(0, 0),
+                    Point(0, 0),
                 ]
             }
         );
-        assert_eq!(s, HashSet::from([Vector(0, 0)]));
+        assert_eq!(trail(&s), HashSet::from([Point(0, 0)]));
 
-        let m = Motion::new("U 8");
-        let (r, s) = r.r#move(&m);
+        let m = parse("U 8").unwrap().remove(0);
+        let mut s = SparseGrid::new();
+        let r = r.r#move(&m, &mut s);
         assert_eq!(
             r,
             Rope {
                 knots: vec![
-                    Vector(5, 8),
-                    Vector(5, 7),
-                    Vector(5, 6),
-                    Vector(5, 5),
-                    Vector(5, 4),
-                    Vector(4, 4),
-                    Vector(3, 3),
-                    Vector(2, 2),
-                    Vector(1, 1),
-                    Vector(0, 0),
+                    Point(5, 8),
+                    Point(5, 7),
+                    Point(5, 6),
+                    Point(5, 5),
+                    Point(5, 4),
+                    Point(4, 4),
+                    Point(3, 3),
+                    Point(2, 2),
+                    Point(1, 1),
+                    Point(0, 0),
                 ]
             }
         );
-        assert_eq!(s, HashSet::from([Vector(0, 0)]));
+        assert_eq!(trail(&s), HashSet::from([Point(0, 0)]));
 
-        let m = Motion::new("L 8");
-        let (r, s) = r.r#move(&m);
+        let m = parse("L 8").unwrap().remove(0);
+        let mut s = SparseGrid::new();
+        let r = r.r#move(&m, &mut s);
         assert_eq!(
             r,
             Rope {
                 knots: vec![
-                    Vector(-3, 8),
-                    Vector(-2, 8),
-                    Vector(-1, 8),
-                    Vector(0, 8),
-                    Vector(1, 8),
-                    Vector(1, 7),
-                    Vector(1, 6),
-                    Vector(1, 5),
-                    Vector(1, 4),
-                    Vector(1, 3),
+                    Point(-3, 8),
+                    Point(-2, 8),
+                    Point(-1, 8),
+                    Point(0, 8),
+                    Point(1, 8),
+                    Point(1, 7),
+                    Point(1, 6),
+                    Point(1, 5),
+                    Point(1, 4),
+                    Point(1, 3),
                 ]
             }
         );
         assert_eq!(
-            s,
-            HashSet::from([Vector(0, 0), Vector(1, 1), Vector(2, 2), Vector(1, 3),])
+            trail(&s),
+            HashSet::from([Point(0, 0), Point(1, 1), Point(2, 2), Point(1, 3),])
         );
 
-        let m = Motion::new("D 3");
-        let (r, s) = r.r#move(&m);
+        let m = parse("D 3").unwrap().remove(0);
+        let mut s = SparseGrid::new();
+        let r = r.r#move(&m, &mut s);
         assert_eq!(
             r,
             Rope {
                 knots: vec![
-                    Vector(-3, 5),
-                    Vector(-3, 6),
-                    Vector(-2, 7),
-                    Vector(-1, 7),
-                    Vector(0, 7),
-                    Vector(1, 7),
-                    Vector(1, 6),
-                    Vector(1, 5),
-                    Vector(1, 4),
-                    Vector(1, 3),
+                    Point(-3, 5),
+                    Point(-3, 6),
+                    Point(-2, 7),
+                    Point(-1, 7),
+                    Point(0, 7),
+                    Point(1, 7),
+                    Point(1, 6),
+                    Point(1, 5),
+                    Point(1, 4),
+                    Point(1, 3),
                 ]
             }
         );
-        assert_eq!(s, HashSet::from([Vector(1, 3),]));
+        assert_eq!(trail(&s), HashSet::from([Point(1, 3),]));
 
-        let m = Motion::new("R 17");
-        let (r, s) = r.r#move(&m);
+        let m = parse("R 17").unwrap().remove(0);
+        let mut s = SparseGrid::new();
+        let r = r.r#move(&m, &mut s);
         assert_eq!(
             r,
             Rope {
                 knots: vec![
-                    Vector(14, 5),
-                    Vector(13, 5),
-                    Vector(12, 5),
-                    Vector(11, 5),
-                    Vector(10, 5),
-                    Vector(9, 5),
-                    Vector(8, 5),
-                    Vector(7, 5),
-                    Vector(6, 5),
-                    Vector(5, 5),
+                    Point(14, 5),
+                    Point(13, 5),
+                    Point(12, 5),
+                    Point(11, 5),
+                    Point(10, 5),
+                    Point(9, 5),
+                    Point(8, 5),
+                    Point(7, 5),
+                    Point(6, 5),
+                    Point(5, 5),
                 ]
             }
         );
         assert_eq!(
-            s,
+            trail(&s),
             HashSet::from([
-                Vector(1, 3),
-                Vector(2, 4),
-                Vector(3, 5),
-                Vector(4, 5),
-                Vector(5, 5),
+                Point(1, 3),
+                Point(2, 4),
+                Point(3, 5),
+                Point(4, 5),
+                Point(5, 5),
             ])
         );
 
-        let m = Motion::new("D 10");
-        let (r, s) = r.r#move(&m);
+        let m = parse("D 10").unwrap().remove(0);
+        let mut s = SparseGrid::new();
+        let r = r.r#move(&m, &mut s);
         assert_eq!(
             r,
             Rope {
                 knots: vec![
-                    Vector(14, -5),
-                    Vector(14, -4),
-                    Vector(14, -3),
-                    Vector(14, -2),
-                    Vector(14, -1),
-                    Vector(14, 0),
-                    Vector(13, 0),
-                    Vector(12, 0),
-                    Vector(11, 0),
-                    Vector(10, 0),
+                    Point(14, -5),
+                    Point(14, -4),
+                    Point(14, -3),
+                    Point(14, -2),
+                    Point(14, -1),
+                    Point(14, 0),
+                    Point(13, 0),
+                    Point(12, 0),
+                    Point(11, 0),
+                    Point(10, 0),
                 ]
             }
         );
         assert_eq!(
-            s,
+            trail(&s),
             HashSet::from([
-                Vector(5, 5),
-                Vector(6, 4),
-                Vector(7, 3),
-                Vector(8, 2),
-                Vector(9, 1),
-                Vector(10, 0),
+                Point(5, 5),
+                Point(6, 4),
+                Point(7, 3),
+                Point(8, 2),
+                Point(9, 1),
+                Point(10, 0),
             ])
         );
 
-        let m = Motion::new("L 25");
-        let (r, s) = r.r#move(&m);
+        let m = parse("L 25").unwrap().remove(0);
+        let mut s = SparseGrid::new();
+        let r = r.r#move(&m, &mut s);
         assert_eq!(
             r,
             Rope {
                 knots: vec![
-                    Vector(-11, -5),
-                    Vector(-10, -5),
-                    Vector(-9, -5),
-                    Vector(-8, -5),
-                    Vector(-7, -5),
-                    Vector(-6, -5),
-                    Vector(-5, -5),
-                    Vector(-4, -5),
-                    Vector(-3, -5),
-                    Vector(-2, -5),
+                    Point(-11, -5),
+                    Point(-10, -5),
+                    Point(-9, -5),
+                    Point(-8, -5),
+                    Point(-7, -5),
+                    Point(-6, -5),
+                    Point(-5, -5),
+                    Point(-4, -5),
+                    Point(-3, -5),
+                    Point(-2, -5),
                 ]
             }
         );
         assert_eq!(
-            s,
+            trail(&s),
             HashSet::from([
-                Vector(10, 0),
-                Vector(9, -1),
-                Vector(8, -2),
-                Vector(7, -3),
-                Vector(6, -4),
-                Vector(5, -5),
-                Vector(4, -5),
-                Vector(3, -5),
-                Vector(2, -5),
-                Vector(1, -5),
-                Vector(0, -5),
-                Vector(-1, -5),
-                Vector(-2, -5),
+                Point(10, 0),
+                Point(9, -1),
+                Point(8, -2),
+                Point(7, -3),
+                Point(6, -4),
+                Point(5, -5),
+                Point(4, -5),
+                Point(3, -5),
+                Point(2, -5),
+                Point(1, -5),
+                Point(0, -5),
+                Point(-1, -5),
+                Point(-2, -5),
             ])
         );
 
-        let m = Motion::new("U 20");
-        let (r, s) = r.r#move(&m);
+        let m = parse("U 20").unwrap().remove(0);
+        let mut s = SparseGrid::new();
+        let r = r.r#move(&m, &mut s);
         assert_eq!(
             r,
             Rope {
                 knots: vec![
-                    Vector(-11, 15),
-                    Vector(-11, 14),
-                    Vector(-11, 13),
-                    Vector(-11, 12),
-                    Vector(-11, 11),
-                    Vector(-11, 10),
-                    Vector(-11, 9),
-                    Vector(-11, 8),
-                    Vector(-11, 7),
-                    Vector(-11, 6),
+                    Point(-11, 15),
+                    Point(-11, 14),
+                    Point(-11, 13),
+                    Point(-11, 12),
+                    Point(-11, 11),
+                    Point(-11, 10),
+                    Point(-11, 9),
+                    Point(-11, 8),
+                    Point(-11, 7),
+                    Point(-11, 6),
                 ]
             }
         );
         assert_eq!(
-            s,
+            trail(&s),
             HashSet::from([
-                Vector(-2, -5),
-                Vector(-3, -4),
-                Vector(-4, -3),
-                Vector(-5, -2),
-                Vector(-6, -1),
-                Vector(-7, 0),
-                Vector(-8, 1),
-                Vector(-9, 2),
-                Vector(-10, 3),
-                Vector(-11, 4),
-                Vector(-11, 5),
-                Vector(-11, 6),
+                Point(-2, -5),
+                Point(-3, -4),
+                Point(-4, -3),
+                Point(-5, -2),
+                Point(-6, -1),
+                Point(-7, 0),
+                Point(-8, 1),
+                Point(-9, 2),
+                Point(-10, 3),
+                Point(-11, 4),
+                Point(-11, 5),
+                Point(-11, 6),
             ])
         );
     }
@@ -0,0 +1,73 @@
+use std::fmt;
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Error {
+    /// Part of the input couldn't be understood. Lines and columns are
+    /// one-indexed.
+    Parse {
+        day: u8,
+        line: usize,
+        column: usize,
+        text: String,
+        message: String,
+    },
+    /// The input was understood but no answer could be found from it
+    Solve {
+        day: u8,
+        message: String,
+    },
+    NotImplemented {
+        day: u8,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn parse(day: u8, line: usize, column: usize, text: &str, message: &str) -> Self {
+        return Self::Parse {
+            day,
+            line,
+            column,
+            text: text.to_owned(),
+            message: message.to_owned(),
+        };
+    }
+
+    pub fn solve(day: u8, message: &str) -> Self {
+        return Self::Solve {
+            day,
+            message: message.to_owned(),
+        };
+    }
+
+    pub fn day(&self) -> u8 {
+        return match self {
+            Self::Parse { day, .. } | Self::Solve { day, .. } | Self::NotImplemented { day } => {
+                *day
+            }
+        };
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Self::Parse {
+                day,
+                line,
+                column,
+                text,
+                message,
+            } => write!(
+                f,
+                "day {} line {} column {}: {}: `{}`",
+                day, line, column, message, text
+            ),
+            Self::Solve { day, message } => write!(f, "day {}: {}", day, message),
+            Self::NotImplemented { day } => write!(f, "day {}: not implemented", day),
+        };
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,368 @@
+use crate::utils::Grid;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+pub type Rgb = [u8; 3];
+
+/// A picture of a grid, one block of `scale` by `scale` pixels per cell.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    /// Colour every cell of `grid` with `colour`. `x` goes across the
+    /// picture and `y` down it.
+    pub fn from_grid<T>(grid: &Grid<T>, colour: impl Fn(&T) -> Rgb) -> Self {
+        return Self {
+            width: grid.width(),
+            height: grid.height(),
+            pixels: grid.iter_points().map(|(_p, t)| colour(t)).collect(),
+        };
+    }
+
+    pub fn scaled(&self, scale: usize) -> Self {
+        let scale = scale.max(1);
+        let mut pixels = Vec::with_capacity(self.pixels.len() * scale * scale);
+        for row in self.pixels.chunks(self.width.max(1)) {
+            let wide: Vec<Rgb> = row
+                .iter()
+                .flat_map(|p| std::iter::repeat_n(*p, scale))
+                .collect();
+            for _i in 0..scale {
+                pixels.extend(wide.iter());
+            }
+        }
+        return Self {
+            width: self.width * scale,
+            height: self.height * scale,
+            pixels,
+        };
+    }
+
+    /// Binary PPM, the simplest format there is to write.
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        out.extend(self.pixels.iter().flatten());
+        return out;
+    }
+
+    pub fn write_png(&self, out: impl Write) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(out, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        let data: Vec<u8> = self.pixels.iter().flatten().copied().collect();
+        return writer.write_image_data(&data).map_err(io::Error::other);
+    }
+
+    /// The colours used, and each pixel as an index into them. `None` if
+    /// there are too many colours for a GIF.
+    fn indexed(&self) -> Option<(Vec<u8>, Vec<u8>)> {
+        let mut palette: Vec<Rgb> = vec![];
+        let mut indices = Vec::with_capacity(self.pixels.len());
+        for p in self.pixels.iter() {
+            let i = match palette.iter().position(|c| c == p) {
+                Some(i) => i,
+                None => {
+                    palette.push(*p);
+                    palette.len() - 1
+                }
+            };
+            indices.push(u8::try_from(i).ok()?);
+        }
+        return Some((palette.concat(), indices));
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Format {
+    Ppm,
+    Png,
+    /// Every frame in one animation
+    Gif,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        return match extension.as_str() {
+            "ppm" => Some(Self::Ppm),
+            "png" => Some(Self::Png),
+            "gif" => Some(Self::Gif),
+            _ => None,
+        };
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Options {
+    /// Pixels along each side of a cell.
+    pub scale: usize,
+    /// Keep every this many steps of the simulation. `None` keeps only the
+    /// final state, except in a GIF which keeps every step.
+    pub step: Option<usize>,
+    /// Time between the frames of a GIF, in hundredths of a second.
+    pub delay: u16,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        return Self {
+            scale: 4,
+            step: None,
+            delay: 5,
+        };
+    }
+}
+
+/// Saves pictures of a simulation as it runs. Frames are written as soon as
+/// they're made, as a long simulation can have far too many to keep.
+///
+/// A GIF gets every frame. PPM and PNG get one file for the final state or,
+/// when keeping steps, a numbered file per frame next to `path`.
+pub struct Recorder {
+    path: PathBuf,
+    format: Format,
+    options: Options,
+    steps: usize,
+    written: Vec<PathBuf>,
+    gif: Option<gif::Encoder<BufWriter<File>>>,
+    error: Option<io::Error>,
+}
+
+impl Recorder {
+    pub fn new(path: &Path, options: Options) -> Result<Self, String> {
+        let format = Format::from_path(path).ok_or_else(|| {
+            format!(
+                "can't tell the format of {}, expected .ppm, .png or .gif",
+                path.display()
+            )
+        })?;
+        return Ok(Self {
+            path: path.to_owned(),
+            format,
+            options,
+            steps: 0,
+            written: vec![],
+            gif: None,
+            error: None,
+        });
+    }
+
+    fn step_size(&self) -> Option<usize> {
+        return match (self.format, self.options.step) {
+            (Format::Gif, None) => Some(1),
+            (_f, step) => step.map(|s| s.max(1)),
+        };
+    }
+
+    /// One step of the simulation. `draw` is only called for the steps that
+    /// are kept.
+    pub fn step(&mut self, draw: impl FnOnce() -> Image) {
+        let kept = self
+            .step_size()
+            .is_some_and(|s| self.steps.is_multiple_of(s));
+        self.steps += 1;
+        if kept && self.error.is_none() {
+            let image = draw();
+            self.write(&image);
+        }
+    }
+
+    /// The state the simulation finished in, which is always kept.
+    pub fn last(&mut self, image: Image) {
+        if self.error.is_none() {
+            self.write(&image);
+        }
+    }
+
+    fn frame_path(&self) -> PathBuf {
+        if self.step_size().is_none() {
+            return self.path.clone();
+        }
+        let stem = self.path.file_stem().unwrap_or_default().to_string_lossy();
+        let extension = self.path.extension().unwrap_or_default().to_string_lossy();
+        let name = format!("{}-{:05}.{}", stem, self.written.len(), extension);
+        return self.path.with_file_name(name);
+    }
+
+    fn write(&mut self, image: &Image) {
+        let image = image.scaled(self.options.scale);
+        let result = match self.format {
+            Format::Ppm => {
+                let path = self.frame_path();
+                let result = std::fs::write(&path, image.to_ppm());
+                self.written.push(path);
+                result
+            }
+            Format::Png => {
+                let path = self.frame_path();
+                let result = File::create(&path).and_then(|f| image.write_png(BufWriter::new(f)));
+                self.written.push(path);
+                result
+            }
+            Format::Gif => self.write_gif_frame(&image),
+        };
+        self.error = result.err();
+    }
+
+    fn write_gif_frame(&mut self, image: &Image) -> io::Result<()> {
+        if self.gif.is_none() {
+            let out = BufWriter::new(File::create(&self.path)?);
+            let too_big = |_e| io::Error::new(io::ErrorKind::InvalidInput, "too big for a GIF");
+            let w = u16::try_from(image.width).map_err(too_big)?;
+            let h = u16::try_from(image.height).map_err(too_big)?;
+            let mut encoder = gif::Encoder::new(out, w, h, &[]).map_err(io::Error::other)?;
+            encoder
+                .set_repeat(gif::Repeat::Infinite)
+                .map_err(io::Error::other)?;
+            self.gif = Some(encoder);
+            self.written.push(self.path.clone());
+        }
+        let (palette, indices) = image.indexed().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "more than 256 colours for a GIF",
+            )
+        })?;
+        let mut frame = gif::Frame::from_palette_pixels(
+            image.width as u16,
+            image.height as u16,
+            indices,
+            palette,
+            None,
+        );
+        frame.delay = self.options.delay;
+        return self
+            .gif
+            .as_mut()
+            .unwrap()
+            .write_frame(&frame)
+            .map_err(io::Error::other);
+    }
+
+    /// Finish writing, returning the files that were written.
+    pub fn finish(mut self) -> io::Result<Vec<PathBuf>> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        if let Some(encoder) = self.gif.take() {
+            encoder.into_inner().map_err(io::Error::other)?.flush()?;
+        }
+        return Ok(self.written);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::Point;
+
+    const BLACK: Rgb = [0, 0, 0];
+    const WHITE: Rgb = [255, 255, 255];
+
+    fn checks() -> Image {
+        let mut grid = Grid::new(Point(0, 0), Point(1, 0), false);
+        grid[Point(1, 0)] = true;
+        return Image::from_grid(&grid, |b| if *b { WHITE } else { BLACK });
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("aoc2022-export-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        return dir;
+    }
+
+    #[test]
+    fn grids_become_pixels_across_then_down() {
+        let image = checks();
+        assert_eq!((image.width, image.height), (2, 1));
+        assert_eq!(image.pixels, vec![BLACK, WHITE]);
+        let scaled = image.scaled(2);
+        assert_eq!((scaled.width, scaled.height), (4, 2));
+        assert_eq!(&scaled.pixels[..4], &[BLACK, BLACK, WHITE, WHITE]);
+        assert_eq!(&scaled.pixels[..4], &scaled.pixels[4..8]);
+    }
+
+    #[test]
+    fn ppm_has_a_header_then_the_bytes() {
+        let ppm = checks().to_ppm();
+        let header = b"P6\n2 1\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        assert_eq!(ppm.len(), header.len() + 3 * 2);
+        assert_eq!(&ppm[header.len() + 3..header.len() + 6], &WHITE);
+    }
+
+    #[test]
+    fn formats_come_from_the_extension() {
+        assert_eq!(
+            Format::from_path(Path::new("a/sand.GIF")),
+            Some(Format::Gif)
+        );
+        assert_eq!(Format::from_path(Path::new("rope.png")), Some(Format::Png));
+        assert_eq!(Format::from_path(Path::new("rope.jpg")), None);
+        assert!(Recorder::new(Path::new("rope"), Options::default()).is_err());
+    }
+
+    #[test]
+    fn still_images_keep_the_last_frame_unless_asked_for_steps() {
+        let dir = temp_dir("still");
+        let options = Options {
+            scale: 1,
+            ..Options::default()
+        };
+        let mut recorder = Recorder::new(&dir.join("out.png"), options.clone()).unwrap();
+        recorder.step(|| panic!("steps aren't kept"));
+        recorder.last(checks());
+        assert_eq!(recorder.finish().unwrap(), vec![dir.join("out.png")]);
+        let png = std::fs::read(dir.join("out.png")).unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+
+        let options = Options {
+            step: Some(2),
+            ..options
+        };
+        let mut recorder = Recorder::new(&dir.join("out.ppm"), options).unwrap();
+        for _i in 0..3 {
+            recorder.step(checks);
+        }
+        recorder.last(checks());
+        let names: Vec<PathBuf> = ["out-00000.ppm", "out-00001.ppm", "out-00002.ppm"]
+            .iter()
+            .map(|n| dir.join(n))
+            .collect();
+        assert_eq!(recorder.finish().unwrap(), names);
+        assert_eq!(std::fs::read(&names[1]).unwrap(), checks().to_ppm());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn gifs_get_every_frame() {
+        let dir = temp_dir("gif");
+        let path = dir.join("out.gif");
+        let mut recorder = Recorder::new(&path, Options::default()).unwrap();
+        for _i in 0..3 {
+            recorder.step(checks);
+        }
+        recorder.last(checks());
+        assert_eq!(recorder.finish().unwrap(), vec![path.clone()]);
+
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(File::open(&path).unwrap())
+            .unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (8, 4));
+        let mut frames = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 5);
+            frames += 1;
+        }
+        assert_eq!(frames, 4);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,31 +1,56 @@
 #[macro_use]
 extern crate lazy_static;
-extern crate impl_ops;
 
-mod utils;
+pub mod answers;
+pub mod client;
+pub mod error;
+pub mod export;
+pub mod parallel;
+pub mod parse;
+pub mod report;
+pub mod scaffold;
+pub mod search;
+pub mod solution;
+pub mod submit;
+pub mod timing;
+pub mod utils;
+pub mod visualise;
+pub mod watch;
 
-pub mod day1;
-pub mod day2;
-pub mod day3;
-pub mod day4;
-pub mod day5;
-pub mod day6;
-pub mod day7;
-pub mod day8;
-pub mod day9;
-pub mod day10;
-pub mod day11;
-pub mod day12;
-pub mod day13;
-pub mod day14;
-pub mod day15;
-pub mod day16;
-pub mod day17;
-pub mod day18;
-pub mod day19;
-pub mod day20;
-pub mod day21;
-pub mod day22;
-pub mod day23;
-pub mod day24;
-pub mod day25;
+use solution::AnySolution;
+use std::path::{Path, PathBuf};
+
+/// Declares every day module and builds the registry from the same list, so
+/// the binary, the tests and anything embedding the crate all see the same
+/// days.
+macro_rules! days {
+    ($($module:ident,)*) => {
+        $(pub mod $module;)*
+
+        /// All days, in order. Days that haven't been solved yet are included
+        /// and report themselves as not implemented.
+        pub fn solutions() -> Vec<&'static dyn AnySolution> {
+            return vec![$(&$module::SOLUTION,)*];
+        }
+    };
+}
+
+days!(
+    day1, day2, day3, day4, day5, day6, day7, day8, day9, day10, day11, day12, day13,
+    day14, day15, day16, day17, day18, day19, day20, day21, day22, day23, day24, day25,
+);
+
+/// The example input for one part of a day. A part with its own example,
+/// such as `day9_part2.txt`, uses that in place of the day's `day9.txt`.
+pub fn example_path(dir: &Path, day: u8, part: u8) -> PathBuf {
+    let own = dir.join(format!("day{}_part{}.txt", day, part));
+    if own.is_file() {
+        return own;
+    }
+    return dir.join(format!("day{}.txt", day));
+}
+
+/// Look up a single day in the registry.
+pub fn get_solution(day: u8) -> Option<&'static dyn AnySolution> {
+    return solutions().into_iter().find(|s| s.day() == day);
+}
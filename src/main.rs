@@ -1,106 +1,1131 @@
-#[macro_use]
-extern crate lazy_static;
-extern crate load_file;
-extern crate impl_ops;
-
-mod utils;
-
-use clap::Parser;
-use std::collections::HashMap;
-
-pub mod day1;
-pub mod day2;
-pub mod day3;
-pub mod day4;
-pub mod day5;
-pub mod day6;
-pub mod day7;
-pub mod day8;
-pub mod day9;
-pub mod day10;
-pub mod day11;
-pub mod day12;
-pub mod day13;
-pub mod day14;
-pub mod day15;
-pub mod day16;
-pub mod day17;
-pub mod day18;
-pub mod day19;
-pub mod day20;
-pub mod day21;
-pub mod day22;
-pub mod day23;
-pub mod day24;
-pub mod day25;
+use aoc2022::answers::Answers;
+use aoc2022::client::{self, Client, Fetched};
+use aoc2022::day1;
+use aoc2022::error::{Error, Result};
+use aoc2022::export::{self, Recorder};
+use aoc2022::parallel;
+use aoc2022::report::{self, Record, Status};
+use aoc2022::scaffold;
+use aoc2022::solution::{AnySolution, ParsedInput};
+use aoc2022::submit::{self, Submitted};
+use aoc2022::timing::{self, Timing};
+use aoc2022::visualise::{self, Glyph, Viewport, Visualiser};
+use aoc2022::watch::{Change, Watcher};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, fs, io, process};
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, args_conflicts_with_subcommands = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
     /// Which day to run. Default is to run all days.
     #[arg(short, long)]
     day: Option<u8>,
     /// Which part to run. Default is to run both parts.
     #[arg(short, long)]
     part: Option<u8>,
+    /// Directory containing the `dayN.txt` puzzle inputs.
+    #[arg(long, env = "AOC_INPUT_DIR", default_value = "input_files")]
+    input_dir: PathBuf,
+    /// Use a different input file for one day, e.g. `--input 3=day3_alt.txt`.
+    /// May be given more than once. A plain path applies to the day chosen
+    /// with `--day`, and `-` reads the input from stdin.
+    #[arg(short, long, value_name = "[DAY=]PATH", value_parser = parse_input_override)]
+    input: Vec<(Option<u8>, PathBuf)>,
+    /// Time parsing and each part, and print a summary table at the end.
+    #[arg(long)]
+    time: bool,
+    /// Like `--time`, but run every step N times and report the min, median
+    /// and mean.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    bench: Option<u32>,
+    /// How many days and parts to run at once.
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    jobs: u32,
+    /// How to print the results. JSON and CSV include the status and time
+    /// taken for every part.
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+    /// File of known answers for the real inputs. Defaults to `answers.toml`
+    /// next to the input directory.
+    #[arg(long)]
+    answers: Option<PathBuf>,
+    /// Check every answer against the answers file, failing on any mismatch.
+    #[arg(long)]
+    verify: bool,
+    /// Save the answers from this run in the answers file.
+    #[arg(long)]
+    record: bool,
+    /// Run against the example inputs instead, showing the expected answers.
+    #[arg(long, conflicts_with_all = ["input", "verify", "record"])]
+    example: bool,
+    /// Run against a particular example file, e.g. `day9_part2.txt`. Implies
+    /// `--example`.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["input", "verify", "record"])]
+    example_file: Option<PathBuf>,
+    /// Directory containing the example inputs and their `answers.toml`.
+    #[arg(long, default_value = "tests/example_files")]
+    example_dir: PathBuf,
+    /// Keep running the day, against its input and its examples, every time
+    /// one of them or the source changes. Source changes rebuild the binary.
+    #[arg(long, requires = "day", conflicts_with_all = ["verify", "record"])]
+    watch: bool,
+    /// Draw the day's simulation in the terminal while solving it.
+    #[arg(
+        long,
+        requires = "day",
+        conflicts_with_all = ["watch", "time", "bench", "verify", "record"]
+    )]
+    visualise: bool,
+    /// Frames per second to draw at, or 0 for as fast as possible.
+    #[arg(long, default_value_t = 30, requires = "visualise")]
+    fps: u32,
+    /// Only draw every Nth step of the simulation, along with the last.
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        requires = "visualise",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    frame_step: u32,
+    /// Only draw this part of the world, in the coordinates it's drawn in.
+    #[arg(long, value_name = "LEFT,TOP,RIGHT,BOTTOM", requires = "visualise")]
+    viewport: Option<Viewport>,
+    /// Draw something else with another character or colour, e.g.
+    /// `--glyph sand=o:yellow`. May be given more than once.
+    #[arg(
+        long,
+        value_name = "ROLE=CHAR[:COLOUR]",
+        requires = "visualise",
+        value_parser = visualise::parse_glyph
+    )]
+    glyph: Vec<(String, Glyph)>,
+    /// Draw without colours. Also turned off by setting `NO_COLOR`.
+    #[arg(long, requires = "visualise")]
+    no_colour: bool,
+    /// Save pictures of the day's simulation while solving it. The extension
+    /// picks the format: `.ppm`, `.png` or an animated `.gif`.
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "day",
+        conflicts_with_all = ["visualise", "watch", "time", "bench", "verify", "record"]
+    )]
+    export: Option<PathBuf>,
+    /// Pixels along each side of a cell in the pictures.
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 4,
+        requires = "export",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    export_scale: u32,
+    /// Keep every Nth step of the simulation. PPM and PNG then get a numbered
+    /// file per step, rather than one of the final state. GIFs keep every
+    /// step unless told otherwise.
+    #[arg(
+        long,
+        value_name = "N",
+        requires = "export",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    export_step: Option<u32>,
+    /// Milliseconds between the frames of a GIF.
+    #[arg(long, value_name = "MS", default_value_t = 50, requires = "export")]
+    export_delay: u32,
+    /// Day 1 only: show the K largest calorie totals and how the elves
+    /// compare, reading the input a line at a time.
+    #[arg(
+        long,
+        value_name = "K",
+        requires = "day",
+        conflicts_with_all = ["visualise", "export", "watch", "time", "bench", "verify", "record"]
+    )]
+    top: Option<usize>,
+    /// With `--top`, also print the median total. Every elf's total has to be
+    /// kept for it, so memory grows with the size of the input.
+    #[arg(long, requires = "top")]
+    median: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Download missing puzzle inputs into the input directory.
+    Fetch(FetchArgs),
+    /// Solve one part from the real input and submit the answer.
+    Submit(SubmitArgs),
+    /// Start a new day: a module to fill in, an example file and tests for
+    /// it. Run from the top of the repository.
+    NewDay(NewDayArgs),
+}
+
+/// Options for talking to the website.
+#[derive(clap::Args, Debug)]
+struct Online {
+    /// Session cookie of a logged in browser.
+    #[arg(long, env = "AOC_SESSION", hide_env_values = true)]
+    session: String,
+    /// Where the puzzles live. Can point at a stand-in server for testing.
+    #[arg(long, env = "AOC_BASE_URL", default_value = client::DEFAULT_BASE_URL)]
+    base_url: String,
+    /// Minimum number of seconds between requests.
+    #[arg(long, default_value_t = 3)]
+    delay: u64,
+}
+
+impl Online {
+    fn client(&self) -> Client {
+        return Client::new(
+            &self.base_url,
+            &self.session,
+            Duration::from_secs(self.delay),
+        );
+    }
+}
+
+#[derive(clap::Args, Debug)]
+struct FetchArgs {
+    /// Which day to fetch. Default is every implemented day that's missing
+    /// its input.
+    #[arg(short, long)]
+    day: Option<u8>,
+    /// Directory to save the `dayN.txt` puzzle inputs in.
+    #[arg(long, env = "AOC_INPUT_DIR", default_value = "input_files")]
+    input_dir: PathBuf,
+    #[command(flatten)]
+    online: Online,
 }
 
-macro_rules! runner {
-    ($($module:tt,)*) => (
-        {
-            let mut m = HashMap::new();
-            let mut i: u8 = 0;
-            $(
-                i += 1;
-                let file_contents = load_file::load_str!(format!("../input_files/day{}.txt", i).as_str());
-                m.insert(i, HashMap::from([(1u8, ($module::part_1 as fn(&str) -> String, file_contents)), (2u8, ($module::part_2 as fn(&str) -> String, file_contents))]));
-            )*
-            m
+#[derive(clap::Args, Debug)]
+struct SubmitArgs {
+    /// Which day to submit.
+    #[arg(short, long)]
+    day: u8,
+    /// Which part to submit.
+    #[arg(short, long)]
+    part: u8,
+    /// Directory containing the `dayN.txt` puzzle inputs.
+    #[arg(long, env = "AOC_INPUT_DIR", default_value = "input_files")]
+    input_dir: PathBuf,
+    /// File of known answers. Right answers are added to it, and every
+    /// outcome is logged in `submissions.log` next to it.
+    #[arg(long)]
+    answers: Option<PathBuf>,
+    #[command(flatten)]
+    online: Online,
+}
+
+#[derive(clap::Args, Debug)]
+struct NewDayArgs {
+    /// Which day to start.
+    day: u8,
+    /// The puzzle's title. Default is the one the day's stub already has.
+    #[arg(long)]
+    title: Option<String>,
+}
+
+fn new_day(args: &NewDayArgs) -> i32 {
+    if !(1..=25).contains(&args.day) {
+        eprintln!("Invalid day input: {}", args.day);
+        return 2;
+    }
+    let root = Path::new(".");
+    if !root.join("src").join("lib.rs").exists() {
+        eprintln!("No src/lib.rs here, run from the top of the repository");
+        return 2;
+    }
+    match scaffold::new_day(root, args.day, args.title.as_deref()) {
+        Ok(written) => {
+            for path in written {
+                println!("Wrote {}", path.display());
+            }
+            println!(
+                "Paste the example into tests/example_files/day{}.txt and fill in the expected answers in tests/tests.rs",
+                args.day
+            );
+            return 0;
         }
-    )
+        Err(e) => {
+            eprintln!("Failed to set up day {}: {}", args.day, e);
+            return 1;
+        }
+    }
 }
 
-fn main() {
-    let func_map: HashMap<u8, HashMap<u8, (fn(&str) -> String, &str)>> = runner!(
-        day1, day2, day3, day4, day5, day6,
-        day7, day8, day9, day10, day11, day12,
-        day13, day14, day15,
-        // day16,
-        // day17,
-        // day18,
-        // day19,
-        // day20,
-        // day21,
-        // day22,
-        // day23,
-        // day24,
-        // day25,
-    );
-    let args = Args::parse();
+fn submit_answer(args: &SubmitArgs) -> i32 {
+    let solution = match aoc2022::get_solution(args.day) {
+        Some(x) if args.part == 1 || args.part == 2 => x,
+        _ => {
+            eprintln!("Invalid day or part: day {} part {}", args.day, args.part);
+            return 2;
+        }
+    };
+    let path = args.input_dir.join(format!("day{}.txt", args.day));
+    let contents = match fs::read_to_string(&path) {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", path.display(), e);
+            return 1;
+        }
+    };
+    let answer = match solution.run(&contents, args.part) {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return 1;
+        }
+    };
+    println!("Day {} part {} answer: {}", args.day, args.part, answer);
+    // e.g. day 10's picture, which has to be read by a person
+    if answer.contains('\n') {
+        eprintln!("The answer spans several lines, so it has to be read and submitted by hand");
+        return 1;
+    }
+
+    let answers = match &args.answers {
+        Some(x) => x.clone(),
+        None => default_answers_path(&args.input_dir),
+    };
+    let mut client = args.online.client();
+    return match submit::submit(&mut client, &answers, args.day, args.part, &answer) {
+        Ok(Submitted::Sent(outcome)) => {
+            println!("Submitted: {}", outcome);
+            if outcome == submit::Outcome::Right {
+                0
+            } else {
+                1
+            }
+        }
+        Ok(Submitted::AlreadyRight) => {
+            println!("Already known to be right, not submitting it again");
+            0
+        }
+        Ok(Submitted::KnownWrong(reason)) => {
+            println!("Not submitting, {}", reason);
+            1
+        }
+        Err(e) => {
+            eprintln!("Failed to submit: {}", e);
+            1
+        }
+    };
+}
 
-    let days = match args.day {
+fn fetch_inputs(args: &FetchArgs) -> i32 {
+    let days: Vec<u8> = match args.day {
+        Some(x) if (1..=25).contains(&x) => vec![x],
         Some(x) => {
-            if !func_map.contains_key(&x) {
-                panic!("Invalid day input. Solution may not be implemented yet");
+            eprintln!("Invalid day input: {}", x);
+            return 2;
+        }
+        None => aoc2022::solutions()
+            .iter()
+            .filter(|s| s.implemented())
+            .map(|s| s.day())
+            .collect(),
+    };
+    let mut client = args.online.client();
+    let mut failed = false;
+    for day in days {
+        match client::fetch(&mut client, &args.input_dir, day) {
+            Ok(Fetched::Downloaded(path)) => {
+                println!("Fetched day {} into {}", day, path.display())
+            }
+            Ok(Fetched::AlreadyCached(path)) => {
+                println!("Already have {}, not fetching it again", path.display())
+            }
+            Err(e) => {
+                eprintln!("Failed to fetch day {}: {}", day, e);
+                failed = true;
+            }
+        }
+    }
+    return if failed { 1 } else { 0 };
+}
+
+/// One input file and the parts of its day to run against it. Unimplemented
+/// days don't have a file.
+struct Task<'a> {
+    solution: &'a dyn AnySolution,
+    path: Option<PathBuf>,
+    parts: Vec<u8>,
+}
+
+fn parse_input_override(s: &str) -> std::result::Result<(Option<u8>, PathBuf), String> {
+    // anything that doesn't start with a day number is just a path, so file
+    // names containing `=` still work
+    if let Some((day, path)) = s.split_once('=') {
+        if let Ok(day) = day.parse::<u8>() {
+            return Ok((Some(day), PathBuf::from(path)));
+        }
+    }
+    if s.is_empty() {
+        return Err("expected a path".to_owned());
+    }
+    return Ok((None, PathBuf::from(s)));
+}
+
+fn is_stdin(path: &Path) -> bool {
+    return path == Path::new("-");
+}
+
+fn read_input(path: &Path) -> io::Result<String> {
+    if is_stdin(path) {
+        return io::read_to_string(io::stdin());
+    }
+    return fs::read_to_string(path);
+}
+
+fn input_path(args: &Args, day: u8) -> PathBuf {
+    let applies = |d: &Option<u8>| d.or(args.day) == Some(day);
+    return match args.input.iter().rev().find(|(d, _p)| applies(d)) {
+        Some((_d, p)) => p.clone(),
+        None => args.input_dir.join(format!("day{}.txt", day)),
+    };
+}
+
+fn example_path(args: &Args, day: u8, part: u8) -> PathBuf {
+    return match &args.example_file {
+        Some(f) => args.example_dir.join(f),
+        None => aoc2022::example_path(&args.example_dir, day, part),
+    };
+}
+
+/// Work out which file each day and part should read, either the real input or
+/// the example. Parts that read the same file are grouped into one task so the
+/// file is only parsed once.
+fn plan<'a>(
+    args: &Args,
+    solutions: &[&'a dyn AnySolution],
+    parts: &[u8],
+    example: bool,
+) -> Vec<Task<'a>> {
+    let mut tasks: Vec<Task> = vec![];
+    for solution in solutions.iter() {
+        let day = solution.day();
+        for part in parts.iter() {
+            let path = if !solution.implemented() {
+                None
+            } else if example {
+                Some(example_path(args, day, *part))
+            } else {
+                Some(input_path(args, day))
+            };
+            match tasks.last_mut() {
+                Some(t) if t.solution.day() == day && t.path == path => t.parts.push(*part),
+                _ => tasks.push(Task {
+                    solution: *solution,
+                    path,
+                    parts: vec![*part],
+                }),
+            }
+        }
+    }
+    return tasks;
+}
+
+/// The example answers that apply to this run. A variant example file only
+/// has expected answers if `answers.toml` gives some for that file.
+fn expected_example_answers(args: &Args, tasks: &[Task]) -> io::Result<Answers> {
+    let known = Answers::load(&args.example_dir.join("answers.toml"))?;
+    let mut expected = Answers::default();
+    for task in tasks.iter() {
+        let day = task.solution.day();
+        let path = match &task.path {
+            Some(p) => p,
+            None => continue,
+        };
+        for part in task.parts.iter() {
+            if let Some(x) = known.for_example(&args.example_dir, day, *part, path) {
+                expected.insert(day, *part, x);
+            }
+        }
+    }
+    return Ok(expected);
+}
+
+fn default_answers_path(input_dir: &Path) -> PathBuf {
+    return input_dir.parent().unwrap_or(input_dir).join("answers.toml");
+}
+
+fn answers_path(args: &Args) -> PathBuf {
+    return match &args.answers {
+        Some(p) => p.clone(),
+        None => default_answers_path(&args.input_dir),
+    };
+}
+
+fn is_wrong(answers: &Answers, r: &Record) -> bool {
+    return match (answers.get(r.day, r.part), &r.answer) {
+        (Some(x), Some(answer)) => x != answer,
+        _ => false,
+    };
+}
+
+/// Returns whether every answer that has been recorded matched.
+fn verify(answers: &Answers, records: &[Record]) -> bool {
+    let mut matched = 0;
+    let mut mismatched = 0;
+    for r in records.iter().filter(|r| r.status == Status::Ok) {
+        let answer = r.answer.as_deref().unwrap_or_default();
+        match answers.get(r.day, r.part) {
+            Some(_x) if !is_wrong(answers, r) => matched += 1,
+            Some(x) => {
+                eprintln!(
+                    "Day {} part {}: expected {}, got {}",
+                    r.day, r.part, x, answer
+                );
+                mismatched += 1;
+            }
+            None => eprintln!("Day {} part {}: no recorded answer", r.day, r.part),
+        }
+    }
+    eprintln!(
+        "Verified {} answer(s), {} wrong",
+        matched + mismatched,
+        mismatched
+    );
+    return mismatched == 0;
+}
+
+fn panicked(day: u8, message: &str) -> Error {
+    return Error::solve(day, &format!("panicked: {}", message));
+}
+
+/// Parse and solve every task, running up to `jobs` steps at once. The parts
+/// of a task share its parsed input. Results are in task and part order no
+/// matter which finishes first.
+fn run(
+    tasks: &[(&Task, String)],
+    repeats: usize,
+    jobs: usize,
+) -> (Vec<Record>, Vec<(u8, String, Timing)>) {
+    let parsed: Vec<Result<(ParsedInput, Timing)>> =
+        parallel::map(jobs, tasks, |(task, contents)| {
+            return timing::time(repeats, || task.solution.parse(contents));
+        })
+        .into_iter()
+        .zip(tasks.iter())
+        .map(|(r, (task, _c))| r.unwrap_or_else(|e| Err(panicked(task.solution.day(), &e))))
+        .collect();
+    let steps: Vec<(usize, u8)> = parsed
+        .iter()
+        .enumerate()
+        .filter(|(_i, p)| p.is_ok())
+        .flat_map(|(i, _p)| tasks[i].0.parts.iter().map(move |j| (i, *j)))
+        .collect();
+    let solved = parallel::map(jobs, &steps, |(i, j)| {
+        let (input, _t) = parsed[*i].as_ref().unwrap();
+        return timing::time(repeats, || tasks[*i].0.solution.solve(input, *j));
+    });
+
+    let mut solved = solved.into_iter();
+    let mut records = vec![];
+    let mut timings = vec![];
+    for ((task, _c), parse) in tasks.iter().zip(parsed.iter()) {
+        let (solution, parts) = (task.solution, &task.parts);
+        let day = solution.day();
+        let parse_timing = match parse {
+            Ok((_input, t)) => *t,
+            Err(e) => {
+                // neither part can run without the input
+                for j in parts.iter() {
+                    records.push(Record::new(day, *j, &Err(e.clone()), None));
+                }
+                continue;
+            }
+        };
+        if solution.implemented() {
+            timings.push((day, "parse".to_owned(), parse_timing));
+        }
+        for j in parts.iter() {
+            let result = solved
+                .next()
+                .unwrap()
+                .unwrap_or_else(|e| Err(panicked(day, &e)));
+            let elapsed = result.as_ref().ok().map(|(_x, t)| t.median);
+            if let Ok((_x, t)) = &result {
+                timings.push((day, format!("part {}", j), *t));
+            }
+            records.push(Record::new(day, *j, &result.map(|(x, _t)| x), elapsed));
+        }
+    }
+    return (records, timings);
+}
+
+/// Print each answer, along with what it should have been if `expected` is
+/// given.
+fn print_text(records: &[Record], expected: Option<&Answers>) {
+    for r in records.iter() {
+        let answer = r.answer.as_deref().unwrap_or_default();
+        match (r.status, expected) {
+            (Status::Ok, None) => println!("Day {} part {} solution: {}", r.day, r.part, answer),
+            (Status::Ok, Some(answers)) => {
+                let wrong = is_wrong(answers, r);
+                match answers.get(r.day, r.part) {
+                    // pictures are easier to compare one above the other
+                    Some(x) if x.contains('\n') || answer.contains('\n') => {
+                        let note = if wrong { "WRONG" } else { "as expected" };
+                        println!(
+                            "Day {} part {} example ({}): {}",
+                            r.day, r.part, note, answer
+                        );
+                        if wrong {
+                            println!("Expected: {}", x);
+                        }
+                    }
+                    Some(x) => {
+                        let note = if wrong { ", WRONG" } else { "" };
+                        println!(
+                            "Day {} part {} example: {} (expected {}{})",
+                            r.day, r.part, answer, x, note
+                        );
+                    }
+                    None => println!(
+                        "Day {} part {} example: {} (no expected answer)",
+                        r.day, r.part, answer
+                    ),
+                }
+            }
+            (Status::NotImplemented, _) => {
+                println!("Day {} part {} solution: not implemented", r.day, r.part)
             }
-            vec![x]
+            (Status::Error, _) => eprintln!(
+                "Error in day {} part {}: {}",
+                r.day,
+                r.part,
+                r.error.as_deref().unwrap_or_default()
+            ),
         }
-        None => (1..=func_map.len() as u8).collect(),
+    }
+}
+
+fn print_timings(timings: &[(u8, String, Timing)]) {
+    let row = |day: &str, step: &str, t: &Timing| {
+        println!(
+            "{:>5} {:>6} {:>5} {:>10} {:>10} {:>10}",
+            day,
+            step,
+            t.runs,
+            format!("{:.2?}", t.min),
+            format!("{:.2?}", t.median),
+            format!("{:.2?}", t.mean)
+        );
+    };
+    println!();
+    println!(
+        "{:>5} {:>6} {:>5} {:>10} {:>10} {:>10}",
+        "Day", "Step", "Runs", "Min", "Median", "Mean"
+    );
+    for (day, step, t) in timings.iter() {
+        row(&day.to_string(), step, t);
+    }
+    let total: Timing = timings.iter().map(|(_d, _s, t)| *t).sum();
+    row("Total", "", &total);
+}
+
+/// How often `--watch` checks whether anything has changed.
+const WATCH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Answers from before a rebuild, handed over to the rebuilt binary so it can
+/// still show what changed.
+const WATCH_PREVIOUS_VAR: &str = "AOC_WATCH_PREVIOUS";
+
+/// Print each answer along with how it differs from the previous run's.
+fn print_changes(records: &[Record], previous: &Answers) {
+    for r in records.iter() {
+        let answer = match (r.status, &r.answer) {
+            (Status::Ok, Some(x)) => x,
+            _ => {
+                print_text(std::slice::from_ref(r), None);
+                continue;
+            }
+        };
+        match Change::between(previous.get(r.day, r.part), answer) {
+            Change::New => println!("Day {} part {} solution: {}", r.day, r.part, answer),
+            Change::Unchanged => println!(
+                "Day {} part {} solution: {} (unchanged)",
+                r.day, r.part, answer
+            ),
+            Change::Changed(x) if x.contains('\n') || answer.contains('\n') => {
+                println!(
+                    "Day {} part {} solution (changed): {}",
+                    r.day, r.part, answer
+                );
+                println!("Was: {}", x);
+            }
+            Change::Changed(x) => println!(
+                "Day {} part {} solution: {} (was {})",
+                r.day, r.part, answer, x
+            ),
+        }
+    }
+}
+
+/// Read the input of every task that has one, leaving out any that can't be
+/// read right now.
+fn read_available<'a>(tasks: &'a [Task<'a>]) -> Vec<(&'a Task<'a>, String)> {
+    let mut contents = vec![];
+    for task in tasks.iter() {
+        match &task.path {
+            None => contents.push((task, String::new())),
+            Some(path) => match fs::read_to_string(path) {
+                Ok(x) => contents.push((task, x)),
+                Err(e) => eprintln!("Skipping {}: {}", path.display(), e),
+            },
+        }
+    }
+    return contents;
+}
+
+/// Rebuild with cargo and replace this process with the new binary. Only
+/// returns if that fails, to carry on with the binary that's running.
+fn rebuild(exe: &io::Result<PathBuf>, previous: &Answers) {
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let mut build = process::Command::new(cargo);
+    build
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(["build", "--bin", env!("CARGO_BIN_NAME")]);
+    // near enough to telling which profile this binary was built with
+    if !cfg!(debug_assertions) {
+        build.arg("--release");
+    }
+    match build.status() {
+        Ok(x) if x.success() => {}
+        Ok(_x) => {
+            eprintln!("Build failed, still running the previous build");
+            return;
+        }
+        Err(e) => {
+            eprintln!("Failed to run cargo: {}", e);
+            return;
+        }
+    }
+    let exe = match exe {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("Can't find the rebuilt binary: {}", e);
+            return;
+        }
+    };
+    let mut command = process::Command::new(exe);
+    command
+        .args(env::args_os().skip(1))
+        .env(WATCH_PREVIOUS_VAR, previous.to_string());
+    restart(command);
+}
+
+#[cfg(unix)]
+fn restart(mut command: process::Command) {
+    use std::os::unix::process::CommandExt;
+    let e = command.exec();
+    eprintln!("Failed to restart: {}", e);
+}
+
+#[cfg(not(unix))]
+fn restart(mut command: process::Command) {
+    match command.status() {
+        Ok(x) => process::exit(x.code().unwrap_or(1)),
+        Err(e) => eprintln!("Failed to restart: {}", e),
+    }
+}
+
+/// Run one day against its input and its examples, then again every time one
+/// of those files or the source changes. Never returns.
+fn watch(args: &Args, solution: &dyn AnySolution, parts: &[u8], example: bool) -> ! {
+    let inputs = match example {
+        true => vec![],
+        false => plan(args, &[solution], parts, false),
+    };
+    let examples = plan(args, &[solution], parts, true);
+    if inputs
+        .iter()
+        .any(|t| t.path.as_deref().is_some_and(is_stdin))
+    {
+        eprintln!("Can't watch stdin, give the input as a file");
+        process::exit(2);
+    }
+    // the binary was built from here, wherever it's being run from
+    let sources: Vec<PathBuf> =
+        match fs::read_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("src")) {
+            Ok(entries) => entries
+                .filter_map(|e| Some(e.ok()?.path()))
+                .filter(|p| p.extension().is_some_and(|x| x == "rs"))
+                .collect(),
+            Err(_e) => vec![],
+        };
+    // a rebuild replaces the file, after which it can't be looked up
+    let exe = env::current_exe();
+    let mut watcher = Watcher::new(
+        inputs
+            .iter()
+            .chain(examples.iter())
+            .filter_map(|t| t.path.clone())
+            .chain([args.example_dir.join("answers.toml")])
+            .chain(sources.iter().cloned()),
+    );
+    let mut previous: Answers = match env::var(WATCH_PREVIOUS_VAR) {
+        Ok(x) => x.parse().unwrap_or_default(),
+        Err(_e) => Answers::default(),
+    };
+    let repeats = args.bench.unwrap_or(1) as usize;
+    let jobs = args.jobs as usize;
+    loop {
+        let (records, mut timings) = run(&read_available(&inputs), repeats, jobs);
+        print_changes(&records, &previous);
+        for r in records.iter() {
+            if let Some(x) = &r.answer {
+                previous.insert(r.day, r.part, x);
+            }
+        }
+        let (records, example_timings) = run(&read_available(&examples), repeats, jobs);
+        match expected_example_answers(args, &examples) {
+            Ok(x) => print_text(&records, Some(&x)),
+            Err(e) => {
+                eprintln!("Failed to read example answers: {}", e);
+                print_text(&records, None);
+            }
+        }
+        if args.time || args.bench.is_some() {
+            timings.extend(example_timings);
+            print_timings(&timings);
+        }
+
+        eprintln!(
+            "Watching {} file(s) for changes, Ctrl-C to stop",
+            watcher.paths().count()
+        );
+        let changed = watcher.wait(WATCH_INTERVAL);
+        println!();
+        for path in changed.iter() {
+            eprintln!("Changed: {}", path.display());
+        }
+        if changed.iter().any(|p| sources.contains(p)) {
+            rebuild(&exe, &previous);
+        }
+    }
+}
+
+/// Columns and rows to draw in, as the shell reports them, leaving a line for
+/// the caption and one for the prompt.
+fn terminal_size() -> (usize, usize) {
+    let get = |name: &str, default: usize| -> usize {
+        return env::var(name)
+            .ok()
+            .and_then(|x| x.parse().ok())
+            .unwrap_or(default);
+    };
+    return (get("COLUMNS", 100), get("LINES", 42).saturating_sub(2));
+}
+
+/// Parse each task's input and solve its parts one at a time with `solve`,
+/// printing the answers. Returns the exit status.
+fn solve_each(
+    contents: &[(&Task, String)],
+    mut solve: impl FnMut(&dyn AnySolution, &ParsedInput, u8) -> Result<String>,
+) -> i32 {
+    let mut failed = false;
+    for (task, contents) in contents.iter() {
+        let (solution, day) = (task.solution, task.solution.day());
+        let input = match solution.parse(contents) {
+            Ok(x) => x,
+            Err(e) => {
+                eprintln!("Error in day {}: {}", day, e);
+                failed = true;
+                continue;
+            }
+        };
+        for part in task.parts.iter() {
+            let result = solve(solution, &input, *part);
+            failed |= result.is_err();
+            print_text(&[Record::new(day, *part, &result, None)], None);
+        }
+    }
+    return if failed { 1 } else { 0 };
+}
+
+/// Solve each part while drawing it. Days with nothing to draw are solved as
+/// usual. Returns the exit status.
+fn visualise(args: &Args, contents: &[(&Task, String)]) -> i32 {
+    let options = visualise::Options {
+        fps: args.fps,
+        step: args.frame_step as usize,
+        colour: !args.no_colour && env::var_os("NO_COLOR").is_none(),
+        size: terminal_size(),
+        viewport: args.viewport,
+        glyphs: args.glyph.iter().cloned().collect(),
+    };
+    return solve_each(contents, |solution, input, part| {
+        let day = solution.day();
+        let mut vis = Visualiser::new(options.clone(), Box::new(io::stdout()));
+        let result = match solution.visualise(input, part, &mut vis) {
+            Some(x) => x,
+            None => {
+                eprintln!("Day {} part {} has nothing to draw", day, part);
+                solution.solve(input, part)
+            }
+        };
+        if let Some(e) = vis.error() {
+            eprintln!("Failed to draw day {} part {}: {}", day, part, e);
+        }
+        return result;
+    });
+}
+
+/// Where the pictures of one part go. When there's more than one part each
+/// gets its own file.
+fn export_path(path: &Path, part: u8, parts: usize) -> PathBuf {
+    if parts == 1 {
+        return path.to_owned();
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(x) => format!("{}_part{}.{}", stem, part, x.to_string_lossy()),
+        None => format!("{}_part{}", stem, part),
+    };
+    return path.with_file_name(name);
+}
+
+/// Solve each part while saving pictures of it. Returns the exit status.
+fn export(args: &Args, path: &Path, contents: &[(&Task, String)]) -> i32 {
+    let options = export::Options {
+        scale: args.export_scale as usize,
+        step: args.export_step.map(|x| x as usize),
+        delay: (args.export_delay / 10).min(u16::MAX as u32) as u16,
+    };
+    let parts: usize = contents.iter().map(|(t, _c)| t.parts.len()).sum();
+    let mut unwritable = false;
+    let status = solve_each(contents, |solution, input, part| {
+        let day = solution.day();
+        let path = export_path(path, part, parts);
+        let mut images = match Recorder::new(&path, options.clone()) {
+            Ok(x) => x,
+            Err(e) => {
+                unwritable = true;
+                return Err(Error::solve(day, &e));
+            }
+        };
+        let result = match solution.export(input, part, &mut images) {
+            Some(x) => x,
+            None => {
+                eprintln!("Day {} part {} has nothing to draw", day, part);
+                return solution.solve(input, part);
+            }
+        };
+        match images.finish() {
+            Ok(written) if written.len() == 1 => eprintln!("Wrote {}", written[0].display()),
+            Ok(written) => eprintln!("Wrote {} images next to {}", written.len(), path.display()),
+            Err(e) => {
+                eprintln!("Failed to write {}: {}", path.display(), e);
+                unwritable = true;
+            }
+        }
+        return result;
+    });
+    return if unwritable { 1 } else { status };
+}
+
+/// Stream day 1's input, printing its largest totals and how the elves
+/// compare. Returns the exit status.
+fn top(k: usize, median: bool, tasks: &[Task]) -> i32 {
+    let path = match tasks.first().and_then(|t| t.path.as_deref()) {
+        Some(x) => x,
+        None => return 1,
+    };
+    let reader: Box<dyn BufRead> = if is_stdin(path) {
+        Box::new(io::stdin().lock())
+    } else {
+        match fs::File::open(path) {
+            Ok(f) => Box::new(BufReader::new(f)),
+            Err(e) => {
+                eprintln!("Failed to read {}: {}", path.display(), e);
+                return 1;
+            }
+        }
+    };
+    let elves = day1::elves(reader);
+    let summary = if median {
+        day1::Summary::with_median(elves, k)
+    } else {
+        day1::Summary::from_elves(elves, k)
+    };
+    return match summary {
+        Ok(summary) => {
+            print!("{}", summary);
+            0
+        }
+        Err(e) => {
+            eprintln!("Error in day 1: {}", e);
+            1
+        }
+    };
+}
+
+fn main() {
+    let args = Args::parse();
+    match &args.command {
+        Some(Command::Fetch(x)) => process::exit(fetch_inputs(x)),
+        Some(Command::Submit(x)) => process::exit(submit_answer(x)),
+        Some(Command::NewDay(x)) => process::exit(new_day(x)),
+        None => {}
+    }
+
+    let solutions: Vec<&dyn AnySolution> = match args.day {
+        Some(x) => match aoc2022::get_solution(x) {
+            Some(s) => vec![s],
+            None => {
+                eprintln!("Invalid day input: {}", x);
+                process::exit(2);
+            }
+        },
+        None => aoc2022::solutions(),
     };
     let parts = match args.part {
         Some(x) => {
             if x == 1 || x == 2 {
                 vec![x]
             } else {
-                panic!("Invalid part input");
+                eprintln!("Invalid part input: {}", x);
+                process::exit(2);
             }
         }
         None => vec![1, 2],
     };
 
-    for i in days.iter() {
-        for j in parts.iter() {
-            let (func, contents) = func_map[i][j];
-            println!("Day {} part {} solution: {}", i, j, func(contents));
+    if args.day.is_none() && args.input.iter().any(|(d, _p)| d.is_none()) {
+        eprintln!("An --input without a day needs --day to say which day it's for");
+        process::exit(2);
+    }
+    if args.top.is_some() && args.day != Some(1) {
+        eprintln!("--top only applies to day 1");
+        process::exit(2);
+    }
+
+    let example = args.example || args.example_file.is_some();
+    if args.watch {
+        watch(&args, solutions[0], &parts, example);
+    }
+    let tasks = plan(&args, &solutions, &parts, example);
+    let stdin_days = tasks
+        .iter()
+        .filter(|t| t.path.as_deref().is_some_and(is_stdin))
+        .count();
+    if stdin_days > 1 {
+        eprintln!("Only one day can read its input from stdin");
+        process::exit(2);
+    }
+    let mut missing: Vec<(u8, &Path)> = tasks
+        .iter()
+        .filter_map(|t| Some((t.solution.day(), t.path.as_deref()?)))
+        .filter(|(_d, p)| !is_stdin(p) && !p.is_file())
+        .collect();
+    missing.dedup();
+    if !missing.is_empty() {
+        eprintln!("Missing input files for {} day(s):", missing.len());
+        for (d, p) in missing {
+            eprintln!("  day {}: {}", d, p.display());
+        }
+        eprintln!("Use the `fetch` command to download them.");
+        process::exit(1);
+    }
+
+    if let Some(k) = args.top {
+        process::exit(top(k, args.median, &tasks));
+    }
+
+    let contents: Vec<(&Task, String)> = tasks
+        .iter()
+        .map(|task| {
+            let path = match &task.path {
+                Some(x) => x,
+                None => return (task, String::new()),
+            };
+            return match read_input(path) {
+                Ok(x) => (task, x),
+                Err(e) => {
+                    eprintln!("Failed to read {}: {}", path.display(), e);
+                    process::exit(1);
+                }
+            };
+        })
+        .collect();
+    if args.visualise {
+        process::exit(visualise(&args, &contents));
+    }
+    if let Some(path) = &args.export {
+        process::exit(export(&args, path, &contents));
+    }
+    let repeats = args.bench.unwrap_or(1) as usize;
+    let (records, timings) = run(&contents, repeats, args.jobs as usize);
+    let expected = match example.then(|| expected_example_answers(&args, &tasks)) {
+        Some(Ok(x)) => Some(x),
+        Some(Err(e)) => {
+            eprintln!("Failed to read example answers: {}", e);
+            process::exit(1);
         }
+        None => None,
+    };
+
+    match args.format {
+        Format::Text => {
+            print_text(&records, expected.as_ref());
+            if args.time || args.bench.is_some() {
+                print_timings(&timings);
+            }
+        }
+        Format::Json => print!("{}", report::to_json(&records)),
+        Format::Csv => print!("{}", report::to_csv(&records)),
+    }
+    let mut failed = records.iter().any(|r| r.status == Status::Error);
+    if let Some(answers) = &expected {
+        if args.format != Format::Text {
+            failed |= !verify(answers, &records);
+        } else {
+            failed |= records.iter().any(|r| is_wrong(answers, r));
+        }
+    }
+
+    if args.verify || args.record {
+        let path = answers_path(&args);
+        let mut answers = match Answers::load(&path) {
+            Ok(x) => x,
+            Err(e) => {
+                eprintln!("Failed to read answers: {}", e);
+                process::exit(1);
+            }
+        };
+        if args.verify && !verify(&answers, &records) {
+            failed = true;
+        }
+        if args.record {
+            let mut count = 0;
+            for r in records.iter() {
+                if let Some(x) = &r.answer {
+                    answers.insert(r.day, r.part, x);
+                    count += 1;
+                }
+            }
+            if let Err(e) = answers.save(&path) {
+                eprintln!("Failed to write {}: {}", path.display(), e);
+                process::exit(1);
+            }
+            eprintln!("Recorded {} answer(s) in {}", count, path.display());
+        }
+    }
+    if failed {
+        process::exit(1);
     }
 }
@@ -0,0 +1,85 @@
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// Message from a caught panic, if it had one.
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        return (*s).to_owned();
+    }
+    if let Some(s) = payload.downcast_ref::<String>() {
+        return s.clone();
+    }
+    return "unknown panic".to_owned();
+}
+
+/// Apply `f` to every item using up to `jobs` threads, returning the results
+/// in the same order as the items. A panic inside `f` only fails that item,
+/// and is returned as `Err` with the panic's message.
+pub fn map<T, R, F>(jobs: usize, items: &[T], f: F) -> Vec<Result<R, String>>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<R, String>>>> =
+        Mutex::new(items.iter().map(|_| None).collect());
+    let worker = || loop {
+        let i = next.fetch_add(1, Ordering::SeqCst);
+        if i >= items.len() {
+            break;
+        }
+        let result = panic::catch_unwind(AssertUnwindSafe(|| f(&items[i])))
+            .map_err(|e| panic_message(e.as_ref()));
+        results.lock().unwrap()[i] = Some(result);
+    };
+    thread::scope(|s| {
+        for _ in 1..jobs.clamp(1, items.len().max(1)) {
+            s.spawn(worker);
+        }
+        // the calling thread does its share too, so one job never spawns
+        worker();
+    });
+    return results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|r| r.expect("every item is processed"))
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn results_keep_input_order() {
+        let items: Vec<u64> = (0..50).collect();
+        let result = map(4, &items, |x| {
+            thread::sleep(std::time::Duration::from_micros(50 - x));
+            return x * 2;
+        });
+        let expected: Vec<Result<u64, String>> = (0..50).map(|x| Ok(x * 2)).collect();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn panics_only_fail_their_own_item() {
+        let result = map(2, &[1, 0, 2], |x| {
+            if *x == 0 {
+                panic!("zero!");
+            }
+            return 10 / x;
+        });
+        assert_eq!(result, vec![Ok(10), Err("zero!".to_owned()), Ok(5)]);
+    }
+
+    #[test]
+    fn no_items_gives_no_results() {
+        let result: Vec<Result<(), String>> = map(3, &[] as &[u8], |_x| ());
+        assert!(result.is_empty());
+    }
+}
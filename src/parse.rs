@@ -0,0 +1,376 @@
+use crate::error::{Error, Result};
+use crate::utils::SplitWhen;
+use std::str::FromStr;
+
+/// Why a parser couldn't read a line, and where
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Failure<'a> {
+    /// The rest of the line from where it went wrong
+    at: &'a str,
+    /// How much of `at` was the problem
+    len: usize,
+    message: String,
+}
+
+impl<'a> Failure<'a> {
+    /// A failure blaming the next word of `at`, or all of it if there's no
+    /// word there
+    fn new(at: &'a str, message: &str) -> Self {
+        let word = at.split(char::is_whitespace).next().unwrap_or("");
+        let len = if word.is_empty() {
+            at.len()
+        } else {
+            word.len()
+        };
+        return Self {
+            at,
+            len,
+            message: message.to_owned(),
+        };
+    }
+
+    fn into_error(self, day: u8, line_no: usize, line: &str) -> Error {
+        let column = line[..line.len() - self.at.len()].chars().count() + 1;
+        return Error::parse(day, line_no, column, &self.at[..self.len], &self.message);
+    }
+}
+
+/// A value, and the rest of the line after it
+pub type Parsed<'a, T> = std::result::Result<(T, &'a str), Failure<'a>>;
+
+/// Something that reads a `T` from the start of some text. Any function
+/// from text to `Parsed` is one, and the methods here build bigger parsers
+/// out of smaller ones.
+pub trait Parser<T> {
+    fn parse<'a>(&self, input: &'a str) -> Parsed<'a, T>;
+
+    fn map<U>(self, f: impl Fn(T) -> U) -> impl Parser<U>
+    where
+        Self: Sized,
+    {
+        return parser(move |input| {
+            let (value, rest) = self.parse(input)?;
+            return Ok((f(value), rest));
+        });
+    }
+
+    /// Check or convert what was read, blaming all of it if `f` fails
+    fn try_map<U>(self, f: impl Fn(T) -> std::result::Result<U, &'static str>) -> impl Parser<U>
+    where
+        Self: Sized,
+    {
+        return parser(move |input| {
+            let (value, rest) = self.parse(input)?;
+            return match f(value) {
+                Ok(u) => Ok((u, rest)),
+                Err(message) => Err(Failure {
+                    at: input,
+                    len: input.len() - rest.len(),
+                    message: message.to_owned(),
+                }),
+            };
+        });
+    }
+
+    /// Read this then `next`, keeping both
+    fn then<U>(self, next: impl Parser<U>) -> impl Parser<(T, U)>
+    where
+        Self: Sized,
+    {
+        return parser(move |input| {
+            let (first, rest) = self.parse(input)?;
+            let (second, rest) = next.parse(rest)?;
+            return Ok(((first, second), rest));
+        });
+    }
+
+    /// Read this then `next`, keeping only this
+    fn then_ignore<U>(self, next: impl Parser<U>) -> impl Parser<T>
+    where
+        Self: Sized,
+    {
+        return self.then(next).map(|(first, _second)| first);
+    }
+
+    /// Read this then `next`, keeping only `next`
+    fn ignore_then<U>(self, next: impl Parser<U>) -> impl Parser<U>
+    where
+        Self: Sized,
+    {
+        return self.then(next).map(|(_first, second)| second);
+    }
+
+    /// Read this or, if that fails, `other`. When both fail it's whichever
+    /// got further that's reported.
+    fn or(self, other: impl Parser<T>) -> impl Parser<T>
+    where
+        Self: Sized,
+    {
+        return parser(move |input| {
+            return match self.parse(input) {
+                Ok(x) => Ok(x),
+                Err(first) => other.parse(input).map_err(|second| {
+                    if first.at.len() < second.at.len() {
+                        first
+                    } else {
+                        second
+                    }
+                }),
+            };
+        });
+    }
+
+    /// One or more of these with `separator` between each
+    fn separated_by<U>(self, separator: impl Parser<U>) -> impl Parser<Vec<T>>
+    where
+        Self: Sized,
+    {
+        return parser(move |input| {
+            let (first, mut rest) = self.parse(input)?;
+            let mut values = vec![first];
+            while let Ok((_s, after)) = separator.parse(rest) {
+                let (value, after) = self.parse(after)?;
+                values.push(value);
+                rest = after;
+            }
+            return Ok((values, rest));
+        });
+    }
+
+    /// Report any failure as the whole of this not being `what`
+    fn expecting(self, what: &'static str) -> impl Parser<T>
+    where
+        Self: Sized,
+    {
+        return parser(move |input| {
+            return self.parse(input).map_err(|_e| Failure {
+                at: input,
+                len: input.len(),
+                message: format!("expected {}", what),
+            });
+        });
+    }
+}
+
+impl<T, F> Parser<T> for F
+where
+    F: Fn(&str) -> Parsed<'_, T>,
+{
+    fn parse<'a>(&self, input: &'a str) -> Parsed<'a, T> {
+        return self(input);
+    }
+}
+
+/// Pins down the type of a closure so that it can be used as a parser
+fn parser<T, F>(f: F) -> F
+where
+    F: Fn(&str) -> Parsed<'_, T>,
+{
+    return f;
+}
+
+/// Exactly `text`
+pub fn literal(text: &'static str) -> impl Parser<()> {
+    return parser(move |input| {
+        return match input.strip_prefix(text) {
+            Some(rest) => Ok(((), rest)),
+            None => Err(Failure::new(input, &format!("expected `{}`", text))),
+        };
+    });
+}
+
+/// Any number of spaces, including none
+pub fn spaces() -> impl Parser<()> {
+    return parser(|input| Ok(((), input.trim_start_matches(' '))));
+}
+
+/// Everything up to the next whitespace, which mustn't be nothing
+pub fn word() -> impl Parser<String> {
+    return parser(|input| {
+        let end = input.find(char::is_whitespace).unwrap_or(input.len());
+        if end == 0 {
+            return Err(Failure::new(input, "expected a word"));
+        }
+        return Ok((input[..end].to_owned(), &input[end..]));
+    });
+}
+
+fn number<T: FromStr>(input: &str, signed: bool) -> Parsed<'_, T> {
+    let sign = usize::from(signed && input.starts_with('-'));
+    let digits = input[sign..]
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len() - sign);
+    if digits == 0 {
+        return Err(Failure::new(input, "expected a number"));
+    }
+    let (text, rest) = input.split_at(sign + digits);
+    return match text.parse() {
+        Ok(n) => Ok((n, rest)),
+        Err(_e) => Err(Failure {
+            at: input,
+            len: text.len(),
+            message: "number out of range".to_owned(),
+        }),
+    };
+}
+
+/// Digits, read as a `T`
+pub fn unsigned<T: FromStr>() -> impl Parser<T> {
+    return parser(|input| number(input, false));
+}
+
+/// Digits with an optional `-` in front, read as a `T`
+pub fn signed<T: FromStr>() -> impl Parser<T> {
+    return parser(|input| number(input, true));
+}
+
+/// Whichever of the options' texts comes next, giving its value. Put any
+/// that start with another option's text first.
+pub fn one_of<S: AsRef<str>, T: Clone>(
+    options: impl IntoIterator<Item = (S, T)>,
+) -> impl Parser<T> {
+    let options: Vec<(String, T)> = options
+        .into_iter()
+        .map(|(text, value)| (text.as_ref().to_owned(), value))
+        .collect();
+    let texts: Vec<&str> = options.iter().map(|(text, _value)| text.as_str()).collect();
+    let message = match texts.split_last() {
+        Some((last, [])) => format!("expected {}", last),
+        Some((last, rest)) => format!("expected one of {} or {}", rest.join(", "), last),
+        None => "expected nothing".to_owned(),
+    };
+    return parser(move |input| {
+        for (text, value) in options.iter() {
+            if let Some(rest) = input.strip_prefix(text.as_str()) {
+                return Ok((value.clone(), rest));
+            }
+        }
+        return Err(Failure::new(input, &message));
+    });
+}
+
+/// Read the whole of one line. Line numbers are one-indexed.
+pub fn parse_line<T>(day: u8, line_no: usize, line: &str, parser: &impl Parser<T>) -> Result<T> {
+    return match parser.parse(line) {
+        Ok((value, "")) => Ok(value),
+        Ok((_value, rest)) => {
+            let failure = Failure {
+                at: rest,
+                len: rest.len(),
+                message: "unexpected text at the end of the line".to_owned(),
+            };
+            Err(failure.into_error(day, line_no, line))
+        }
+        Err(failure) => Err(failure.into_error(day, line_no, line)),
+    };
+}
+
+/// Read every line of the input the same way
+pub fn parse_lines<T>(day: u8, input: &str, parser: &impl Parser<T>) -> Result<Vec<T>> {
+    return input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| parse_line(day, i + 1, line, parser))
+        .collect();
+}
+
+/// The groups of lines between blank lines, each line with its one-indexed
+/// line number
+pub fn blocks(input: &str) -> impl Iterator<Item = Vec<(usize, &str)>> {
+    return input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line))
+        .split_when(|(_i, line)| line.is_empty());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point() -> impl Parser<(i32, i32)> {
+        return signed()
+            .then_ignore(literal(","))
+            .then(signed())
+            .expecting("a point");
+    }
+
+    #[test]
+    fn numbers_and_literals() {
+        assert_eq!(unsigned::<u8>().parse("12 apples"), Ok((12, " apples")));
+        assert_eq!(signed::<i64>().parse("-7,"), Ok((-7, ",")));
+        assert!(unsigned::<u8>().parse("-7").is_err());
+        assert_eq!(
+            parse_line(1, 3, "300", &unsigned::<u8>()),
+            Err(Error::parse(1, 3, 1, "300", "number out of range"))
+        );
+        assert_eq!(
+            parse_line(1, 3, "cd x", &literal("cd ").ignore_then(word())),
+            Ok("x".to_owned())
+        );
+        assert_eq!(
+            parse_line(1, 3, "cd  ", &literal("cd ").ignore_then(word())),
+            Err(Error::parse(1, 3, 4, " ", "expected a word"))
+        );
+    }
+
+    #[test]
+    fn parsers_combine() {
+        let points = point().separated_by(literal(" -> "));
+        assert_eq!(
+            parse_line(0, 1, "1,2 -> -3,4", &points),
+            Ok(vec![(1, 2), (-3, 4)])
+        );
+        assert_eq!(
+            parse_line(0, 1, "1,2 -> 3;4", &points),
+            Err(Error::parse(0, 1, 8, "3;4", "expected a point"))
+        );
+        assert_eq!(
+            parse_line(0, 1, "1,2 3,4", &points),
+            Err(Error::parse(
+                0,
+                1,
+                4,
+                " 3,4",
+                "unexpected text at the end of the line"
+            ))
+        );
+
+        let op = one_of([("+", 1), ("-", -1)]);
+        let even = unsigned::<u32>().try_map(|n| match n % 2 {
+            0 => Ok(n),
+            _ => Err("expected an even number"),
+        });
+        let sum = spaces().ignore_then(op).then_ignore(spaces()).then(even);
+        assert_eq!(parse_line(0, 2, "  - 42", &sum), Ok((-1, 42)));
+        assert_eq!(
+            parse_line(0, 2, " * 42", &sum),
+            Err(Error::parse(0, 2, 2, "*", "expected one of + or -"))
+        );
+        assert_eq!(
+            parse_line(0, 2, "+ 41", &sum),
+            Err(Error::parse(0, 2, 3, "41", "expected an even number"))
+        );
+
+        let either = literal("noop")
+            .map(|_u| None)
+            .or(literal("addx ").ignore_then(signed::<i32>()).map(Some));
+        assert_eq!(parse_line(0, 1, "addx -3", &either), Ok(Some(-3)));
+        assert_eq!(
+            parse_line(0, 1, "addx x", &either),
+            Err(Error::parse(0, 1, 6, "x", "expected a number"))
+        );
+    }
+
+    #[test]
+    fn lines_and_blocks_know_where_they_are() {
+        assert_eq!(
+            parse_lines(4, "1\n2\nx", &unsigned::<u32>()),
+            Err(Error::parse(4, 3, 1, "x", "expected a number"))
+        );
+        assert_eq!(
+            blocks("a\nb\n\nc\n").collect::<Vec<_>>(),
+            vec![vec![(1, "a"), (2, "b")], vec![(4, "c")]]
+        );
+    }
+}
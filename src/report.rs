@@ -0,0 +1,175 @@
+use crate::error::{Error, Result};
+use std::fmt::Write;
+use std::time::Duration;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Status {
+    Ok,
+    Error,
+    NotImplemented,
+}
+
+impl Status {
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Self::Ok => "ok",
+            Self::Error => "error",
+            Self::NotImplemented => "not implemented",
+        };
+    }
+}
+
+/// The outcome of running one part of one day.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Record {
+    pub day: u8,
+    pub part: u8,
+    pub status: Status,
+    pub answer: Option<String>,
+    pub error: Option<String>,
+    pub elapsed: Option<Duration>,
+}
+
+impl Record {
+    pub fn new(day: u8, part: u8, result: &Result<String>, elapsed: Option<Duration>) -> Self {
+        let (status, answer, error) = match result {
+            Ok(x) => (Status::Ok, Some(x.clone()), None),
+            Err(Error::NotImplemented { .. }) => (Status::NotImplemented, None, None),
+            Err(e) => (Status::Error, None, Some(e.to_string())),
+        };
+        return Self {
+            day,
+            part,
+            status,
+            answer,
+            error,
+            elapsed,
+        };
+    }
+}
+
+fn elapsed_ms(elapsed: Option<Duration>) -> Option<String> {
+    return elapsed.map(|d| format!("{:.3}", d.as_secs_f64() * 1000.0));
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    return out;
+}
+
+// quoted as per RFC 4180, so answers spanning several lines stay in one field
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        return format!("\"{}\"", s.replace('"', "\"\""));
+    }
+    return s.to_owned();
+}
+
+/// A JSON array with one object per record.
+pub fn to_json(records: &[Record]) -> String {
+    let opt = |x: Option<String>| x.unwrap_or_else(|| "null".to_owned());
+    let objects: Vec<String> = records
+        .iter()
+        .map(|r| {
+            format!(
+                "  {{\"day\": {}, \"part\": {}, \"status\": {}, \"answer\": {}, \"elapsed_ms\": {}, \"error\": {}}}",
+                r.day,
+                r.part,
+                json_string(r.status.name()),
+                opt(r.answer.as_deref().map(json_string)),
+                opt(elapsed_ms(r.elapsed)),
+                opt(r.error.as_deref().map(json_string)),
+            )
+        })
+        .collect();
+    if objects.is_empty() {
+        return "[]\n".to_owned();
+    }
+    return format!("[\n{}\n]\n", objects.join(",\n"));
+}
+
+/// CSV with a header row, one row per record. Missing values are left empty.
+pub fn to_csv(records: &[Record]) -> String {
+    let mut out = String::from("day,part,status,answer,elapsed_ms,error\n");
+    for r in records.iter() {
+        writeln!(
+            out,
+            "{},{},{},{},{},{}",
+            r.day,
+            r.part,
+            csv_field(r.status.name()),
+            csv_field(r.answer.as_deref().unwrap_or("")),
+            elapsed_ms(r.elapsed).unwrap_or_default(),
+            csv_field(r.error.as_deref().unwrap_or("")),
+        )
+        .unwrap();
+    }
+    return out;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records() -> Vec<Record> {
+        return vec![
+            Record::new(
+                10,
+                2,
+                &Ok("\n#.\n.#".to_owned()),
+                Some(Duration::from_micros(1500)),
+            ),
+            Record::new(5, 1, &Err(Error::solve(5, "stack 3 doesn't exist")), None),
+            Record::new(25, 1, &Err(Error::NotImplemented { day: 25 }), None),
+        ];
+    }
+
+    #[test]
+    fn status_follows_result() {
+        let statuses: Vec<Status> = records().iter().map(|r| r.status).collect();
+        assert_eq!(
+            statuses,
+            vec![Status::Ok, Status::Error, Status::NotImplemented]
+        );
+    }
+
+    #[test]
+    fn json_escapes_multi_line_answers() {
+        let expected = r#"[
+  {"day": 10, "part": 2, "status": "ok", "answer": "\n#.\n.#", "elapsed_ms": 1.500, "error": null},
+  {"day": 5, "part": 1, "status": "error", "answer": null, "elapsed_ms": null, "error": "day 5: stack 3 doesn't exist"},
+  {"day": 25, "part": 1, "status": "not implemented", "answer": null, "elapsed_ms": null, "error": null}
+]
+"#;
+        assert_eq!(to_json(&records()), expected);
+    }
+
+    #[test]
+    fn csv_quotes_multi_line_answers() {
+        let expected = "day,part,status,answer,elapsed_ms,error
+10,2,ok,\"
+#.
+.#\",1.500,
+5,1,error,,,day 5: stack 3 doesn't exist
+25,1,not implemented,,,
+";
+        assert_eq!(to_csv(&records()), expected);
+    }
+
+    #[test]
+    fn csv_doubles_quotes_inside_fields() {
+        assert_eq!(csv_field("say \"hi\", ok"), "\"say \"\"hi\"\", ok\"");
+    }
+}
@@ -0,0 +1,268 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+const MODULE_TEMPLATE: &str = r#"use crate::error::{Error, Result};
+use crate::solution::{Registered, Solution};
+
+pub fn parse(file_contents: &str) -> Result<Vec<String>> {
+    return Ok(file_contents.lines().map(|line| line.to_owned()).collect());
+}
+
+pub fn part_1(_lines: &[String]) -> Result<String> {
+    return Err(Error::NotImplemented { day: DayN::DAY });
+}
+
+pub fn part_2(_lines: &[String]) -> Result<String> {
+    return Err(Error::NotImplemented { day: DayN::DAY });
+}
+
+pub struct DayN;
+
+pub static SOLUTION: Registered<DayN> = Registered::new();
+
+impl Solution for DayN {
+    type Input = Vec<String>;
+
+    const DAY: u8 = N;
+    const TITLE: &'static str = "{title}";
+
+    fn parse(file_contents: &str) -> Result<Self::Input> {
+        return parse(file_contents);
+    }
+
+    fn part_1(input: &Self::Input) -> Result<String> {
+        return part_1(input);
+    }
+
+    fn part_2(input: &Self::Input) -> Result<String> {
+        return part_2(input);
+    }
+}
+"#;
+
+const TESTS_TEMPLATE: &str = r#"
+#[test]
+fn dayN_part_1_integration_test() {
+    let contents = include_str!("example_files/dayN.txt");
+    let input = aoc2022::dayN::parse(contents).unwrap();
+    let result = aoc2022::dayN::part_1(&input);
+    // swap in the expected answer once part 1 is solved
+    let expected = aoc2022::error::Error::NotImplemented { day: N };
+    assert_eq!(result, Err(expected));
+}
+
+#[test]
+fn dayN_part_2_integration_test() {
+    let contents = include_str!("example_files/dayN.txt");
+    let input = aoc2022::dayN::parse(contents).unwrap();
+    let result = aoc2022::dayN::part_2(&input);
+    // swap in the expected answer once part 2 is solved
+    let expected = aoc2022::error::Error::NotImplemented { day: N };
+    assert_eq!(result, Err(expected));
+}
+"#;
+
+fn render(template: &str, day: u8, title: &str) -> String {
+    return template
+        .replace("DayN", &format!("Day{}", day))
+        .replace("dayN", &format!("day{}", day))
+        .replace("= N;", &format!("= {};", day))
+        .replace("day: N }", &format!("day: {} }}", day))
+        .replace("{title}", &title.replace('\\', "\\\\").replace('"', "\\\""));
+}
+
+fn already_exists(what: String) -> io::Error {
+    return io::Error::new(io::ErrorKind::AlreadyExists, what);
+}
+
+/// Title of a day that's only a stub, `None` if the module has been written.
+fn stub_title(module: &str) -> Option<String> {
+    if !module.contains("Unimplemented {") {
+        return None;
+    }
+    let start = module.find("title: \"")? + "title: \"".len();
+    let end = start + module[start..].find('"')?;
+    return Some(module[start..end].to_owned());
+}
+
+/// `lib.rs` with `module` added to the `days!` list, or `None` if it's
+/// already there.
+fn register(lib: &str, module: &str) -> Option<String> {
+    let start = lib.find("days!(")? + "days!(".len();
+    let end = start + lib[start..].find(");")?;
+    let mut modules: Vec<&str> = lib[start..end]
+        .split(',')
+        .map(|m| m.trim())
+        .filter(|m| !m.is_empty())
+        .collect();
+    if modules.contains(&module) {
+        return None;
+    }
+    modules.push(module);
+    modules.sort_by_key(|m| m.trim_start_matches("day").parse::<u8>().unwrap_or(u8::MAX));
+
+    // wrapped about where rustfmt wraps the list
+    let mut lines: Vec<String> = vec![];
+    for m in modules {
+        match lines.last_mut() {
+            Some(line) if line.len() + m.len() + 2 <= 90 => {
+                line.push_str(&format!(" {},", m));
+            }
+            _ => lines.push(format!("    {},", m)),
+        }
+    }
+    return Some(format!(
+        "{}\n{}\n{}",
+        &lib[..start],
+        lines.join("\n"),
+        &lib[end..]
+    ));
+}
+
+/// Set up a new day in the repository at `root`: a module implementing
+/// `Solution` that reports itself as not implemented, its entry in the
+/// `days!` list in `lib.rs`, an empty example file and integration tests
+/// that expect exactly that until they're given real answers. Returns the
+/// files that were written.
+///
+/// The title is taken from the day's stub if there is one. A day that has
+/// already been written is left alone.
+pub fn new_day(root: &Path, day: u8, title: Option<&str>) -> io::Result<Vec<PathBuf>> {
+    let module_name = format!("day{}", day);
+    let module_path = root.join("src").join(format!("{}.rs", module_name));
+    let lib_path = root.join("src").join("lib.rs");
+    let tests_path = root.join("tests").join("tests.rs");
+    let example_path = root
+        .join("tests")
+        .join("example_files")
+        .join(format!("{}.txt", module_name));
+
+    let stub = match fs::read_to_string(&module_path) {
+        Ok(x) => Some(stub_title(&x).ok_or_else(|| {
+            already_exists(format!(
+                "{} has already been written",
+                module_path.display()
+            ))
+        })?),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e),
+    };
+    let title = title
+        .map(|t| t.to_owned())
+        .or(stub)
+        .unwrap_or_else(|| "TODO".to_owned());
+    let lib = fs::read_to_string(&lib_path)?;
+    let tests = fs::read_to_string(&tests_path)?;
+    if tests.contains(&format!("fn {}_part_1_integration_test", module_name)) {
+        return Err(already_exists(format!(
+            "{} already has tests for {}",
+            tests_path.display(),
+            module_name
+        )));
+    }
+
+    let mut written = vec![];
+    fs::write(&module_path, render(MODULE_TEMPLATE, day, &title))?;
+    written.push(module_path);
+    if let Some(lib) = register(&lib, &module_name) {
+        fs::write(&lib_path, lib)?;
+        written.push(lib_path);
+    }
+    if !example_path.exists() {
+        fs::create_dir_all(example_path.parent().unwrap())?;
+        fs::write(&example_path, "")?;
+        written.push(example_path);
+    }
+    let mut file = OpenOptions::new().append(true).open(&tests_path)?;
+    file.write_all(render(TESTS_TEMPLATE, day, &title).as_bytes())?;
+    written.push(tests_path);
+    return Ok(written);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIB: &str = "mod utils;
+
+days!(
+    day1, day2, day3, day4, day5, day6, day7, day8, day9, day10, day11, day12, day13,
+    day14, day15, day16, day17, day18, day19, day20, day21, day22, day23, day24,
+);
+
+pub fn get_solution() {}
+";
+
+    fn temp_repo(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("aoc2022-scaffold-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("tests").join("example_files")).unwrap();
+        fs::write(root.join("src").join("lib.rs"), LIB).unwrap();
+        fs::write(
+            root.join("tests").join("tests.rs"),
+            "use std::include_str;\n",
+        )
+        .unwrap();
+        return root;
+    }
+
+    #[test]
+    fn registers_new_modules_in_order() {
+        let result = register(LIB, "day25").unwrap();
+        assert_eq!(result, LIB.replace("day23, day24,", "day23, day24, day25,"));
+        assert_eq!(register(LIB, "day3"), None);
+        let result = register("days!(\n    day1, day3,\n);\n", "day2").unwrap();
+        assert_eq!(result, "days!(\n    day1, day2, day3,\n);\n");
+    }
+
+    #[test]
+    fn stubs_are_replaced_keeping_their_title() {
+        let root = temp_repo("stub");
+        let stub = "use crate::solution::Unimplemented;
+
+pub static SOLUTION: Unimplemented = Unimplemented {
+    day: 16,
+    title: \"Proboscidea Volcanium\",
+};
+";
+        fs::write(root.join("src/day16.rs"), stub).unwrap();
+        let written = new_day(&root, 16, None).unwrap();
+        assert_eq!(
+            written,
+            vec![
+                root.join("src/day16.rs"),
+                root.join("tests/example_files/day16.txt"),
+                root.join("tests/tests.rs"),
+            ]
+        );
+        let module = fs::read_to_string(root.join("src/day16.rs")).unwrap();
+        assert!(module.contains("pub static SOLUTION: Registered<Day16> = Registered::new();"));
+        assert!(module.contains("const DAY: u8 = 16;"));
+        assert!(module.contains("const TITLE: &'static str = \"Proboscidea Volcanium\";"));
+        let tests = fs::read_to_string(root.join("tests/tests.rs")).unwrap();
+        assert!(tests.contains("fn day16_part_2_integration_test() {"));
+        assert!(tests.contains("include_str!(\"example_files/day16.txt\")"));
+        // the fixture imports nothing but `include_str`, so paths must be full
+        assert!(tests.contains("aoc2022::error::Error::NotImplemented { day: 16 };"));
+        assert_eq!(fs::read_to_string(root.join("src/lib.rs")).unwrap(), LIB);
+
+        // running it again would throw away whatever has been written since
+        let result = new_day(&root, 16, None);
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn missing_days_are_created_and_registered() {
+        let root = temp_repo("missing");
+        new_day(&root, 25, Some("Full of \"Hot Air\"")).unwrap();
+        let module = fs::read_to_string(root.join("src/day25.rs")).unwrap();
+        assert!(module.contains("const TITLE: &'static str = \"Full of \\\"Hot Air\\\"\";"));
+        let lib = fs::read_to_string(root.join("src/lib.rs")).unwrap();
+        assert!(lib.contains("day24, day25,\n);"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
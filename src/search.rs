@@ -0,0 +1,427 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// What a search found: how far each node it reached is from the nearest
+/// source, and the way back to that source.
+#[derive(Debug, Clone)]
+pub struct Search<N, C> {
+    distances: HashMap<N, C>,
+    predecessors: HashMap<N, N>,
+    goal: Option<N>,
+}
+
+impl<N: Eq + Hash + Clone, C: Copy> Search<N, C> {
+    fn new() -> Self {
+        return Self {
+            distances: HashMap::new(),
+            predecessors: HashMap::new(),
+            goal: None,
+        };
+    }
+
+    /// The first node the goal predicate accepted, if the search found one
+    pub fn goal(&self) -> Option<&N> {
+        return self.goal.as_ref();
+    }
+
+    pub fn goal_distance(&self) -> Option<C> {
+        return self.distance(self.goal.as_ref()?);
+    }
+
+    pub fn goal_path(&self) -> Option<Vec<N>> {
+        return self.path_to(self.goal.as_ref()?);
+    }
+
+    /// Every node reached so far, and how far it is from the nearest source
+    pub fn distances(&self) -> &HashMap<N, C> {
+        return &self.distances;
+    }
+
+    pub fn distance(&self, node: &N) -> Option<C> {
+        return self.distances.get(node).copied();
+    }
+
+    pub fn reached(&self, node: &N) -> bool {
+        return self.distances.contains_key(node);
+    }
+
+    /// The nodes from the nearest source to `node`, both included
+    pub fn path_to(&self, node: &N) -> Option<Vec<N>> {
+        if !self.reached(node) {
+            return None;
+        }
+        let mut path = vec![node.clone()];
+        while let Some(previous) = self.predecessors.get(path.last().unwrap()) {
+            path.push(previous.clone());
+        }
+        path.reverse();
+        return Some(path);
+    }
+}
+
+/// A breadth first search, visiting a node each time it's stepped. Every
+/// step costs the same, so nodes are visited nearest first.
+pub struct BreadthFirst<N, F> {
+    neighbours: F,
+    queue: VecDeque<N>,
+    search: Search<N, usize>,
+}
+
+impl<N, F, I> BreadthFirst<N, F>
+where
+    N: Eq + Hash + Clone,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = N>,
+{
+    pub fn new(sources: impl IntoIterator<Item = N>, neighbours: F) -> Self {
+        let mut search = Search::new();
+        let mut queue = VecDeque::new();
+        for source in sources {
+            if search.distances.insert(source.clone(), 0).is_none() {
+                queue.push_back(source);
+            }
+        }
+        return Self {
+            neighbours,
+            queue,
+            search,
+        };
+    }
+
+    /// Nodes that have been reached but not visited yet
+    pub fn frontier(&self) -> impl Iterator<Item = &N> {
+        return self.queue.iter();
+    }
+
+    pub fn search(&self) -> &Search<N, usize> {
+        return &self.search;
+    }
+
+    pub fn into_search(self) -> Search<N, usize> {
+        return self.search;
+    }
+}
+
+impl<N, F, I> Iterator for BreadthFirst<N, F>
+where
+    N: Eq + Hash + Clone,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = N>,
+{
+    type Item = N;
+
+    fn next(&mut self) -> Option<N> {
+        let node = self.queue.pop_front()?;
+        let distance = self.search.distances[&node];
+        for next in (self.neighbours)(&node) {
+            if self.search.reached(&next) {
+                continue;
+            }
+            self.search.distances.insert(next.clone(), distance + 1);
+            self.search.predecessors.insert(next.clone(), node.clone());
+            self.queue.push_back(next);
+        }
+        return Some(node);
+    }
+}
+
+/// An entry in the queue of a best first search, ordered so that the lowest
+/// priority comes out of a `BinaryHeap` first
+struct Queued<N, C> {
+    priority: C,
+    cost: C,
+    node: N,
+}
+
+impl<N, C: Ord> PartialEq for Queued<N, C> {
+    fn eq(&self, other: &Self) -> bool {
+        return self.priority == other.priority;
+    }
+}
+
+impl<N, C: Ord> Eq for Queued<N, C> {}
+
+impl<N, C: Ord> PartialOrd for Queued<N, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl<N, C: Ord> Ord for Queued<N, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        return other.priority.cmp(&self.priority);
+    }
+}
+
+/// Dijkstra's algorithm, or A* given a heuristic, visiting a node each time
+/// it's stepped. The heuristic must never guess more than the real cost to
+/// the goal, and `C::default()` is taken to be no cost at all.
+///
+/// If the heuristic isn't also consistent, a shorter way to a node can turn
+/// up after it's been visited. It's then visited again, so as an iterator
+/// the same node can come out more than once, each time at a lower cost
+/// than the last. With a consistent heuristic, or none, every node comes out
+/// once, at its final cost.
+pub struct BestFirst<N, C, F, H> {
+    neighbours: F,
+    heuristic: H,
+    queue: BinaryHeap<Queued<N, C>>,
+    visited: HashSet<N>,
+    search: Search<N, C>,
+}
+
+impl<N, C, F, H, I> BestFirst<N, C, F, H>
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Add<Output = C> + Default,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+    H: FnMut(&N) -> C,
+{
+    pub fn new(sources: impl IntoIterator<Item = N>, neighbours: F, mut heuristic: H) -> Self {
+        let mut search = Search::new();
+        let mut queue = BinaryHeap::new();
+        for source in sources {
+            if search
+                .distances
+                .insert(source.clone(), C::default())
+                .is_none()
+            {
+                queue.push(Queued {
+                    priority: heuristic(&source),
+                    cost: C::default(),
+                    node: source,
+                });
+            }
+        }
+        return Self {
+            neighbours,
+            heuristic,
+            queue,
+            visited: HashSet::new(),
+            search,
+        };
+    }
+
+    /// Nodes that have been reached but not visited yet, in no order
+    pub fn frontier(&self) -> impl Iterator<Item = &N> {
+        return self
+            .queue
+            .iter()
+            .map(|q| &q.node)
+            .filter(|n| !self.visited.contains(*n));
+    }
+
+    pub fn search(&self) -> &Search<N, C> {
+        return &self.search;
+    }
+
+    pub fn into_search(self) -> Search<N, C> {
+        return self.search;
+    }
+}
+
+impl<N, C, F, H, I> Iterator for BestFirst<N, C, F, H>
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Add<Output = C> + Default,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+    H: FnMut(&N) -> C,
+{
+    type Item = N;
+
+    fn next(&mut self) -> Option<N> {
+        // a node can be queued more than once as shorter ways to it are
+        // found, so skip any that have been dealt with since or that have
+        // been beaten by a shorter way
+        let Queued { cost, node, .. } = loop {
+            let queued = self.queue.pop()?;
+            let best = self.search.distance(&queued.node);
+            if !self.visited.contains(&queued.node) && best == Some(queued.cost) {
+                break queued;
+            }
+        };
+        self.visited.insert(node.clone());
+        for (next, step) in (self.neighbours)(&node) {
+            let distance = cost + step;
+            if self.search.distance(&next).is_some_and(|d| d <= distance) {
+                continue;
+            }
+            self.search.distances.insert(next.clone(), distance);
+            self.search.predecessors.insert(next.clone(), node.clone());
+            self.visited.remove(&next);
+            self.queue.push(Queued {
+                priority: distance + (self.heuristic)(&next),
+                cost: distance,
+                node: next,
+            });
+        }
+        return Some(node);
+    }
+}
+
+/// Visit nodes until one is a goal, or there are none left
+fn until_goal<N: Clone>(
+    nodes: &mut impl Iterator<Item = N>,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<N> {
+    return nodes.find(|n| is_goal(n));
+}
+
+/// Search out from every source at once until `is_goal` accepts a node, with
+/// every step costing 1. Give a goal that's never met for the distance to
+/// everything that can be reached.
+pub fn breadth_first<N, I>(
+    sources: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Search<N, usize>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut bfs = BreadthFirst::new(sources, neighbours);
+    let goal = until_goal(&mut bfs, is_goal);
+    let mut search = bfs.into_search();
+    search.goal = goal;
+    return search;
+}
+
+/// Like `breadth_first`, but with each step costing whatever `neighbours`
+/// says it does.
+pub fn dijkstra<N, C, I>(
+    sources: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Search<N, C>
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (N, C)>,
+{
+    return a_star(sources, neighbours, |_n| C::default(), is_goal);
+}
+
+/// Like `dijkstra`, but heading first for whatever `heuristic` guesses is
+/// closest to the goal.
+pub fn a_star<N, C, I>(
+    sources: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(&N) -> I,
+    heuristic: impl FnMut(&N) -> C,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Search<N, C>
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut best = BestFirst::new(sources, neighbours, heuristic);
+    let goal = until_goal(&mut best, is_goal);
+    let mut search = best.into_search();
+    search.goal = goal;
+    return search;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{Grid, Point};
+
+    // a little town, with the roads between places and how long they take
+    const ROADS: [(char, char, u32); 7] = [
+        ('a', 'b', 7),
+        ('a', 'c', 9),
+        ('a', 'f', 14),
+        ('b', 'c', 10),
+        ('c', 'd', 11),
+        ('c', 'f', 2),
+        ('d', 'e', 6),
+    ];
+
+    fn roads_from(place: &char) -> Vec<(char, u32)> {
+        return ROADS
+            .iter()
+            .filter_map(|&(a, b, cost)| match *place {
+                p if p == a => Some((b, cost)),
+                p if p == b => Some((a, cost)),
+                _ => None,
+            })
+            .collect();
+    }
+
+    #[test]
+    fn breadth_first_counts_steps() {
+        let places = |p: &char| roads_from(p).into_iter().map(|(q, _cost)| q);
+        let search = breadth_first(['a'], places, |p| *p == 'e');
+        assert_eq!(search.goal(), Some(&'e'));
+        assert_eq!(search.goal_distance(), Some(3));
+        assert_eq!(search.goal_path(), Some(vec!['a', 'c', 'd', 'e']));
+
+        let everywhere = breadth_first(['e', 'f'], places, |_p| false);
+        assert_eq!(everywhere.goal(), None);
+        assert_eq!(everywhere.distances().len(), 6);
+        assert_eq!(everywhere.distance(&'a'), Some(1));
+        assert_eq!(everywhere.path_to(&'b'), Some(vec!['f', 'a', 'b']));
+        assert_eq!(everywhere.path_to(&'z'), None);
+    }
+
+    #[test]
+    fn dijkstra_finds_the_cheapest_way() {
+        let search = dijkstra(['a'], roads_from, |p| *p == 'e');
+        assert_eq!(search.goal_distance(), Some(26));
+        assert_eq!(search.goal_path(), Some(vec!['a', 'c', 'd', 'e']));
+        let search = dijkstra(['a'], roads_from, |_p| false);
+        assert_eq!(search.distance(&'f'), Some(11));
+    }
+
+    #[test]
+    fn a_star_agrees_with_dijkstra_and_does_less() {
+        let walls = Grid::from_str(0, "....#\n.##.#\n...#.\n.#...", "a tile", |c| {
+            Some(c == '#')
+        })
+        .unwrap();
+        let (start, end) = (Point(0, 0), Point(4, 2));
+        let open = |p: &Point| {
+            let p = *p;
+            return walls
+                .neighbours4(p)
+                .filter(|n| !walls[*n])
+                .map(|n| (n, 1))
+                .collect::<Vec<_>>();
+        };
+        let dijkstra = dijkstra([start], open, |p| *p == end);
+        let a_star = a_star([start], open, |p| p.manhattan(end), |p| *p == end);
+        assert_eq!(dijkstra.goal_distance(), Some(8));
+        assert_eq!(a_star.goal_distance(), Some(8));
+        assert_eq!(a_star.goal_path().unwrap().len(), 9);
+        assert!(a_star.distances().len() <= dijkstra.distances().len());
+    }
+
+    #[test]
+    fn a_star_revisits_nodes_when_the_heuristic_is_inconsistent() {
+        // b looks far from the goal, so c is visited the long way round first
+        let roads = |p: &char| match *p {
+            's' => vec![('a', 1), ('b', 1)],
+            'a' => vec![('c', 3)],
+            'b' => vec![('c', 1)],
+            'c' => vec![('g', 10)],
+            _ => vec![],
+        };
+        let guess = |p: &char| if *p == 'b' { 9 } else { 0 };
+        let search = a_star(['s'], roads, guess, |p| *p == 'g');
+        assert_eq!(search.goal_distance(), Some(12));
+        assert_eq!(search.goal_path(), Some(vec!['s', 'b', 'c', 'g']));
+
+        // c is visited twice, the second time at its real cost
+        let mut best = BestFirst::new(['s'], roads, guess);
+        let mut visits = vec![];
+        while let Some(p) = best.next() {
+            visits.push((p, best.search().distance(&p).unwrap()));
+        }
+        let expected = [('s', 0), ('a', 1), ('c', 4), ('b', 1), ('c', 2), ('g', 12)];
+        assert_eq!(visits, expected);
+    }
+}
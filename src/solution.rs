@@ -0,0 +1,187 @@
+use crate::error::{Error, Result};
+use crate::export::Recorder;
+use crate::visualise::Visualiser;
+use std::any::Any;
+use std::marker::PhantomData;
+
+/// A day's input after parsing, with its concrete type erased so that every
+/// day can sit in the same registry.
+pub type ParsedInput = Box<dyn Any + Send + Sync>;
+
+/// A single day's puzzle. The raw input is parsed once and both parts are
+/// solved from the parsed value.
+pub trait Solution {
+    type Input: Send + Sync + 'static;
+
+    const DAY: u8;
+    const TITLE: &'static str;
+
+    fn parse(file_contents: &str) -> Result<Self::Input>;
+    fn part_1(input: &Self::Input) -> Result<String>;
+    fn part_2(input: &Self::Input) -> Result<String>;
+
+    /// Solve one part while drawing the simulation behind it. `None` for days
+    /// that have nothing to show.
+    fn visualise(_input: &Self::Input, _part: u8, _vis: &mut Visualiser) -> Option<Result<String>> {
+        return None;
+    }
+
+    /// Solve one part while saving pictures of it. `None` for days that have
+    /// nothing to show.
+    fn export(_input: &Self::Input, _part: u8, _images: &mut Recorder) -> Option<Result<String>> {
+        return None;
+    }
+}
+
+/// Object-safe view of a day, used by the registry in `lib.rs`.
+pub trait AnySolution: Sync {
+    fn day(&self) -> u8;
+    fn title(&self) -> &'static str;
+    fn implemented(&self) -> bool;
+    fn parse(&self, file_contents: &str) -> Result<ParsedInput>;
+    /// Solve one part from a value returned by `parse`
+    fn solve(&self, input: &ParsedInput, part: u8) -> Result<String>;
+    /// Like `solve`, but drawing as it goes. `None` if the day can't.
+    fn visualise(
+        &self,
+        input: &ParsedInput,
+        part: u8,
+        vis: &mut Visualiser,
+    ) -> Option<Result<String>>;
+    /// Like `solve`, but saving pictures as it goes. `None` if the day can't.
+    fn export(
+        &self,
+        input: &ParsedInput,
+        part: u8,
+        images: &mut Recorder,
+    ) -> Option<Result<String>>;
+
+    fn run(&self, file_contents: &str, part: u8) -> Result<String> {
+        if !self.implemented() {
+            return Err(Error::NotImplemented { day: self.day() });
+        }
+        let input = self.parse(file_contents)?;
+        return self.solve(&input, part);
+    }
+}
+
+/// Registry entry for a day that implements `Solution`.
+pub struct Registered<S>(PhantomData<fn() -> S>);
+
+impl<S> Default for Registered<S> {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl<S> Registered<S> {
+    pub const fn new() -> Self {
+        return Self(PhantomData);
+    }
+}
+
+impl<S: Solution> Registered<S> {
+    /// The value `parse` returned, as long as it came from this day
+    fn input(input: &ParsedInput) -> Result<&S::Input> {
+        return input
+            .downcast_ref::<S::Input>()
+            .ok_or_else(|| Error::solve(S::DAY, "parsed input belongs to a different day"));
+    }
+}
+
+impl<S: Solution> AnySolution for Registered<S> {
+    fn day(&self) -> u8 {
+        return S::DAY;
+    }
+
+    fn title(&self) -> &'static str {
+        return S::TITLE;
+    }
+
+    fn implemented(&self) -> bool {
+        return true;
+    }
+
+    fn parse(&self, file_contents: &str) -> Result<ParsedInput> {
+        return Ok(Box::new(S::parse(file_contents)?));
+    }
+
+    fn solve(&self, input: &ParsedInput, part: u8) -> Result<String> {
+        let input = Self::input(input)?;
+        return match part {
+            1 => S::part_1(input),
+            2 => S::part_2(input),
+            _ => Err(Error::solve(S::DAY, &format!("there is no part {}", part))),
+        };
+    }
+
+    fn visualise(
+        &self,
+        input: &ParsedInput,
+        part: u8,
+        vis: &mut Visualiser,
+    ) -> Option<Result<String>> {
+        return match Self::input(input) {
+            Ok(input) => S::visualise(input, part, vis),
+            Err(e) => Some(Err(e)),
+        };
+    }
+
+    fn export(
+        &self,
+        input: &ParsedInput,
+        part: u8,
+        images: &mut Recorder,
+    ) -> Option<Result<String>> {
+        return match Self::input(input) {
+            Ok(input) => S::export(input, part, images),
+            Err(e) => Some(Err(e)),
+        };
+    }
+}
+
+/// Registry entry for a day that hasn't been solved yet.
+pub struct Unimplemented {
+    pub day: u8,
+    pub title: &'static str,
+}
+
+impl AnySolution for Unimplemented {
+    fn day(&self) -> u8 {
+        return self.day;
+    }
+
+    fn title(&self) -> &'static str {
+        return self.title;
+    }
+
+    fn implemented(&self) -> bool {
+        return false;
+    }
+
+    fn parse(&self, _file_contents: &str) -> Result<ParsedInput> {
+        return Ok(Box::new(()));
+    }
+
+    fn solve(&self, _input: &ParsedInput, _part: u8) -> Result<String> {
+        return Err(Error::NotImplemented { day: self.day });
+    }
+
+    fn visualise(
+        &self,
+        _input: &ParsedInput,
+        _part: u8,
+        _vis: &mut Visualiser,
+    ) -> Option<Result<String>> {
+        return None;
+    }
+
+    fn export(
+        &self,
+        _input: &ParsedInput,
+        _part: u8,
+        _images: &mut Recorder,
+    ) -> Option<Result<String>> {
+        return None;
+    }
+}
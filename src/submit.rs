@@ -0,0 +1,424 @@
+use crate::answers::Answers;
+use crate::client::{Client, ClientError};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// What the server made of a submitted answer.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Outcome {
+    Right,
+    Wrong,
+    TooHigh,
+    TooLow,
+    /// Answered too recently, try again after the given time
+    Wait(Option<Duration>),
+    /// The part has already been solved, so there's nothing to submit to
+    AlreadySolved,
+    /// A response that isn't recognised, with its text
+    Unknown(String),
+}
+
+impl Outcome {
+    /// Whether the outcome says anything about the answer itself, and so
+    /// should be remembered.
+    pub fn is_final(&self) -> bool {
+        return matches!(
+            self,
+            Self::Right | Self::Wrong | Self::TooHigh | Self::TooLow
+        );
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        return match self {
+            Self::Right => Some("right"),
+            Self::Wrong => Some("wrong"),
+            Self::TooHigh => Some("too-high"),
+            Self::TooLow => Some("too-low"),
+            _ => None,
+        };
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        return match name {
+            "right" => Some(Self::Right),
+            "wrong" => Some(Self::Wrong),
+            "too-high" => Some(Self::TooHigh),
+            "too-low" => Some(Self::TooLow),
+            _ => None,
+        };
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Self::Right => write!(f, "right"),
+            Self::Wrong => write!(f, "wrong"),
+            Self::TooHigh => write!(f, "wrong, too high"),
+            Self::TooLow => write!(f, "wrong, too low"),
+            Self::Wait(Some(x)) => write!(f, "answered too recently, wait {}s", x.as_secs()),
+            Self::Wait(None) => write!(f, "answered too recently, wait a while"),
+            Self::AlreadySolved => write!(f, "already solved"),
+            Self::Unknown(text) => write!(f, "unrecognised response: {}", text),
+        };
+    }
+}
+
+// parses e.g. "1m 5s" out of "You have 1m 5s left to wait."
+fn parse_wait(text: &str) -> Option<Duration> {
+    let start = text.find("You have ")? + "You have ".len();
+    let end = start + text[start..].find(" left to wait")?;
+    let mut secs = 0;
+    for word in text[start..end].split_whitespace() {
+        let (number, unit) = word.split_at(word.find(|c: char| !c.is_ascii_digit())?);
+        let number: u64 = number.parse().ok()?;
+        secs += match unit {
+            "h" => number * 3600,
+            "m" => number * 60,
+            "s" => number,
+            _ => return None,
+        };
+    }
+    return Some(Duration::from_secs(secs));
+}
+
+/// Work out the outcome from the page returned after submitting.
+pub fn parse_response(page: &str) -> Outcome {
+    if page.contains("That's the right answer") {
+        return Outcome::Right;
+    }
+    if page.contains("That's not the right answer") {
+        if page.contains("too high") {
+            return Outcome::TooHigh;
+        }
+        if page.contains("too low") {
+            return Outcome::TooLow;
+        }
+        return Outcome::Wrong;
+    }
+    if page.contains("You gave an answer too recently") {
+        return Outcome::Wait(parse_wait(page));
+    }
+    if page.contains("Did you already complete it") {
+        return Outcome::AlreadySolved;
+    }
+    // pages are HTML, so keep it to something short enough to print
+    let text: String = page.split_whitespace().collect::<Vec<&str>>().join(" ");
+    return Outcome::Unknown(text.chars().take(200).collect());
+}
+
+/// Log of every answer given a final outcome, one per line as
+/// `DAY PART OUTCOME ANSWER`.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct Submissions {
+    entries: Vec<(u8, u8, Outcome, String)>,
+}
+
+impl Submissions {
+    pub fn path(answers_path: &Path) -> PathBuf {
+        return answers_path.with_file_name("submissions.log");
+    }
+
+    /// Read the log. A missing file is treated as an empty log.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = match fs::read_to_string(path) {
+            Ok(x) => x,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+        let mut entries = vec![];
+        for (i, line) in contents.lines().enumerate() {
+            let invalid = || {
+                let message = format!(
+                    "{} line {}: invalid entry `{}`",
+                    path.display(),
+                    i + 1,
+                    line
+                );
+                io::Error::new(io::ErrorKind::InvalidData, message)
+            };
+            let mut words = line.splitn(4, ' ');
+            let day = words
+                .next()
+                .and_then(|x| x.parse().ok())
+                .ok_or_else(invalid)?;
+            let part = words
+                .next()
+                .and_then(|x| x.parse().ok())
+                .ok_or_else(invalid)?;
+            let outcome = words
+                .next()
+                .and_then(Outcome::from_name)
+                .ok_or_else(invalid)?;
+            let answer = words.next().ok_or_else(invalid)?;
+            entries.push((day, part, outcome, answer.to_owned()));
+        }
+        return Ok(Self { entries });
+    }
+
+    pub fn append(
+        &mut self,
+        path: &Path,
+        day: u8,
+        part: u8,
+        outcome: &Outcome,
+        answer: &str,
+    ) -> io::Result<()> {
+        let name = outcome.name().expect("only final outcomes are logged");
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{} {} {} {}", day, part, name, answer)?;
+        self.entries
+            .push((day, part, outcome.clone(), answer.to_owned()));
+        return Ok(());
+    }
+
+    /// The answer the server said was right, if one has been submitted
+    pub fn right_answer(&self, day: u8, part: u8) -> Option<&str> {
+        return self
+            .entries
+            .iter()
+            .find(|(d, p, outcome, _a)| *d == day && *p == part && *outcome == Outcome::Right)
+            .map(|(_d, _p, _o, answer)| answer.as_str());
+    }
+
+    /// Why `answer` is already known to be wrong, if it is. Besides answers
+    /// that were rejected before, a number at or past one that was too high
+    /// or too low can't be right either.
+    pub fn known_wrong(&self, day: u8, part: u8, answer: &str) -> Option<String> {
+        let number = answer.parse::<i128>().ok();
+        for (_d, _p, outcome, previous) in self.entries.iter().filter(|e| e.0 == day && e.1 == part)
+        {
+            if *outcome == Outcome::Right {
+                continue;
+            }
+            if previous == answer {
+                return Some(format!(
+                    "{} was already submitted and was {}",
+                    answer, outcome
+                ));
+            }
+            let bound = match (number, previous.parse::<i128>()) {
+                (Some(x), Ok(y)) => (x, y),
+                _ => continue,
+            };
+            match outcome {
+                Outcome::TooHigh if bound.0 > bound.1 => {
+                    return Some(format!("{} was too high, so {} is too", previous, answer));
+                }
+                Outcome::TooLow if bound.0 < bound.1 => {
+                    return Some(format!("{} was too low, so {} is too", previous, answer));
+                }
+                _ => {}
+            }
+        }
+        return None;
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Submitted {
+    Sent(Outcome),
+    /// The same answer was already submitted and was right, so nothing was
+    /// sent
+    AlreadyRight,
+    /// The answer can't be right, so nothing was sent
+    KnownWrong(String),
+}
+
+/// Submit an answer unless it's already known to be right or wrong. Final
+/// outcomes are logged in `submissions.log` next to the answers file, and a
+/// right answer is also saved in the answers file itself. Only the log
+/// counts as knowing, as `--record` puts answers in the answers file without
+/// checking them.
+pub fn submit(
+    client: &mut Client,
+    answers_path: &Path,
+    day: u8,
+    part: u8,
+    answer: &str,
+) -> Result<Submitted, ClientError> {
+    let log_path = Submissions::path(answers_path);
+    let mut submissions = Submissions::load(&log_path)?;
+    match submissions.right_answer(day, part) {
+        Some(x) if x == answer => return Ok(Submitted::AlreadyRight),
+        Some(x) => {
+            let reason = format!("the right answer was already submitted as {}", x);
+            return Ok(Submitted::KnownWrong(reason));
+        }
+        None => {}
+    }
+    if let Some(reason) = submissions.known_wrong(day, part, answer) {
+        return Ok(Submitted::KnownWrong(reason));
+    }
+
+    let outcome = parse_response(&client.post_answer(day, part, answer)?);
+    if outcome.is_final() {
+        submissions.append(&log_path, day, part, &outcome, answer)?;
+    }
+    if outcome == Outcome::Right {
+        let mut answers = Answers::load(answers_path)?;
+        answers.insert(day, part, answer);
+        answers.save(answers_path)?;
+    }
+    return Ok(Submitted::Sent(outcome));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::tests::FakeServer;
+
+    const RIGHT: &str = "<article><p>That's the right answer!  You are one gold star closer to collecting enough star fruit.</p></article>";
+    const TOO_HIGH: &str = "<article><p>That's not the right answer; your answer is too high.  If you're stuck, make sure you're using the full input data.  Please wait one minute before trying again.</p></article>";
+    const TOO_LOW: &str =
+        "<article><p>That's not the right answer; your answer is too low.</p></article>";
+    const WRONG: &str = "<article><p>That's not the right answer.  If you're stuck, make sure you're using the full input data.</p></article>";
+    const WAIT: &str = "<article><p>You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 5s left to wait. <a href=\"/2022/day/1\">[Return to Day 1]</a></p></article>";
+
+    fn temp_answers(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("aoc2022-submit-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        return dir.join("answers.toml");
+    }
+
+    #[test]
+    fn recognises_each_kind_of_response() {
+        assert_eq!(parse_response(RIGHT), Outcome::Right);
+        assert_eq!(parse_response(TOO_HIGH), Outcome::TooHigh);
+        assert_eq!(parse_response(TOO_LOW), Outcome::TooLow);
+        assert_eq!(parse_response(WRONG), Outcome::Wrong);
+        assert_eq!(
+            parse_response(WAIT),
+            Outcome::Wait(Some(Duration::from_secs(65)))
+        );
+        assert_eq!(
+            parse_response("<p>You don't seem to be solving the right level.  Did you already complete it?</p>"),
+            Outcome::AlreadySolved
+        );
+        assert_eq!(
+            parse_response("<p>Something\n  else</p>"),
+            Outcome::Unknown("<p>Something else</p>".to_owned())
+        );
+    }
+
+    #[test]
+    fn right_answers_are_posted_and_recorded() {
+        let server = FakeServer::start(|_r| (200, RIGHT.to_owned()));
+        let path = temp_answers("right");
+        let mut client = Client::new(&server.url, "abc123", Duration::ZERO);
+        let result = submit(&mut client, &path, 4, 2, "841").unwrap();
+        assert_eq!(result, Submitted::Sent(Outcome::Right));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/2022/day/4/answer");
+        assert_eq!(requests[0].cookie.as_deref(), Some("session=abc123"));
+        assert_eq!(requests[0].body, "level=2&answer=841");
+        assert_eq!(Answers::load(&path).unwrap().get(4, 2), Some("841"));
+        assert_eq!(
+            fs::read_to_string(Submissions::path(&path)).unwrap(),
+            "4 2 right 841\n"
+        );
+
+        // now that it's known, submitting again doesn't ask the server
+        let result = submit(&mut client, &path, 4, 2, "841").unwrap();
+        assert_eq!(result, Submitted::AlreadyRight);
+        assert_eq!(server.requests().len(), 1);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn known_wrong_answers_are_never_resubmitted() {
+        let server = FakeServer::start(|r| {
+            return match r.body.as_str() {
+                "level=1&answer=100" => (200, TOO_HIGH.to_owned()),
+                "level=1&answer=10" => (200, TOO_LOW.to_owned()),
+                _ => (200, WRONG.to_owned()),
+            };
+        });
+        let path = temp_answers("wrong");
+        let mut client = Client::new(&server.url, "abc123", Duration::ZERO);
+        let sent = Submitted::Sent;
+        assert_eq!(
+            submit(&mut client, &path, 7, 1, "100").unwrap(),
+            sent(Outcome::TooHigh)
+        );
+        assert_eq!(
+            submit(&mut client, &path, 7, 1, "10").unwrap(),
+            sent(Outcome::TooLow)
+        );
+        assert_eq!(
+            submit(&mut client, &path, 7, 1, "abc").unwrap(),
+            sent(Outcome::Wrong)
+        );
+
+        for answer in ["100", "150", "10", "3", "abc"] {
+            match submit(&mut client, &path, 7, 1, answer).unwrap() {
+                Submitted::KnownWrong(_reason) => {}
+                other => panic!("{} was submitted again: {:?}", answer, other),
+            }
+        }
+        assert_eq!(server.requests().len(), 3);
+        // the other part has its own history
+        assert_eq!(
+            submit(&mut client, &path, 7, 2, "100").unwrap(),
+            sent(Outcome::Wrong)
+        );
+        assert!(Answers::load(&path).unwrap().get(7, 1).is_none());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn recorded_answers_are_still_submitted() {
+        let server = FakeServer::start(|r| {
+            return match r.body.as_str() {
+                "level=1&answer=24000" => (200, RIGHT.to_owned()),
+                _ => (200, WRONG.to_owned()),
+            };
+        });
+        let path = temp_answers("recorded");
+        let mut client = Client::new(&server.url, "abc123", Duration::ZERO);
+        // as `--record` leaves it, with answers nobody has checked
+        let mut recorded = Answers::default();
+        recorded.insert(1, 1, "24000");
+        recorded.insert(1, 2, "12");
+        recorded.save(&path).unwrap();
+
+        let sent = Submitted::Sent;
+        assert_eq!(
+            submit(&mut client, &path, 1, 1, "24000").unwrap(),
+            sent(Outcome::Right)
+        );
+        assert_eq!(
+            submit(&mut client, &path, 1, 2, "45000").unwrap(),
+            sent(Outcome::Wrong)
+        );
+        assert_eq!(server.requests().len(), 2);
+        assert_eq!(
+            submit(&mut client, &path, 1, 1, "24001").unwrap(),
+            Submitted::KnownWrong("the right answer was already submitted as 24000".to_owned())
+        );
+        assert_eq!(server.requests().len(), 2);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn waiting_is_not_recorded() {
+        let server = FakeServer::start(|_r| (200, WAIT.to_owned()));
+        let path = temp_answers("wait");
+        let mut client = Client::new(&server.url, "abc123", Duration::ZERO);
+        let result = submit(&mut client, &path, 1, 1, "5").unwrap();
+        assert_eq!(
+            result,
+            Submitted::Sent(Outcome::Wait(Some(Duration::from_secs(65))))
+        );
+        assert!(!Submissions::path(&path).exists());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
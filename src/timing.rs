@@ -0,0 +1,121 @@
+use std::iter::Sum;
+use std::ops::Add;
+use std::time::{Duration, Instant};
+
+/// Wall time statistics over repeated runs of the same step.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub struct Timing {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+}
+
+impl Timing {
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let mid = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) {
+            (sorted[mid - 1] + sorted[mid]) / 2
+        } else {
+            sorted[mid]
+        };
+        let total: Duration = sorted.iter().sum();
+        return Self {
+            runs: sorted.len(),
+            min: sorted[0],
+            median,
+            mean: total / sorted.len() as u32,
+        };
+    }
+}
+
+// adding timings of different steps gives the combined cost of running them
+// all, which is what the totals in the summary table show
+impl Add for Timing {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        return Self {
+            runs: self.runs.max(other.runs),
+            min: self.min + other.min,
+            median: self.median + other.median,
+            mean: self.mean + other.mean,
+        };
+    }
+}
+
+impl Sum for Timing {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        return iter.fold(Self::default(), |acc, t| acc + t);
+    }
+}
+
+/// Run `f` `repeats` times (at least once), returning the last result and the
+/// timings. Stops at the first error, since a failing step isn't worth timing.
+pub fn time<T, E>(repeats: usize, mut f: impl FnMut() -> Result<T, E>) -> Result<(T, Timing), E> {
+    let mut samples = vec![];
+    let mut result;
+    loop {
+        let start = Instant::now();
+        result = f()?;
+        samples.push(start.elapsed());
+        if samples.len() >= repeats {
+            break;
+        }
+    }
+    return Ok((result, Timing::from_samples(&samples)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(x: u64) -> Duration {
+        return Duration::from_millis(x);
+    }
+
+    #[test]
+    fn statistics_of_odd_number_of_samples() {
+        let expected = Timing {
+            runs: 3,
+            min: ms(1),
+            median: ms(4),
+            mean: ms(5),
+        };
+        assert_eq!(Timing::from_samples(&[ms(10), ms(1), ms(4)]), expected);
+    }
+
+    #[test]
+    fn median_of_even_number_of_samples_is_halfway() {
+        let result = Timing::from_samples(&[ms(2), ms(8), ms(4), ms(6)]);
+        assert_eq!(result.median, ms(5));
+    }
+
+    #[test]
+    fn timed_function_runs_requested_number_of_times() {
+        let mut calls = 0;
+        let (result, timing) = time(4, || {
+            calls += 1;
+            return Ok::<_, ()>(calls);
+        })
+        .unwrap();
+        assert_eq!(result, 4);
+        assert_eq!(timing.runs, 4);
+    }
+
+    #[test]
+    fn timing_stops_at_first_error() {
+        let mut calls = 0;
+        let result = time(4, || {
+            calls += 1;
+            return Err::<(), _>("broken");
+        });
+        assert_eq!(result, Err("broken"));
+        assert_eq!(calls, 1);
+    }
+}
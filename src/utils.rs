@@ -1,55 +1,908 @@
-pub fn divide_iterator_on_predicate<'a, I, P, T>(input: I, pred: P) -> Vec<Vec<T>>
+use crate::error::{Error, Result};
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, RangeInclusive, Sub, SubAssign};
+
+/// Splits an iterator into groups of items, lazily, on items that
+/// separate them
+pub trait SplitWhen: Iterator + Sized {
+    /// The runs of items between separators. Separators are dropped, and
+    /// there are no empty groups, so separators at the start or end or
+    /// next to each other make no difference.
+    fn split_when<P>(self, is_separator: P) -> Groups<Self, P>
+    where
+        P: FnMut(&Self::Item) -> bool,
+    {
+        return Groups {
+            iter: self,
+            is_separator,
+        };
+    }
+}
+
+impl<I: Iterator> SplitWhen for I {}
+
+pub struct Groups<I, P> {
+    iter: I,
+    is_separator: P,
+}
+
+impl<I, P> Iterator for Groups<I, P>
 where
-    I: IntoIterator<Item = T>,
-    P: Fn(&T) -> bool,
+    I: Iterator,
+    P: FnMut(&I::Item) -> bool,
 {
-    let vec: Vec<Vec<T>> = vec![];
-    let entries = input.into_iter().fold(vec, |mut acc, s| {
-        if pred(&s) {
-            acc.push(vec![]);
-            acc
-        } else {
-            let last = match acc.last_mut() {
-                Some(x) => x,
-                None => {
-                    acc.push(vec![]);
-                    acc.last_mut().unwrap()
-                }
-            };
-            last.push(s);
-            acc
+    type Item = Vec<I::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut group = vec![];
+        for item in self.iter.by_ref() {
+            if !(self.is_separator)(&item) {
+                group.push(item);
+            } else if !group.is_empty() {
+                return Some(group);
+            }
+        }
+        return if group.is_empty() { None } else { Some(group) };
+    }
+}
+
+/// The same as `split_when`, but borrowing each group from `items`
+pub fn groups_by_separator<'a, T>(
+    items: &'a [T],
+    is_separator: impl FnMut(&T) -> bool + 'a,
+) -> impl Iterator<Item = &'a [T]> + 'a {
+    return items.split(is_separator).filter(|g| !g.is_empty());
+}
+
+/// A position, or the difference between two. `x` goes across and `y` goes
+/// down, the way grids are drawn, unless a day says otherwise.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash, Default)]
+pub struct Point<T = isize>(pub T, pub T);
+
+/// Like `Point`, with a `z` as well
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash, Default)]
+pub struct Point3<T = isize>(pub T, pub T, pub T);
+
+impl<T: Add<Output = T>> Add for Point<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        return Point(self.0 + other.0, self.1 + other.1);
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        return Point(self.0 - other.0, self.1 - other.1);
+    }
+}
+
+impl<T: Mul<Output = T> + Copy> Mul<T> for Point<T> {
+    type Output = Self;
+
+    fn mul(self, n: T) -> Self {
+        return Point(self.0 * n, self.1 * n);
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Point<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        return Point(-self.0, -self.1);
+    }
+}
+
+impl<T: AddAssign> AddAssign for Point<T> {
+    fn add_assign(&mut self, other: Self) {
+        self.0 += other.0;
+        self.1 += other.1;
+    }
+}
+
+impl<T: SubAssign> SubAssign for Point<T> {
+    fn sub_assign(&mut self, other: Self) {
+        self.0 -= other.0;
+        self.1 -= other.1;
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point3<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        return Point3(self.0 + other.0, self.1 + other.1, self.2 + other.2);
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point3<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        return Point3(self.0 - other.0, self.1 - other.1, self.2 - other.2);
+    }
+}
+
+impl<T: Mul<Output = T> + Copy> Mul<T> for Point3<T> {
+    type Output = Self;
+
+    fn mul(self, n: T) -> Self {
+        return Point3(self.0 * n, self.1 * n, self.2 * n);
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Point3<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        return Point3(-self.0, -self.1, -self.2);
+    }
+}
+
+impl<T: AddAssign> AddAssign for Point3<T> {
+    fn add_assign(&mut self, other: Self) {
+        self.0 += other.0;
+        self.1 += other.1;
+        self.2 += other.2;
+    }
+}
+
+impl<T: SubAssign> SubAssign for Point3<T> {
+    fn sub_assign(&mut self, other: Self) {
+        self.0 -= other.0;
+        self.1 -= other.1;
+        self.2 -= other.2;
+    }
+}
+
+/// The signed whole numbers a point's coordinates can be
+pub trait Coordinate:
+    Copy + Ord + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Neg<Output = Self>
+{
+    fn abs(self) -> Self;
+    fn signum(self) -> Self;
+    fn to_usize(self) -> Option<usize>;
+    fn from_usize(n: usize) -> Self;
+}
+
+macro_rules! coordinates {
+    ($($t:ty),*) => {$(
+        impl Coordinate for $t {
+            fn abs(self) -> Self {
+                return <$t>::abs(self);
+            }
+
+            fn signum(self) -> Self {
+                return <$t>::signum(self);
+            }
+
+            fn to_usize(self) -> Option<usize> {
+                return usize::try_from(self).ok();
+            }
+
+            fn from_usize(n: usize) -> Self {
+                return n as $t;
+            }
         }
-    });
-    return entries;
+    )*};
+}
+
+coordinates!(isize, i64, i32);
+
+impl<T: Coordinate> Point<T> {
+    pub fn manhattan(self, other: Self) -> T {
+        let d = self - other;
+        return d.0.abs() + d.1.abs();
+    }
+
+    /// How many king's moves apart two points are
+    pub fn chebyshev(self, other: Self) -> T {
+        let d = self - other;
+        return d.0.abs().max(d.1.abs());
+    }
+
+    /// Each coordinate as -1, 0 or 1
+    pub fn signum(self) -> Self {
+        return Point(self.0.signum(), self.1.signum());
+    }
+
+    /// A quarter turn clockwise, as drawn
+    pub fn rotate_clockwise(self) -> Self {
+        return Point(-self.1, self.0);
+    }
+
+    /// A quarter turn anticlockwise, as drawn
+    pub fn rotate_anticlockwise(self) -> Self {
+        return Point(self.1, -self.0);
+    }
+
+    /// Both coordinates as indices, if neither is negative
+    pub fn to_indices(self) -> Option<(usize, usize)> {
+        return Some((self.0.to_usize()?, self.1.to_usize()?));
+    }
+
+    /// Where this is in a grid `width` wide stored a row at a time, if it's
+    /// in it
+    pub fn to_index(self, width: usize) -> Option<usize> {
+        let (x, y) = self.to_indices()?;
+        return (x < width).then_some(y * width + x);
+    }
+
+    /// The point at `index` in a grid `width` wide stored a row at a time
+    pub fn from_index(index: usize, width: usize) -> Self {
+        return Point(T::from_usize(index % width), T::from_usize(index / width));
+    }
+}
+
+impl<T: Coordinate> Point3<T> {
+    pub fn manhattan(self, other: Self) -> T {
+        let d = self - other;
+        return d.0.abs() + d.1.abs() + d.2.abs();
+    }
+
+    pub fn chebyshev(self, other: Self) -> T {
+        let d = self - other;
+        return d.0.abs().max(d.1.abs()).max(d.2.abs());
+    }
+
+    pub fn signum(self) -> Self {
+        return Point3(self.0.signum(), self.1.signum(), self.2.signum());
+    }
+}
+
+// constants can't be generic over the coordinate type
+impl Point {
+    pub const ORIGIN: Self = Point(0, 0);
+    pub const UP: Self = Point(0, -1);
+    pub const DOWN: Self = Point(0, 1);
+    pub const LEFT: Self = Point(-1, 0);
+    pub const RIGHT: Self = Point(1, 0);
+    /// Clockwise, starting from up
+    pub const DIRECTIONS: [Self; 4] = [Self::UP, Self::RIGHT, Self::DOWN, Self::LEFT];
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, Copy)]
-pub struct Point(pub isize, pub isize);
+impl Point3 {
+    pub const ORIGIN: Self = Point3(0, 0, 0);
+    /// One step along each axis, both ways
+    pub const DIRECTIONS: [Self; 6] = [
+        Point3(1, 0, 0),
+        Point3(-1, 0, 0),
+        Point3(0, 1, 0),
+        Point3(0, -1, 0),
+        Point3(0, 0, 1),
+        Point3(0, 0, -1),
+    ];
+}
 
-#[derive(PartialEq, Eq, Debug)]
-pub struct Grid<T: PartialEq + Eq + Clone> {
+/// A rectangle of cells covering `min_point` to `max_point` inclusive, with
+/// `x` going across and `y` going down.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Grid<T> {
     pub min_point: Point,
     pub max_point: Point,
+    /// Indexed by column then row, `array[x][y]`
     pub array: Vec<Vec<T>>,
 }
 
-impl<T: Clone + PartialEq + Eq + std::fmt::Debug + Copy> Grid<T> {
+/// Turn rows into columns, or columns into rows
+fn flip<T>(lines: Vec<Vec<T>>) -> Vec<Vec<T>> {
+    let len = lines.first().map(|line| line.len()).unwrap_or(0);
+    let mut flipped: Vec<Vec<T>> = (0..len).map(|_i| Vec::with_capacity(lines.len())).collect();
+    for line in lines {
+        for (to, t) in flipped.iter_mut().zip(line) {
+            to.push(t);
+        }
+    }
+    return flipped;
+}
+
+impl<T: Clone> Grid<T> {
     pub fn new(min_point: Point, max_point: Point, init: T) -> Self {
         let Point(min_x, min_y) = min_point;
         let Point(max_x, max_y) = max_point;
+        let width = (max_x - min_x + 1).max(0) as usize;
+        let height = (max_y - min_y + 1).max(0) as usize;
         return Self {
             min_point,
             max_point,
-            array: vec![vec![init; (max_y - min_y) as usize + 2]; (max_x - min_x) as usize + 2],
+            array: vec![vec![init; height]; width],
+        };
+    }
+
+    /// Swap `x` and `y`, so rows become columns
+    pub fn transpose(&self) -> Self {
+        return Self {
+            min_point: Point(self.min_point.1, self.min_point.0),
+            max_point: Point(self.max_point.1, self.max_point.0),
+            array: flip(self.array.clone()),
+        };
+    }
+
+    /// Turn a quarter clockwise, as it's shown, keeping `min_point` where it
+    /// is
+    pub fn rotate_clockwise(&self) -> Self {
+        let mut array = flip(self.array.clone());
+        array.reverse();
+        return self.rotated(array);
+    }
+
+    /// Turn a quarter anticlockwise, as it's shown, keeping `min_point`
+    /// where it is
+    pub fn rotate_anticlockwise(&self) -> Self {
+        let mut array = self.array.clone();
+        array.reverse();
+        return self.rotated(flip(array));
+    }
+
+    fn rotated(&self, array: Vec<Vec<T>>) -> Self {
+        let Point(x, y) = self.min_point;
+        return Self {
+            min_point: self.min_point,
+            max_point: Point(
+                x + self.height() as isize - 1,
+                y + self.width() as isize - 1,
+            ),
+            array,
+        };
+    }
+}
+
+impl<T> Grid<T> {
+    /// One cell per character, with rows going down from `Point(0, 0)`.
+    /// `cell` returns `None` for characters that aren't what's `expected`.
+    pub fn from_str(
+        day: u8,
+        s: &str,
+        expected: &str,
+        cell: impl Fn(char) -> Option<T>,
+    ) -> Result<Self> {
+        let mut rows: Vec<Vec<T>> = vec![];
+        for (i, line) in s.lines().enumerate() {
+            let row = line
+                .chars()
+                .enumerate()
+                .map(|(j, c)| {
+                    cell(c).ok_or_else(|| {
+                        let message = format!("expected {}", expected);
+                        Error::parse(day, i + 1, j + 1, &c.to_string(), &message)
+                    })
+                })
+                .collect::<Result<Vec<T>>>()?;
+            if rows.first().is_some_and(|first| first.len() != row.len()) {
+                return Err(Error::parse(
+                    day,
+                    i + 1,
+                    1,
+                    line,
+                    "rows must all be the same length",
+                ));
+            }
+            rows.push(row);
+        }
+        let width = rows.first().map(|row| row.len()).unwrap_or(0);
+        if width == 0 {
+            return Err(Error::parse(day, 1, 1, "", "no grid in input"));
+        }
+        return Ok(Self {
+            min_point: Point(0, 0),
+            max_point: Point(width as isize - 1, rows.len() as isize - 1),
+            array: flip(rows),
+        });
+    }
+
+    pub fn width(&self) -> usize {
+        return self.array.len();
+    }
+
+    pub fn height(&self) -> usize {
+        return self.array.first().map(|column| column.len()).unwrap_or(0);
+    }
+
+    fn index_of(&self, point: Point) -> Option<(usize, usize)> {
+        let (x, y) = (point - self.min_point).to_indices()?;
+        return (x < self.width() && y < self.height()).then_some((x, y));
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        return self.index_of(point).is_some();
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        let (x, y) = self.index_of(point)?;
+        return Some(&self.array[x][y]);
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        let (x, y) = self.index_of(point)?;
+        return Some(&mut self.array[x][y]);
+    }
+
+    /// Every point in the grid, a row at a time
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let Point(min_x, min_y) = self.min_point;
+        let Point(max_x, max_y) = self.max_point;
+        return (min_y..=max_y).flat_map(move |y| (min_x..=max_x).map(move |x| Point(x, y)));
+    }
+
+    /// Every cell in the grid along with its point, a row at a time
+    pub fn iter_points(&self) -> impl Iterator<Item = (Point, &T)> {
+        return self.points().map(|p| (p, &self[p]));
+    }
+
+    /// The points above, below, left and right of `point` that are in the
+    /// grid
+    pub fn neighbours4(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        return Point::DIRECTIONS
+            .into_iter()
+            .map(move |d| point + d)
+            .filter(|p| self.contains(*p));
+    }
+
+    /// Like `neighbours4`, but with the diagonals as well
+    pub fn neighbours8(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        let Point(x, y) = point;
+        return (-1..=1)
+            .flat_map(move |j| (-1..=1).map(move |i| Point(x + i, y + j)))
+            .filter(move |p| *p != point && self.contains(*p));
+    }
+
+    /// The cells in row `y`, left to right
+    pub fn row(&self, y: isize) -> impl DoubleEndedIterator<Item = (Point, &T)> {
+        return (self.min_point.0..=self.max_point.0)
+            .filter_map(move |x| self.get(Point(x, y)).map(|t| (Point(x, y), t)));
+    }
+
+    /// The cells in column `x`, top to bottom
+    pub fn column(&self, x: isize) -> impl DoubleEndedIterator<Item = (Point, &T)> {
+        return (self.min_point.1..=self.max_point.1)
+            .filter_map(move |y| self.get(Point(x, y)).map(|t| (Point(x, y), t)));
+    }
+
+    /// The cells passed going from `from` in steps of `step`, not including
+    /// `from` itself, until the edge of the grid
+    pub fn ray(&self, from: Point, step: Point) -> impl Iterator<Item = (Point, &T)> {
+        return std::iter::successors(Some(from), move |p| Some(*p + step))
+            .skip(1)
+            .map_while(|p| self.get(p).map(|t| (p, t)));
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        return Grid {
+            min_point: self.min_point,
+            max_point: self.max_point,
+            array: self
+                .array
+                .iter()
+                .map(|column| column.iter().map(&f).collect())
+                .collect(),
+        };
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        return self
+            .get(point)
+            .unwrap_or_else(|| panic!("{:?} is outside the grid", point));
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        return self
+            .get_mut(point)
+            .unwrap_or_else(|| panic!("{:?} is outside the grid", point));
+    }
+}
+
+/// One character per cell, if that's how the cells display
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for y in self.min_point.1..=self.max_point.1 {
+            if y != self.min_point.1 {
+                writeln!(f)?;
+            }
+            for (_p, t) in self.row(y) {
+                write!(f, "{}", t)?;
+            }
+        }
+        return Ok(());
+    }
+}
+
+/// A grid without edges that only keeps the cells that have been set. Its
+/// bounds are whatever's needed to hold them.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point, T>,
+    bounds: Option<(Point, Point)>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl<T: Clone> SparseGrid<T> {
+    /// The cells of `grid` that `keep` says to
+    pub fn from_dense(grid: &Grid<T>, keep: impl Fn(&T) -> bool) -> Self {
+        let mut sparse = Self::new();
+        for (p, t) in grid.iter_points().filter(|(_p, t)| keep(t)) {
+            sparse.insert(p, t.clone());
+        }
+        return sparse;
+    }
+
+    /// A dense grid covering the current bounds, with `fill` where there
+    /// isn't a cell. Empty if there aren't any cells.
+    pub fn to_dense(&self, fill: T) -> Grid<T> {
+        let (min, max) = self.bounds.unwrap_or((Point(0, 0), Point(-1, -1)));
+        let mut grid = Grid::new(min, max, fill);
+        for (p, t) in self.cells.iter() {
+            grid[*p] = t.clone();
+        }
+        return grid;
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        return Self {
+            cells: HashMap::new(),
+            bounds: None,
+        };
+    }
+
+    /// Like `Grid::from_str`, with every character becoming a cell
+    pub fn from_str(
+        day: u8,
+        s: &str,
+        expected: &str,
+        cell: impl Fn(char) -> Option<T>,
+    ) -> Result<Self> {
+        let grid = Grid::from_str(day, s, expected, cell)?;
+        let mut sparse = Self::new();
+        for (x, column) in grid.array.into_iter().enumerate() {
+            for (y, t) in column.into_iter().enumerate() {
+                sparse.insert(Point(x as isize, y as isize), t);
+            }
+        }
+        return Ok(sparse);
+    }
+
+    /// The corners of the smallest rectangle holding every cell, or `None` if
+    /// there aren't any
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        return self.bounds;
+    }
+
+    pub fn len(&self) -> usize {
+        return self.cells.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.cells.is_empty();
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        return self.cells.contains_key(&point);
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        return self.cells.get(&point);
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        return self.cells.get_mut(&point);
+    }
+
+    /// Set a cell, growing the bounds if need be. Returns what was there.
+    pub fn insert(&mut self, point: Point, value: T) -> Option<T> {
+        let Point(x, y) = point;
+        self.bounds = Some(match self.bounds {
+            Some((min, max)) => (
+                Point(min.0.min(x), min.1.min(y)),
+                Point(max.0.max(x), max.1.max(y)),
+            ),
+            None => (point, point),
+        });
+        return self.cells.insert(point, value);
+    }
+
+    /// Clear a cell, shrinking the bounds if it was on their edge
+    pub fn remove(&mut self, point: Point) -> Option<T> {
+        let removed = self.cells.remove(&point)?;
+        if let Some((min, max)) = self.bounds {
+            let Point(x, y) = point;
+            if x == min.0 || x == max.0 || y == min.1 || y == max.1 {
+                self.bounds = self.cells.keys().fold(None, |bounds, p| match bounds {
+                    Some((min, max)) => Some((
+                        Point(min.0.min(p.0), min.1.min(p.1)),
+                        Point(max.0.max(p.0), max.1.max(p.1)),
+                    )),
+                    None => Some((*p, *p)),
+                });
+            }
+        }
+        return Some(removed);
+    }
+
+    /// Every point with a cell, a row at a time
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let mut points: Vec<Point> = self.cells.keys().copied().collect();
+        points.sort_unstable_by_key(|p| (p.1, p.0));
+        return points.into_iter();
+    }
+
+    /// Every cell along with its point, a row at a time
+    pub fn iter_points(&self) -> impl Iterator<Item = (Point, &T)> {
+        return self.points().map(|p| (p, &self.cells[&p]));
+    }
+
+    /// The points above, below, left and right of `point`. There are no edges
+    /// to leave any out.
+    pub fn neighbours4(&self, point: Point) -> impl Iterator<Item = Point> {
+        return Point::DIRECTIONS.into_iter().map(move |d| point + d);
+    }
+
+    /// Like `neighbours4`, but with the diagonals as well
+    pub fn neighbours8(&self, point: Point) -> impl Iterator<Item = Point> {
+        let Point(x, y) = point;
+        return (-1..=1)
+            .flat_map(move |j| (-1..=1).map(move |i| Point(x + i, y + j)))
+            .filter(move |p| *p != point);
+    }
+
+    /// The cells in row `y`, left to right
+    pub fn row(&self, y: isize) -> impl DoubleEndedIterator<Item = (Point, &T)> {
+        let (min, max) = self.bounds.unwrap_or((Point(0, 0), Point(-1, -1)));
+        return (min.0..=max.0)
+            .filter_map(move |x| self.get(Point(x, y)).map(|t| (Point(x, y), t)));
+    }
+
+    /// The cells in column `x`, top to bottom
+    pub fn column(&self, x: isize) -> impl DoubleEndedIterator<Item = (Point, &T)> {
+        let (min, max) = self.bounds.unwrap_or((Point(0, 0), Point(-1, -1)));
+        return (min.1..=max.1)
+            .filter_map(move |y| self.get(Point(x, y)).map(|t| (Point(x, y), t)));
+    }
+
+    /// The cells passed going from `from` in steps of `step`, not including
+    /// `from` itself, until there can't be any more
+    pub fn ray(&self, from: Point, step: Point) -> impl Iterator<Item = (Point, &T)> {
+        let (min, max) = self.bounds.unwrap_or((Point(0, 0), Point(-1, -1)));
+        let inside = move |p: &Point| {
+            // heading away from the cells, or not moving at all, finds nothing
+            let x = (step.0 >= 0 || p.0 >= min.0) && (step.0 <= 0 || p.0 <= max.0);
+            let y = (step.1 >= 0 || p.1 >= min.1) && (step.1 <= 0 || p.1 <= max.1);
+            return x && y && step != Point(0, 0);
+        };
+        return std::iter::successors(Some(from), move |p| Some(*p + step))
+            .skip(1)
+            .take_while(inside)
+            .filter_map(|p| self.get(p).map(|t| (p, t)));
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> SparseGrid<U> {
+        return SparseGrid {
+            cells: self.cells.iter().map(|(p, t)| (*p, f(t))).collect(),
+            bounds: self.bounds,
+        };
+    }
+
+    /// Move every cell to a new point
+    fn moved(&self, to: impl Fn(Point) -> Point) -> Self
+    where
+        T: Clone,
+    {
+        let mut moved = Self::new();
+        for (p, t) in self.cells.iter() {
+            moved.insert(to(*p), t.clone());
+        }
+        return moved;
+    }
+
+    /// Swap `x` and `y`, so rows become columns
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        return self.moved(|Point(x, y)| Point(y, x));
+    }
+
+    /// Turn a quarter clockwise, as it's shown, about `Point(0, 0)`
+    pub fn rotate_clockwise(&self) -> Self
+    where
+        T: Clone,
+    {
+        return self.moved(|p| p.rotate_clockwise());
+    }
+
+    /// Turn a quarter anticlockwise, as it's shown, about `Point(0, 0)`
+    pub fn rotate_anticlockwise(&self) -> Self
+    where
+        T: Clone,
+    {
+        return self.moved(|p| p.rotate_anticlockwise());
+    }
+}
+
+impl<T> Index<Point> for SparseGrid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        return self
+            .get(point)
+            .unwrap_or_else(|| panic!("nothing at {:?}", point));
+    }
+}
+
+impl<T> IndexMut<Point> for SparseGrid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        return self
+            .get_mut(point)
+            .unwrap_or_else(|| panic!("nothing at {:?}", point));
+    }
+}
+
+/// The cells within the bounds, with `.` where there isn't one
+impl<T: fmt::Display> fmt::Display for SparseGrid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Some((min, max)) = self.bounds else {
+            return Ok(());
         };
+        for y in min.1..=max.1 {
+            if y != min.1 {
+                writeln!(f)?;
+            }
+            for x in min.0..=max.0 {
+                match self.get(Point(x, y)) {
+                    Some(t) => write!(f, "{}", t)?,
+                    None => write!(f, ".")?,
+                }
+            }
+        }
+        return Ok(());
+    }
+}
+
+/// A set of integers kept as sorted, separate ranges, so it takes as much
+/// memory as it has gaps however many numbers it holds.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct IntervalSet {
+    // inclusive, with at least one number missing between each
+    ranges: Vec<(isize, isize)>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    /// Add every number in `range`, merging it with any ranges it overlaps
+    /// or touches.
+    pub fn insert(&mut self, range: RangeInclusive<isize>) {
+        let (mut start, mut end) = range.into_inner();
+        if start > end {
+            return;
+        }
+        // the ranges from `first` up to `last` overlap or touch the new one,
+        // and everything either side of them is left where it is
+        let first = self
+            .ranges
+            .partition_point(|&(_a, b)| b.saturating_add(1) < start);
+        let last = self
+            .ranges
+            .partition_point(|&(a, _b)| a <= end.saturating_add(1));
+        if first < last {
+            start = start.min(self.ranges[first].0);
+            end = end.max(self.ranges[last - 1].1);
+        }
+        self.ranges.splice(first..last, [(start, end)]);
+    }
+
+    pub fn ranges(&self) -> impl DoubleEndedIterator<Item = RangeInclusive<isize>> + '_ {
+        return self.ranges.iter().map(|&(a, b)| a..=b);
+    }
+
+    /// How many numbers are in the set. A set of every `isize` has one more
+    /// than fits in a `usize`, so this stops at `usize::MAX`.
+    pub fn len(&self) -> usize {
+        return self
+            .ranges
+            .iter()
+            .map(|&(a, b)| a.abs_diff(b).saturating_add(1))
+            .fold(0, usize::saturating_add);
     }
 
-    pub fn get_value(&self, point: Point) -> &T {
-        return &self.array[(point.0 - self.min_point.0) as usize][(point.1 - self.min_point.1) as usize];
+    pub fn is_empty(&self) -> bool {
+        return self.ranges.is_empty();
+    }
+
+    pub fn contains(&self, x: isize) -> bool {
+        let i = self.ranges.partition_point(|&(_a, b)| b < x);
+        return self.ranges.get(i).is_some_and(|&(a, _b)| a <= x);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        for range in other.ranges() {
+            union.insert(range);
+        }
+        return union;
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
+        while let (Some(&(a, b)), Some(&(c, d))) = (self.ranges.get(i), other.ranges.get(j)) {
+            if a.max(c) <= b.min(d) {
+                ranges.push((a.max(c), b.min(d)));
+            }
+            if b < d {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        return Self { ranges };
+    }
+
+    /// The numbers in this set but not in `other`
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let mut j = 0;
+        for &(a, b) in self.ranges.iter() {
+            while other.ranges.get(j).is_some_and(|&(_c, d)| d < a) {
+                j += 1;
+            }
+            // whatever's left of this range after taking out the ones it
+            // overlaps, going left to right
+            let mut start = Some(a);
+            for &(c, d) in other.ranges[j..].iter().take_while(|&&(c, _d)| c <= b) {
+                let Some(s) = start else { break };
+                if s < c {
+                    ranges.push((s, c - 1));
+                }
+                start = (d < b).then(|| d + 1);
+            }
+            if let Some(s) = start {
+                ranges.push((s, b));
+            }
+        }
+        return Self { ranges };
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        return self.difference(other).is_empty();
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        return !self.intersection(other).is_empty();
+    }
+}
+
+impl From<RangeInclusive<isize>> for IntervalSet {
+    fn from(range: RangeInclusive<isize>) -> Self {
+        let mut set = Self::new();
+        set.insert(range);
+        return set;
     }
+}
 
-    pub fn set_value(&mut self, point: Point, value: T) -> () {
-        self.array[(point.0 - self.min_point.0) as usize][(point.1 - self.min_point.1) as usize] = value;
+impl FromIterator<RangeInclusive<isize>> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<isize>>>(ranges: I) -> Self {
+        let mut set = Self::new();
+        for range in ranges {
+            set.insert(range);
+        }
+        return set;
     }
 }
 
@@ -60,3 +913,245 @@ pub fn range_inclusive(x: isize, y: isize) -> Vec<isize> {
         return (y..=x).rev().collect::<Vec<isize>>();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_do_arithmetic() {
+        let (a, b) = (Point(1, 5), Point(7, -16));
+        assert_eq!(a + b, Point(8, -11));
+        assert_eq!(a - b, Point(-6, 21));
+        assert_eq!(-a * 3, Point(-3, -15));
+        assert_eq!(a.manhattan(b), 27);
+        assert_eq!(a.chebyshev(b), 21);
+        assert_eq!((b - a).signum(), Point(1, -1));
+        let mut c = a;
+        c += Point::RIGHT;
+        c -= Point::DOWN;
+        assert_eq!(c, Point(2, 4));
+        // works for other types too
+        assert_eq!(Point(3i64, -4).manhattan(Point(0, 0)), 7);
+    }
+
+    #[test]
+    fn points_turn_and_index() {
+        assert_eq!(Point::UP.rotate_clockwise(), Point::RIGHT);
+        assert_eq!(Point::RIGHT.rotate_clockwise(), Point::DOWN);
+        assert_eq!(Point::UP.rotate_anticlockwise(), Point::LEFT);
+        assert_eq!(
+            Point(2, 3).rotate_clockwise().rotate_anticlockwise(),
+            Point(2, 3)
+        );
+        assert_eq!(Point::from_index(7, 3), Point(1, 2));
+        assert_eq!(Point(1, 2).to_index(3), Some(7));
+        assert_eq!(Point(3, 2).to_index(3), None);
+        assert_eq!(Point(-1, 2).to_indices(), None);
+    }
+
+    #[test]
+    fn points_in_three_dimensions() {
+        let (a, b) = (Point3(1, 2, 3), Point3(-1, 4, 0));
+        assert_eq!(a + b, Point3(0, 6, 3));
+        assert_eq!(a - b, Point3(2, -2, 3));
+        assert_eq!(-b * 2, Point3(2, -8, 0));
+        assert_eq!(a.manhattan(b), 7);
+        assert_eq!(a.chebyshev(b), 3);
+        assert_eq!((a - b).signum(), Point3(1, -1, 1));
+        let around: Point3 = Point3::DIRECTIONS
+            .iter()
+            .fold(Point3::ORIGIN, |acc, d| acc + *d);
+        assert_eq!(around, Point3::ORIGIN);
+    }
+
+    fn letters() -> Grid<char> {
+        return Grid::from_str(0, "abc\ndef", "a letter", Some).unwrap();
+    }
+
+    #[test]
+    fn grids_are_parsed_a_row_at_a_time() {
+        let grid = letters();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get(Point(2, 0)), Some(&'c'));
+        assert_eq!(grid.get(Point(0, 1)), Some(&'d'));
+        assert_eq!(grid.get(Point(3, 0)), None);
+        assert_eq!(grid.get(Point(0, -1)), None);
+        assert_eq!(grid.to_string(), "abc\ndef");
+
+        let digits = |c: char| c.to_digit(10);
+        assert_eq!(
+            Grid::from_str(0, "12\n3x", "a digit", digits),
+            Err(Error::parse(0, 2, 2, "x", "expected a digit"))
+        );
+        // columns count chars, not bytes
+        let accented = |c: char| (c != 'x').then_some(c);
+        assert_eq!(
+            Grid::from_str(0, "éé\néx", "an accent", accented),
+            Err(Error::parse(0, 2, 2, "x", "expected an accent"))
+        );
+        assert_eq!(
+            Grid::from_str(0, "12\n3", "a digit", digits),
+            Err(Error::parse(
+                0,
+                2,
+                1,
+                "3",
+                "rows must all be the same length"
+            ))
+        );
+    }
+
+    #[test]
+    fn neighbours_stay_in_the_grid() {
+        let grid = letters();
+        let four: Vec<Point> = grid.neighbours4(Point(0, 0)).collect();
+        assert_eq!(four, vec![Point(1, 0), Point(0, 1)]);
+        let eight: Vec<Point> = grid.neighbours8(Point(1, 0)).collect();
+        assert_eq!(
+            eight,
+            vec![
+                Point(0, 0),
+                Point(2, 0),
+                Point(0, 1),
+                Point(1, 1),
+                Point(2, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn lines_through_the_grid() {
+        let grid = letters();
+        let cells = |line: &mut dyn Iterator<Item = (Point, &char)>| {
+            line.map(|(_p, c)| *c).collect::<String>()
+        };
+        assert_eq!(cells(&mut grid.row(1)), "def");
+        assert_eq!(cells(&mut grid.row(1).rev()), "fed");
+        assert_eq!(cells(&mut grid.row(2)), "");
+        assert_eq!(cells(&mut grid.column(2)), "cf");
+        assert_eq!(cells(&mut grid.ray(Point(0, 0), Point(1, 1))), "e");
+        assert_eq!(cells(&mut grid.ray(Point(2, 1), Point(-1, 0))), "ed");
+    }
+
+    #[test]
+    fn grids_can_be_turned() {
+        let grid = letters();
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf");
+        assert_eq!(grid.rotate_clockwise().to_string(), "da\neb\nfc");
+        assert_eq!(grid.rotate_anticlockwise().to_string(), "cf\nbe\nad");
+        assert_eq!(grid.rotate_clockwise().rotate_anticlockwise(), grid);
+        assert_eq!(grid.map(|c| c.to_ascii_uppercase()).to_string(), "ABC\nDEF");
+    }
+
+    #[test]
+    fn sparse_grids_grow_to_fit() {
+        let mut grid = SparseGrid::new();
+        assert_eq!(grid.bounds(), None);
+        grid.insert(Point(2, 1), 'a');
+        grid.insert(Point(-1, 3), 'b');
+        grid.insert(Point(0, 0), 'c');
+        assert_eq!(grid.bounds(), Some((Point(-1, 0), Point(2, 3))));
+        assert_eq!(grid.get(Point(1, 1)), None);
+        assert_eq!(grid.to_string(), ".c..\n...a\n....\nb...");
+        let points: Vec<Point> = grid.points().collect();
+        assert_eq!(points, vec![Point(0, 0), Point(2, 1), Point(-1, 3)]);
+
+        assert_eq!(grid.remove(Point(-1, 3)), Some('b'));
+        assert_eq!(grid.bounds(), Some((Point(0, 0), Point(2, 1))));
+        assert_eq!(grid.remove(Point(-1, 3)), None);
+        let ray: Vec<char> = grid
+            .ray(Point(-5, -5), Point(1, 1))
+            .map(|(_p, c)| *c)
+            .collect();
+        assert_eq!(ray, vec!['c']);
+        assert_eq!(
+            grid.rotate_clockwise().bounds(),
+            Some((Point(-1, 0), Point(0, 2)))
+        );
+    }
+
+    #[test]
+    fn sparse_grids_convert_to_and_from_dense() {
+        let dense = letters();
+        let sparse = SparseGrid::from_dense(&dense, |c| *c != 'e');
+        assert_eq!(sparse.len(), 5);
+        assert_eq!(sparse.to_string(), "abc\nd.f");
+        assert_eq!(sparse.to_dense('e'), dense);
+        let all = SparseGrid::from_str(0, "abc\ndef", "a letter", Some).unwrap();
+        assert_eq!(all.to_dense('?'), dense);
+    }
+
+    fn intervals(ranges: &[(isize, isize)]) -> IntervalSet {
+        return ranges.iter().map(|&(a, b)| a..=b).collect();
+    }
+
+    #[test]
+    fn intervals_merge_as_they_are_added() {
+        let set = intervals(&[(5, 8), (1, 2), (3, 3), (10, 12), (11, 20), (30, 29)]);
+        assert_eq!(set, intervals(&[(1, 3), (5, 8), (10, 20)]));
+        assert_eq!(set.len(), 3 + 4 + 11);
+        assert!(set.contains(3) && set.contains(10) && set.contains(20));
+        assert!(!set.contains(4) && !set.contains(0) && !set.contains(21));
+        assert!(IntervalSet::new().is_empty());
+        let (start, end) = (4, 3);
+        assert_eq!(IntervalSet::from(start..=end), IntervalSet::new());
+        let huge = IntervalSet::from(-5_000_000_000..=5_000_000_000);
+        assert_eq!(huge.len(), 10_000_000_001);
+        let everything = IntervalSet::from(isize::MIN..=isize::MAX);
+        assert_eq!(everything.len(), usize::MAX);
+        assert_eq!(intervals(&[(isize::MIN, -1), (0, isize::MAX)]), everything);
+    }
+
+    #[test]
+    fn inserted_intervals_merge_with_only_what_they_reach() {
+        let mut set = intervals(&[(0, 2), (10, 12), (20, 22), (30, 32)]);
+        // overlapping one range at each end, and taking in the one between
+        set.insert(11..=21);
+        assert_eq!(set, intervals(&[(0, 2), (10, 22), (30, 32)]));
+        // touching, but not overlapping, the ranges either side
+        set.insert(3..=9);
+        assert_eq!(set, intervals(&[(0, 22), (30, 32)]));
+        // inside a range, and enclosing one
+        set.insert(5..=6);
+        set.insert(25..=40);
+        assert_eq!(set, intervals(&[(0, 22), (25, 40)]));
+        // in a gap, and at either end
+        set.insert(23..=23);
+        set.insert(-5..=-2);
+        set.insert(50..=50);
+        assert_eq!(set, intervals(&[(-5, -2), (0, 23), (25, 40), (50, 50)]));
+    }
+
+    #[test]
+    fn intervals_combine() {
+        let a = intervals(&[(1, 5), (10, 15)]);
+        let b = intervals(&[(4, 11), (14, 14), (20, 25)]);
+        assert_eq!(a.union(&b), intervals(&[(1, 15), (20, 25)]));
+        assert_eq!(a.intersection(&b), intervals(&[(4, 5), (10, 11), (14, 14)]));
+        assert_eq!(a.difference(&b), intervals(&[(1, 3), (12, 13), (15, 15)]));
+        assert_eq!(b.difference(&a), intervals(&[(6, 9), (20, 25)]));
+        assert_eq!(a.difference(&a), IntervalSet::new());
+        assert!(a.overlaps(&b));
+        assert!(!a.overlaps(&intervals(&[(6, 9)])));
+        assert!(intervals(&[(2, 3), (11, 12)]).is_subset(&a));
+        assert!(!b.is_subset(&a));
+    }
+
+    #[test]
+    fn groups_are_split_lazily() {
+        let groups: Vec<Vec<u32>> = [0, 1, 2, 0, 0, 3, 0]
+            .into_iter()
+            .split_when(|n| *n == 0)
+            .collect();
+        assert_eq!(groups, vec![vec![1, 2], vec![3]]);
+        let mut endless = (1..).split_when(|n| n % 3 == 0);
+        assert_eq!(endless.next(), Some(vec![1, 2]));
+        assert_eq!(endless.next(), Some(vec![4, 5]));
+        assert_eq!(std::iter::empty::<u32>().split_when(|_n| true).next(), None);
+
+        let lines = ["", "a", "b", "", "", "c", ""];
+        let borrowed: Vec<&[&str]> = groups_by_separator(&lines, |s| s.is_empty()).collect();
+        assert_eq!(borrowed, vec![&lines[1..3], &lines[5..6]]);
+    }
+}
@@ -0,0 +1,459 @@
+use crate::utils::Point;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Terminal colours, as ANSI escape codes.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Colour {
+    Default,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Grey,
+}
+
+impl Colour {
+    const NAMES: [(&'static str, Colour); 9] = [
+        ("default", Colour::Default),
+        ("red", Colour::Red),
+        ("green", Colour::Green),
+        ("yellow", Colour::Yellow),
+        ("blue", Colour::Blue),
+        ("magenta", Colour::Magenta),
+        ("cyan", Colour::Cyan),
+        ("white", Colour::White),
+        ("grey", Colour::Grey),
+    ];
+
+    fn code(&self) -> Option<u8> {
+        return match self {
+            Self::Default => None,
+            Self::Red => Some(31),
+            Self::Green => Some(32),
+            Self::Yellow => Some(33),
+            Self::Blue => Some(34),
+            Self::Magenta => Some(35),
+            Self::Cyan => Some(36),
+            Self::White => Some(97),
+            Self::Grey => Some(90),
+        };
+    }
+}
+
+impl FromStr for Colour {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return Self::NAMES
+            .iter()
+            .find(|(name, _c)| *name == s)
+            .map(|(_name, c)| *c)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::NAMES.iter().map(|(name, _c)| *name).collect();
+                format!(
+                    "unknown colour `{}`, expected one of {}",
+                    s,
+                    names.join(", ")
+                )
+            });
+    }
+}
+
+/// How one cell is drawn.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Glyph {
+    pub ch: char,
+    pub colour: Colour,
+}
+
+impl Glyph {
+    pub const fn new(ch: char, colour: Colour) -> Self {
+        return Self { ch, colour };
+    }
+
+    /// The same colour with a different character, for cells that show their
+    /// own contents.
+    pub fn with_char(&self, ch: char) -> Self {
+        return Self { ch, ..*self };
+    }
+}
+
+/// Parse `ROLE=CHAR[:COLOUR]`, e.g. `sand=o:yellow`.
+pub fn parse_glyph(s: &str) -> Result<(String, Glyph), String> {
+    let (role, glyph) = s
+        .split_once('=')
+        .ok_or_else(|| "expected ROLE=CHAR[:COLOUR]".to_owned())?;
+    let (ch, colour) = match glyph.split_once(':') {
+        Some((ch, colour)) => (ch, colour.parse()?),
+        None => (glyph, Colour::Default),
+    };
+    let mut chars = ch.chars();
+    return match (chars.next(), chars.next()) {
+        (Some(ch), None) => Ok((role.to_owned(), Glyph::new(ch, colour))),
+        _ => Err(format!("expected a single character, got `{}`", ch)),
+    };
+}
+
+/// The part of a day's world to show, in the coordinates it's drawn in, with
+/// `y` increasing downwards.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Viewport {
+    pub left: isize,
+    pub top: isize,
+    pub right: isize,
+    pub bottom: isize,
+}
+
+impl FromStr for Viewport {
+    type Err = String;
+
+    /// `LEFT,TOP,RIGHT,BOTTOM`, all inclusive.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let numbers: Vec<isize> = s
+            .split(',')
+            .map(|x| x.trim().parse::<isize>())
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?;
+        return match numbers[..] {
+            [left, top, right, bottom] if left <= right && top <= bottom => Ok(Self {
+                left,
+                top,
+                right,
+                bottom,
+            }),
+            [_, _, _, _] => {
+                Err("the bottom right corner is above or left of the top left".to_owned())
+            }
+            _ => Err("expected LEFT,TOP,RIGHT,BOTTOM".to_owned()),
+        };
+    }
+}
+
+/// One picture of a day's world, squeezed to fit in the terminal. Every cell
+/// of the frame stands for a square block of points, so large worlds are
+/// shown scaled down.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Frame {
+    min: Point,
+    max: Point,
+    scale: isize,
+    width: usize,
+    height: usize,
+    cells: Vec<Option<Glyph>>,
+    caption: String,
+}
+
+impl Frame {
+    /// A blank frame showing `min` to `max` inclusive, in at most `size`
+    /// columns and rows.
+    pub fn new(min: Point, max: Point, size: (usize, usize)) -> Self {
+        let (columns, rows) = (size.0.max(1) as isize, size.1.max(1) as isize);
+        let (w, h) = (max.0 - min.0 + 1, max.1 - min.1 + 1);
+        let scale = [1, (w + columns - 1) / columns, (h + rows - 1) / rows]
+            .into_iter()
+            .max()
+            .unwrap();
+        let width = ((w + scale - 1) / scale).max(0) as usize;
+        let height = ((h + scale - 1) / scale).max(0) as usize;
+        return Self {
+            min,
+            max,
+            scale,
+            width,
+            height,
+            cells: vec![None; width * height],
+            caption: String::new(),
+        };
+    }
+
+    fn cell(&self, p: Point) -> Option<usize> {
+        let Point(x, y) = p;
+        if x < self.min.0 || x > self.max.0 || y < self.min.1 || y > self.max.1 {
+            return None;
+        }
+        let column = ((x - self.min.0) / self.scale) as usize;
+        let row = ((y - self.min.1) / self.scale) as usize;
+        return Some(row * self.width + column);
+    }
+
+    /// Draw one point, over whatever was drawn there before. Points outside
+    /// the frame are left out.
+    pub fn plot(&mut self, p: Point, glyph: Glyph) {
+        if let Some(i) = self.cell(p) {
+            self.cells[i] = Some(glyph);
+        }
+    }
+
+    pub fn plot_all(&mut self, points: impl IntoIterator<Item = Point>, glyph: Glyph) {
+        for p in points {
+            self.plot(p, glyph);
+        }
+    }
+
+    /// Draw every cell that `f` gives a glyph for, asking about the top left
+    /// point of the block each cell stands for. Useful when there are far
+    /// more points than cells.
+    pub fn fill(&mut self, f: impl Fn(Point) -> Option<Glyph>) {
+        for row in 0..self.height {
+            for column in 0..self.width {
+                let p = Point(
+                    self.min.0 + column as isize * self.scale,
+                    self.min.1 + row as isize * self.scale,
+                );
+                if let Some(glyph) = f(p) {
+                    self.cells[row * self.width + column] = Some(glyph);
+                }
+            }
+        }
+    }
+
+    /// How many points each cell stands for along each side.
+    pub fn scale(&self) -> isize {
+        return self.scale;
+    }
+
+    /// A line of text shown under the picture.
+    pub fn caption(&mut self, caption: &str) {
+        self.caption = caption.to_owned();
+    }
+
+    pub fn render(&self, colour: bool) -> String {
+        let mut out = String::new();
+        for row in self.cells.chunks(self.width.max(1)) {
+            for cell in row {
+                match cell {
+                    Some(g) => match g.colour.code().filter(|_c| colour) {
+                        Some(code) => out.push_str(&format!("\x1b[{}m{}\x1b[0m", code, g.ch)),
+                        None => out.push(g.ch),
+                    },
+                    None => out.push(' '),
+                }
+            }
+            out.push('\n');
+        }
+        if self.scale > 1 {
+            out.push_str(&format!("(1 cell = {0}x{0}) ", self.scale));
+        }
+        out.push_str(&self.caption);
+        out.push('\n');
+        return out;
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Options {
+    /// Frames per second. Zero shows them as fast as they come.
+    pub fps: u32,
+    /// Only show every this many steps, along with the last.
+    pub step: usize,
+    pub colour: bool,
+    /// Columns and rows available for drawing.
+    pub size: (usize, usize),
+    /// Show this part of the world rather than all of it.
+    pub viewport: Option<Viewport>,
+    /// Replacements for the glyphs the days draw with, by role.
+    pub glyphs: HashMap<String, Glyph>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        return Self {
+            fps: 30,
+            step: 1,
+            colour: true,
+            size: (100, 40),
+            viewport: None,
+            glyphs: HashMap::new(),
+        };
+    }
+}
+
+/// Draws a day's simulation frame by frame. The days ask it for a `Frame`,
+/// draw on it and hand it back to be shown.
+///
+/// Failing to write a frame doesn't stop the simulation, it only stops the
+/// drawing. The error is kept for whoever set the visualiser up.
+pub struct Visualiser {
+    options: Options,
+    out: Box<dyn Write + Send>,
+    steps: usize,
+    last_shown: Option<Instant>,
+    error: Option<io::Error>,
+}
+
+impl Visualiser {
+    pub fn new(options: Options, out: Box<dyn Write + Send>) -> Self {
+        return Self {
+            options,
+            out,
+            steps: 0,
+            last_shown: None,
+            error: None,
+        };
+    }
+
+    /// The glyph for `role`, unless it's been replaced in the options.
+    pub fn glyph(&self, role: &str, default: Glyph) -> Glyph {
+        return *self.options.glyphs.get(role).unwrap_or(&default);
+    }
+
+    /// A blank frame for a world spanning `min` to `max`, or whatever part of
+    /// it the options ask for.
+    pub fn frame(&self, min: Point, max: Point) -> Frame {
+        return match self.options.viewport {
+            Some(v) => Frame::new(
+                Point(v.left, v.top),
+                Point(v.right, v.bottom),
+                self.options.size,
+            ),
+            None => Frame::new(min, max, self.options.size),
+        };
+    }
+
+    /// One step of the simulation. `draw` is only called for the steps that
+    /// are shown, so skipped steps cost nothing.
+    pub fn step(&mut self, draw: impl FnOnce(&Self) -> Frame) {
+        let shown = self.steps.is_multiple_of(self.options.step.max(1));
+        self.steps += 1;
+        if shown && self.error.is_none() {
+            let frame = draw(self);
+            self.show(&frame);
+        }
+    }
+
+    /// The state the simulation finished in, which is always shown.
+    pub fn last(&mut self, frame: Frame) {
+        if self.error.is_none() {
+            self.show(&frame);
+        }
+    }
+
+    /// The first error writing a frame, if there was one.
+    pub fn error(&self) -> Option<&io::Error> {
+        return self.error.as_ref();
+    }
+
+    fn show(&mut self, frame: &Frame) {
+        if let (Some(last), true) = (self.last_shown, self.options.fps > 0) {
+            let interval = Duration::from_secs(1) / self.options.fps;
+            let elapsed = last.elapsed();
+            if elapsed < interval {
+                thread::sleep(interval - elapsed);
+            }
+        }
+        // back to the top left and clear, so each frame replaces the last
+        let result = write!(
+            self.out,
+            "\x1b[H\x1b[2J{}",
+            frame.render(self.options.colour)
+        )
+        .and_then(|_x| self.out.flush());
+        self.error = result.err();
+        self.last_shown = Some(Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    const WALL: Glyph = Glyph::new('#', Colour::White);
+
+    #[test]
+    fn glyphs_and_viewports_are_parsed() {
+        assert_eq!(
+            parse_glyph("sand=o:yellow"),
+            Ok(("sand".to_owned(), Glyph::new('o', Colour::Yellow)))
+        );
+        assert_eq!(
+            parse_glyph("wall=█"),
+            Ok(("wall".to_owned(), Glyph::new('█', Colour::Default)))
+        );
+        assert!(parse_glyph("wall=##").is_err());
+        assert!(parse_glyph("wall=#:purple").is_err());
+        assert_eq!(
+            "-5,0,10,20".parse(),
+            Ok(Viewport {
+                left: -5,
+                top: 0,
+                right: 10,
+                bottom: 20
+            })
+        );
+        assert!("5,0,-10,20".parse::<Viewport>().is_err());
+        assert!("1,2,3".parse::<Viewport>().is_err());
+    }
+
+    #[test]
+    fn frames_draw_points_in_place() {
+        let mut frame = Frame::new(Point(-1, 0), Point(2, 1), (10, 10));
+        frame.plot(Point(-1, 0), WALL);
+        frame.plot(Point(2, 1), Glyph::new('o', Colour::Yellow));
+        frame.plot(Point(3, 1), WALL);
+        frame.caption("step 1");
+        assert_eq!(frame.render(false), "#   \n   o\nstep 1\n");
+        assert_eq!(
+            frame.render(true),
+            "\x1b[97m#\x1b[0m   \n   \x1b[33mo\x1b[0m\nstep 1\n"
+        );
+    }
+
+    #[test]
+    fn large_worlds_are_scaled_down() {
+        let mut frame = Frame::new(Point(0, 0), Point(99, 19), (10, 10));
+        frame.fill(|Point(x, _y)| (x < 50).then_some(WALL));
+        assert_eq!(
+            frame.render(false),
+            "#####     \n#####     \n(1 cell = 10x10) \n"
+        );
+    }
+
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            return self.0.lock().unwrap().write(buf);
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            return Ok(());
+        }
+    }
+
+    #[test]
+    fn skipped_steps_are_not_drawn() {
+        let buffer = Arc::new(Mutex::new(vec![]));
+        let options = Options {
+            fps: 0,
+            step: 2,
+            colour: false,
+            ..Options::default()
+        };
+        let mut vis = Visualiser::new(options, Box::new(Shared(Arc::clone(&buffer))));
+        let mut drawn = vec![];
+        for i in 0..4 {
+            vis.step(|vis| {
+                drawn.push(i);
+                let mut frame = vis.frame(Point(0, 0), Point(0, 0));
+                frame.caption(&i.to_string());
+                return frame;
+            });
+        }
+        let mut frame = vis.frame(Point(0, 0), Point(0, 0));
+        frame.caption("done");
+        vis.last(frame);
+        assert_eq!(drawn, vec![0, 2]);
+        assert!(vis.error().is_none());
+        let out = String::from_utf8(buffer.lock().unwrap().clone()).unwrap();
+        let frames: Vec<&str> = out.split("\x1b[H\x1b[2J").skip(1).collect();
+        assert_eq!(frames, vec![" \n0\n", " \n2\n", " \ndone\n"]);
+    }
+}
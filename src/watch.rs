@@ -0,0 +1,123 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// What's known about a file without reading it. Size as well as time, as
+/// some filesystems only keep modification times to the second.
+type Stamp = Option<(SystemTime, u64)>;
+
+fn stamp(path: &Path) -> Stamp {
+    let metadata = fs::metadata(path).ok()?;
+    return Some((metadata.modified().ok()?, metadata.len()));
+}
+
+/// Notices when any of a set of files is modified, created or removed, by
+/// checking on them every so often.
+pub struct Watcher {
+    files: Vec<(PathBuf, Stamp)>,
+}
+
+impl Watcher {
+    pub fn new(paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        let mut files: Vec<(PathBuf, Stamp)> = vec![];
+        for path in paths {
+            if !files.iter().any(|(p, _s)| *p == path) {
+                let s = stamp(&path);
+                files.push((path, s));
+            }
+        }
+        return Self { files };
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        return self.files.iter().map(|(p, _s)| p.as_path());
+    }
+
+    /// The files that have changed since they were last looked at.
+    pub fn changed(&mut self) -> Vec<PathBuf> {
+        let mut changed = vec![];
+        for (path, last) in self.files.iter_mut() {
+            let now = stamp(path);
+            if now != *last {
+                *last = now;
+                changed.push(path.clone());
+            }
+        }
+        return changed;
+    }
+
+    /// Block until something changes, checking every `interval`. Editors
+    /// often save in more than one step, so this only returns once the files
+    /// have stayed the same for a whole interval.
+    pub fn wait(&mut self, interval: Duration) -> Vec<PathBuf> {
+        let mut changed: Vec<PathBuf> = vec![];
+        loop {
+            thread::sleep(interval);
+            let now = self.changed();
+            if now.is_empty() && !changed.is_empty() {
+                return changed;
+            }
+            for path in now {
+                if !changed.contains(&path) {
+                    changed.push(path);
+                }
+            }
+        }
+    }
+}
+
+/// How an answer compares with the one from the previous run.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Change {
+    /// There wasn't an answer before
+    New,
+    Unchanged,
+    Changed(String),
+}
+
+impl Change {
+    pub fn between(previous: Option<&str>, answer: &str) -> Self {
+        return match previous {
+            None => Self::New,
+            Some(x) if x == answer => Self::Unchanged,
+            Some(x) => Self::Changed(x.to_owned()),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_are_compared_with_the_previous_run() {
+        assert_eq!(Change::between(None, "24"), Change::New);
+        assert_eq!(Change::between(Some("24"), "24"), Change::Unchanged);
+        assert_eq!(
+            Change::between(Some("24"), "25"),
+            Change::Changed("24".to_owned())
+        );
+    }
+
+    #[test]
+    fn changes_are_noticed_once() {
+        let dir = std::env::temp_dir().join(format!("aoc2022-watch-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let (a, b) = (dir.join("a.txt"), dir.join("b.txt"));
+        fs::write(&a, "1").unwrap();
+        let mut watcher = Watcher::new(vec![a.clone(), b.clone(), a.clone()]);
+        assert_eq!(watcher.paths().count(), 2);
+        assert!(watcher.changed().is_empty());
+
+        fs::write(&a, "12").unwrap();
+        fs::write(&b, "created").unwrap();
+        assert_eq!(watcher.changed(), vec![a.clone(), b.clone()]);
+        assert!(watcher.changed().is_empty());
+
+        fs::remove_file(&b).unwrap();
+        assert_eq!(watcher.wait(Duration::from_millis(10)), vec![b]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
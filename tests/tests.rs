@@ -1,73 +1,86 @@
-use aoc2022;
+use aoc2022::answers::Answers;
+use aoc2022::error::Error;
+use std::fs;
 use std::include_str;
+use std::path::Path;
 
 #[test]
 fn day1_part_1_integration_test() {
     let contents = include_str!("example_files/day1.txt");
-    let result = aoc2022::day1::part_1(contents);
+    let input = aoc2022::day1::parse(contents).unwrap();
+    let result = aoc2022::day1::part_1(&input).unwrap();
     assert_eq!(result, "24000");
 }
 
 #[test]
 fn day1_part_2_integration_test() {
     let contents = include_str!("example_files/day1.txt");
-    let result = aoc2022::day1::part_2(contents);
+    let input = aoc2022::day1::parse(contents).unwrap();
+    let result = aoc2022::day1::part_2(&input).unwrap();
     assert_eq!(result, "45000");
 }
 
 #[test]
 fn day2_part_1_integration_test() {
     let contents = include_str!("example_files/day2.txt");
-    let result = aoc2022::day2::part_1(contents);
+    let input = aoc2022::day2::parse(contents).unwrap();
+    let result = aoc2022::day2::part_1(&input).unwrap();
     assert_eq!(result, "15");
 }
 
 #[test]
 fn day2_part_2_integration_test() {
     let contents = include_str!("example_files/day2.txt");
-    let result = aoc2022::day2::part_2(contents);
+    let input = aoc2022::day2::parse(contents).unwrap();
+    let result = aoc2022::day2::part_2(&input).unwrap();
     assert_eq!(result, "12");
 }
 
 #[test]
 fn day3_part_1_integration_test() {
     let contents = include_str!("example_files/day3.txt");
-    let result = aoc2022::day3::part_1(contents);
+    let input = aoc2022::day3::parse(contents).unwrap();
+    let result = aoc2022::day3::part_1(&input).unwrap();
     assert_eq!(result, "157");
 }
 
 #[test]
 fn day3_part_2_integration_test() {
     let contents = include_str!("example_files/day3.txt");
-    let result = aoc2022::day3::part_2(contents);
+    let input = aoc2022::day3::parse(contents).unwrap();
+    let result = aoc2022::day3::part_2(&input).unwrap();
     assert_eq!(result, "70");
 }
 
 #[test]
 fn day4_part_1_integration_test() {
     let contents = include_str!("example_files/day4.txt");
-    let result = aoc2022::day4::part_1(contents);
+    let input = aoc2022::day4::parse(contents).unwrap();
+    let result = aoc2022::day4::part_1(&input).unwrap();
     assert_eq!(result, "2");
 }
 
 #[test]
 fn day4_part_2_integration_test() {
     let contents = include_str!("example_files/day4.txt");
-    let result = aoc2022::day4::part_2(contents);
+    let input = aoc2022::day4::parse(contents).unwrap();
+    let result = aoc2022::day4::part_2(&input).unwrap();
     assert_eq!(result, "4");
 }
 
 #[test]
 fn day5_part_1_integration_test() {
     let contents = include_str!("example_files/day5.txt");
-    let result = aoc2022::day5::part_1(contents);
+    let input = aoc2022::day5::parse(contents).unwrap();
+    let result = aoc2022::day5::part_1(&input).unwrap();
     assert_eq!(result, "CMZ");
 }
 
 #[test]
 fn day5_part_2_integration_test() {
     let contents = include_str!("example_files/day5.txt");
-    let result = aoc2022::day5::part_2(contents);
+    let input = aoc2022::day5::parse(contents).unwrap();
+    let result = aoc2022::day5::part_2(&input).unwrap();
     assert_eq!(result, "MCD");
 }
 
@@ -76,7 +89,7 @@ fn day6_part_1_integration_test() {
     let contents = include_str!("example_files/day6.txt");
     let result: Vec<String> = contents
         .lines()
-        .map(|line| aoc2022::day6::part_1(line))
+        .map(|line| aoc2022::day6::part_1(&aoc2022::day6::parse(line).unwrap()).unwrap())
         .collect();
     assert_eq!(result, vec!["7", "5", "6", "10", "11"]);
 }
@@ -86,7 +99,7 @@ fn day6_part_2_integration_test() {
     let contents = include_str!("example_files/day6.txt");
     let result: Vec<String> = contents
         .lines()
-        .map(|line| aoc2022::day6::part_2(line))
+        .map(|line| aoc2022::day6::part_2(&aoc2022::day6::parse(line).unwrap()).unwrap())
         .collect();
     assert_eq!(result, vec!["19", "23", "23", "29", "26"]);
 }
@@ -94,56 +107,64 @@ fn day6_part_2_integration_test() {
 #[test]
 fn day7_part_1_integration_test() {
     let contents = include_str!("example_files/day7.txt");
-    let result = aoc2022::day7::part_1(contents);
+    let input = aoc2022::day7::parse(contents).unwrap();
+    let result = aoc2022::day7::part_1(&input).unwrap();
     assert_eq!(result, "95437");
 }
 
 #[test]
 fn day7_part_2_integration_test() {
     let contents = include_str!("example_files/day7.txt");
-    let result = aoc2022::day7::part_2(contents);
+    let input = aoc2022::day7::parse(contents).unwrap();
+    let result = aoc2022::day7::part_2(&input).unwrap();
     assert_eq!(result, "24933642");
 }
 
 #[test]
 fn day8_part_1_integration_test() {
     let contents = include_str!("example_files/day8.txt");
-    let result = aoc2022::day8::part_1(contents);
+    let input = aoc2022::day8::parse(contents).unwrap();
+    let result = aoc2022::day8::part_1(&input).unwrap();
     assert_eq!(result, "21");
 }
 
 #[test]
 fn day8_part_2_integration_test() {
     let contents = include_str!("example_files/day8.txt");
-    let result = aoc2022::day8::part_2(contents);
+    let input = aoc2022::day8::parse(contents).unwrap();
+    let result = aoc2022::day8::part_2(&input).unwrap();
     assert_eq!(result, "8");
 }
 
 #[test]
 fn day9_part_1_integration_test() {
     let contents = include_str!("example_files/day9_part1.txt");
-    let result = aoc2022::day9::part_1(contents);
+    let input = aoc2022::day9::parse(contents).unwrap();
+    let result = aoc2022::day9::part_1(&input).unwrap();
     assert_eq!(result, "13");
 }
 
 #[test]
 fn day9_part_2_integration_test() {
     let contents = include_str!("example_files/day9_part2.txt");
-    let result = aoc2022::day9::part_2(contents);
+    let input = aoc2022::day9::parse(contents).unwrap();
+    let result = aoc2022::day9::part_2(&input).unwrap();
     assert_eq!(result, "36");
 }
 
 #[test]
 fn day10_part_1_integration_test() {
     let contents = include_str!("example_files/day10.txt");
-    let result = aoc2022::day10::part_1(contents);
+    let input = aoc2022::day10::parse(contents).unwrap();
+    let result = aoc2022::day10::part_1(&input).unwrap();
     assert_eq!(result, "13140");
 }
 
 #[test]
 fn day10_part_2_integration_test() {
     let contents = include_str!("example_files/day10.txt");
-    let result = aoc2022::day10::part_2(contents);
+    let input = aoc2022::day10::parse(contents).unwrap();
+    let result = aoc2022::day10::part_2(&input).unwrap();
     let expected = "
 ##..##..##..##..##..##..##..##..##..##..
 ###...###...###...###...###...###...###.
@@ -157,14 +178,16 @@ fn day10_part_2_integration_test() {
 #[test]
 fn day11_part_1_integration_test() {
     let contents = include_str!("example_files/day11.txt");
-    let result = aoc2022::day11::part_1(contents);
+    let input = aoc2022::day11::parse(contents).unwrap();
+    let result = aoc2022::day11::part_1(&input).unwrap();
     assert_eq!(result, "10605");
 }
 
 #[test]
 fn day11_part_2_integration_test() {
     let contents = include_str!("example_files/day11.txt");
-    let result = aoc2022::day11::part_2(contents);
+    let input = aoc2022::day11::parse(contents).unwrap();
+    let result = aoc2022::day11::part_2(&input).unwrap();
     let expected = "2713310158";
     assert_eq!(result, expected);
 }
@@ -172,14 +195,16 @@ fn day11_part_2_integration_test() {
 #[test]
 fn day12_part_1_integration_test() {
     let contents = include_str!("example_files/day12.txt");
-    let result = aoc2022::day12::part_1(contents);
+    let input = aoc2022::day12::parse(contents).unwrap();
+    let result = aoc2022::day12::part_1(&input).unwrap();
     assert_eq!(result, "31");
 }
 
 #[test]
 fn day12_part_2_integration_test() {
     let contents = include_str!("example_files/day12.txt");
-    let result = aoc2022::day12::part_2(contents);
+    let input = aoc2022::day12::parse(contents).unwrap();
+    let result = aoc2022::day12::part_2(&input).unwrap();
     let expected = "29";
     assert_eq!(result, expected);
 }
@@ -187,14 +212,16 @@ fn day12_part_2_integration_test() {
 #[test]
 fn day13_part_1_integration_test() {
     let contents = include_str!("example_files/day13.txt");
-    let result = aoc2022::day13::part_1(contents);
+    let input = aoc2022::day13::parse(contents).unwrap();
+    let result = aoc2022::day13::part_1(&input).unwrap();
     assert_eq!(result, "13");
 }
 
 #[test]
 fn day13_part_2_integration_test() {
     let contents = include_str!("example_files/day13.txt");
-    let result = aoc2022::day13::part_2(contents);
+    let input = aoc2022::day13::parse(contents).unwrap();
+    let result = aoc2022::day13::part_2(&input).unwrap();
     let expected = "140";
     assert_eq!(result, expected);
 }
@@ -202,14 +229,16 @@ fn day13_part_2_integration_test() {
 #[test]
 fn day14_part_1_integration_test() {
     let contents = include_str!("example_files/day14.txt");
-    let result = aoc2022::day14::part_1(contents);
+    let input = aoc2022::day14::parse(contents).unwrap();
+    let result = aoc2022::day14::part_1(&input).unwrap();
     assert_eq!(result, "24");
 }
 
 #[test]
 fn day14_part_2_integration_test() {
     let contents = include_str!("example_files/day14.txt");
-    let result = aoc2022::day14::part_2(contents);
+    let input = aoc2022::day14::parse(contents).unwrap();
+    let result = aoc2022::day14::part_2(&input).unwrap();
     let expected = "93";
     assert_eq!(result, expected);
 }
@@ -217,14 +246,114 @@ fn day14_part_2_integration_test() {
 #[test]
 fn day15_part_1_integration_test() {
     let contents = include_str!("example_files/day15.txt");
-    let result = aoc2022::day15::part_1_solver(contents, 10);
+    let input = aoc2022::day15::parse(contents).unwrap();
+    let result = aoc2022::day15::part_1_solver(&input, 10).unwrap();
     assert_eq!(result, "26");
 }
 
 #[test]
 fn day15_part_2_integration_test() {
     let contents = include_str!("example_files/day15.txt");
-    let result = aoc2022::day15::part_2_solver(contents, 20);
+    let input = aoc2022::day15::parse(contents).unwrap();
+    let result = aoc2022::day15::part_2_solver(&input, 20).unwrap();
     let expected = "56000011";
     assert_eq!(result, expected);
 }
+
+#[test]
+fn registry_lists_every_day_in_order() {
+    let days: Vec<u8> = aoc2022::solutions().iter().map(|s| s.day()).collect();
+    assert_eq!(days, (1..=25).collect::<Vec<u8>>());
+}
+
+#[test]
+fn registry_runs_days_through_the_solution_trait() {
+    let contents = include_str!("example_files/day1.txt");
+    let solution = aoc2022::get_solution(1).unwrap();
+    assert_eq!(solution.title(), "Calorie Counting");
+    assert_eq!(solution.run(contents, 1), Ok("24000".to_owned()));
+    assert_eq!(solution.run(contents, 2), Ok("45000".to_owned()));
+}
+
+#[test]
+fn unimplemented_days_are_reported_instead_of_panicking() {
+    let solution = aoc2022::get_solution(25).unwrap();
+    assert!(!solution.implemented());
+    assert_eq!(solution.run("", 1), Err(Error::NotImplemented { day: 25 }));
+}
+
+#[test]
+fn bad_parts_and_mismatched_inputs_are_reported_instead_of_panicking() {
+    let contents = include_str!("example_files/day1.txt");
+    let day1 = aoc2022::get_solution(1).unwrap();
+    assert_eq!(
+        day1.run(contents, 3),
+        Err(Error::solve(1, "there is no part 3"))
+    );
+    let day2 = aoc2022::get_solution(2).unwrap();
+    let input = day1.parse(contents).unwrap();
+    assert_eq!(
+        day2.solve(&input, 1),
+        Err(Error::solve(2, "parsed input belongs to a different day"))
+    );
+}
+
+#[test]
+fn malformed_input_is_reported_with_its_position() {
+    let solution = aoc2022::get_solution(1).unwrap();
+    let expected = Error::Parse {
+        day: 1,
+        line: 3,
+        column: 1,
+        text: "12x".to_owned(),
+        message: "invalid number".to_owned(),
+    };
+    assert_eq!(solution.run("1000\n2000\n12x\n", 1), Err(expected));
+}
+
+#[test]
+fn example_answers_file_matches_every_registered_day() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/example_files");
+    let answers = Answers::load(&dir.join("answers.toml")).unwrap();
+    for solution in aoc2022::solutions().iter().filter(|s| s.implemented()) {
+        for part in [1, 2] {
+            let expected = match answers.get(solution.day(), part) {
+                Some(x) => x,
+                None => continue,
+            };
+            let path = aoc2022::example_path(&dir, solution.day(), part);
+            let contents = fs::read_to_string(path).unwrap();
+            let result = solution.run(&contents, part);
+            assert_eq!(
+                result.as_deref(),
+                Ok(expected),
+                "day {} part {}",
+                solution.day(),
+                part
+            );
+        }
+    }
+}
+
+#[test]
+fn example_answers_for_particular_files_match_them() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/example_files");
+    let answers = Answers::load(&dir.join("answers.toml")).unwrap();
+    for (day, file, part, expected) in answers.variants() {
+        let solution = aoc2022::get_solution(day).unwrap();
+        let contents = fs::read_to_string(dir.join(file)).unwrap();
+        let result = solution.run(&contents, part);
+        assert_eq!(result.as_deref(), Ok(expected), "day {} {}", day, file);
+    }
+}
+
+#[test]
+fn variant_example_files_use_their_own_answers() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/example_files");
+    let answers = Answers::load(&dir.join("answers.toml")).unwrap();
+    let big = dir.join("day9_part2.txt");
+    assert_eq!(answers.for_example(&dir, 9, 1, &big), Some("88"));
+    assert_eq!(answers.for_example(&dir, 9, 2, &big), Some("36"));
+    let other = dir.join("day1.txt");
+    assert_eq!(answers.for_example(&dir, 9, 1, &other), None);
+}